@@ -0,0 +1,348 @@
+//! Direct access to the AGC archive container
+//!
+//! The C API only exposes sequence queries. Provenance and compression
+//! parameters live in plain streams of the archive container, so they are
+//! read here without going through libagc.
+//!
+//! An archive ends with a footer listing every stream and the location of
+//! its parts, followed by the footer size as a little-endian `u64`. Integers
+//! inside the footer use AGC's variable-length encoding: one byte holding the
+//! number of value bytes, then the value big-endian.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::AgcError;
+
+/// A stream entry from the archive footer
+#[derive(Debug, Clone)]
+pub(crate) struct StreamEntry {
+    pub(crate) name: String,
+    /// `(offset, size)` of each part
+    pub(crate) parts: Vec<(u64, u64)>,
+}
+
+/// Cursor over a byte buffer using AGC's integer and string encodings
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Cursor { data, pos: 0 }
+    }
+
+    fn read_u64(&mut self) -> Result<u64, AgcError> {
+        let n = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| truncated("integer"))? as usize;
+        if n > 8 {
+            return Err(AgcError::InvalidArchive(format!("Integer of {} bytes", n)));
+        }
+        let bytes = self
+            .data
+            .get(self.pos + 1..self.pos + 1 + n)
+            .ok_or_else(|| truncated("integer"))?;
+        self.pos += 1 + n;
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn read_str(&mut self) -> Result<String, AgcError> {
+        let rest = &self.data[self.pos..];
+        let end = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| truncated("string"))?;
+        self.pos += end + 1;
+        Ok(String::from_utf8_lossy(&rest[..end]).into_owned())
+    }
+}
+
+fn truncated(what: &str) -> AgcError {
+    AgcError::InvalidArchive(format!("Truncated {} in archive footer", what))
+}
+
+/// Read the stream directory from the archive footer
+pub(crate) fn read_directory(file: &mut File) -> Result<Vec<StreamEntry>, AgcError> {
+    let file_len = file.metadata()?.len();
+    if file_len < 8 {
+        return Err(AgcError::InvalidArchive("File too small".to_string()));
+    }
+
+    let mut size_buf = [0u8; 8];
+    file.seek(SeekFrom::End(-8))?;
+    file.read_exact(&mut size_buf)?;
+    let footer_size = u64::from_le_bytes(size_buf);
+    if footer_size == 0 || footer_size > file_len - 8 {
+        return Err(AgcError::InvalidArchive(format!(
+            "Footer size {} does not fit in file of {} bytes",
+            footer_size, file_len
+        )));
+    }
+
+    let mut footer = vec![0u8; footer_size as usize];
+    file.seek(SeekFrom::Start(file_len - 8 - footer_size))?;
+    file.read_exact(&mut footer)?;
+
+    let mut cur = Cursor::new(&footer);
+    let n_streams = cur.read_u64()?;
+    let mut streams = Vec::new();
+    for _ in 0..n_streams {
+        let name = cur.read_str()?;
+        let n_parts = cur.read_u64()?;
+        let _raw_size = cur.read_u64()?;
+        let mut parts = Vec::new();
+        for _ in 0..n_parts {
+            let offset = cur.read_u64()?;
+            let size = cur.read_u64()?;
+            if offset.checked_add(size).is_none_or(|end| end > file_len) {
+                return Err(AgcError::InvalidArchive(format!(
+                    "Stream '{}' extends past end of file",
+                    name
+                )));
+            }
+            parts.push((offset, size));
+        }
+        streams.push(StreamEntry { name, parts });
+    }
+
+    Ok(streams)
+}
+
+/// Streams every AGC archive has, whatever the format version
+const REQUIRED_STREAMS: [&str; 2] = ["params", "collection-samples"];
+
+/// Magic number at the start of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Check that a file looks like an AGC archive before handing it to AGC
+///
+/// AGC archives have no leading magic number; the footer directory is the
+/// signature, so it must parse and list the streams every archive has.
+/// AGC writes the footer last, so a file whose footer does not parse but
+/// which is empty or starts like an archive body is reported as
+/// `AgcError::IncompleteArchive` rather than `AgcError::InvalidArchive`.
+pub(crate) fn validate(path: &Path) -> Result<(), AgcError> {
+    let mut file = File::open(path)?;
+    let streams = match read_directory(&mut file) {
+        Ok(streams) => streams,
+        Err(AgcError::InvalidArchive(msg)) if looks_unfinished(&mut file)? => {
+            return Err(AgcError::IncompleteArchive(format!(
+                "{}: {}",
+                path.display(),
+                msg
+            )))
+        }
+        Err(e) => return Err(e),
+    };
+
+    for name in REQUIRED_STREAMS {
+        if !streams.iter().any(|s| s.name == name) {
+            return Err(AgcError::InvalidArchive(format!(
+                "Missing '{}' stream in {}",
+                name,
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a file is empty or starts like an AGC archive body
+///
+/// The first stream part is written at offset 0 as its metadata integer
+/// followed by a zstd-compressed payload.
+fn looks_unfinished(file: &mut File) -> Result<bool, AgcError> {
+    let mut head = [0u8; 13];
+    file.seek(SeekFrom::Start(0))?;
+    let mut n = 0;
+    while n < head.len() {
+        match file.read(&mut head[n..])? {
+            0 => break,
+            read => n += read,
+        }
+    }
+    if n == 0 {
+        return Ok(true);
+    }
+
+    let meta_len = head[0] as usize;
+    Ok(meta_len <= 8
+        && head
+            .get(1 + meta_len..n)
+            .is_some_and(|payload| payload.starts_with(&ZSTD_MAGIC)))
+}
+
+/// Read the first part of a named stream
+///
+/// A part is stored as its metadata integer followed by `size` payload bytes.
+///
+/// # Returns
+/// The part metadata value and its payload, or None if the stream is absent
+fn read_first_part(
+    file: &mut File,
+    streams: &[StreamEntry],
+    name: &str,
+) -> Result<Option<(u64, Vec<u8>)>, AgcError> {
+    let entry = match streams.iter().find(|s| s.name == name) {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let (offset, size) = match entry.parts.first() {
+        Some(&part) => part,
+        None => return Ok(None),
+    };
+
+    file.seek(SeekFrom::Start(offset))?;
+    let mut n = [0u8; 1];
+    file.read_exact(&mut n)?;
+    let mut encoded = vec![n[0]; 1 + n[0] as usize];
+    file.read_exact(&mut encoded[1..])?;
+    let meta = Cursor::new(&encoded).read_u64()?;
+
+    let mut data = vec![0u8; size as usize];
+    file.read_exact(&mut data)?;
+    Ok(Some((meta, data)))
+}
+
+/// Read the key/value pairs of the `file_type_info` stream
+///
+/// Holds the producer string (`comment`), file format version and producer
+/// version. Archives written by old AGC releases may not have this stream.
+pub(crate) fn read_file_type_info(path: &Path) -> Result<Vec<(String, String)>, AgcError> {
+    let mut file = File::open(path)?;
+    let streams = read_directory(&mut file)?;
+
+    let (n_pairs, data) = match read_first_part(&mut file, &streams, "file_type_info")? {
+        Some(part) => part,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut cur = Cursor::new(&data);
+    let mut pairs = Vec::new();
+    for _ in 0..n_pairs {
+        let key = cur.read_str()?;
+        let value = cur.read_str()?;
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+/// Names of the compression parameters in `params` stream order
+const PARAM_NAMES: [&str; 4] = [
+    "kmer_length",
+    "min_match_len",
+    "pack_cardinality",
+    "segment_size",
+];
+
+/// Read the compression parameters from the `params` stream
+pub(crate) fn read_params(path: &Path) -> Result<Vec<(String, u32)>, AgcError> {
+    let mut file = File::open(path)?;
+    let streams = read_directory(&mut file)?;
+
+    let data = match read_first_part(&mut file, &streams, "params")? {
+        Some((_, data)) => data,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(PARAM_NAMES
+        .iter()
+        .zip(data.chunks_exact(4))
+        .map(|(name, b)| {
+            (
+                name.to_string(),
+                u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_read_directory() {
+        let mut file = File::open(TEST_FILE).expect("Failed to open file");
+        let streams = read_directory(&mut file).expect("Failed to read directory");
+        assert!(streams.iter().any(|s| s.name == "collection-samples"));
+        assert!(streams.iter().any(|s| s.name == "file_type_info"));
+    }
+
+    #[test]
+    fn test_validate() {
+        validate(Path::new(TEST_FILE)).expect("Fixture should validate");
+        validate(Path::new("test/data/input/toy_ex.agc")).expect("v3.0 fixture should validate");
+        assert!(matches!(
+            validate(Path::new("Cargo.toml")),
+            Err(AgcError::InvalidArchive(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_truncated() {
+        let data = std::fs::read(TEST_FILE).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        for len in [0, data.len() / 2, data.len() - 1] {
+            let path = dir.path().join(format!("truncated_{}.agc", len));
+            std::fs::write(&path, &data[..len]).unwrap();
+            assert!(
+                matches!(validate(&path), Err(AgcError::IncompleteArchive(_))),
+                "A {} byte prefix should be incomplete",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_file_type_info() {
+        let info = read_file_type_info(Path::new(TEST_FILE)).expect("Failed to read info");
+        let producer = info
+            .iter()
+            .find(|(k, _)| k == "producer")
+            .map(|(_, v)| v.as_str());
+        assert_eq!(producer, Some("agc"));
+    }
+
+    #[test]
+    fn test_read_params() {
+        let params = read_params(Path::new(TEST_FILE)).expect("Failed to read params");
+        assert_eq!(params.len(), PARAM_NAMES.len());
+        assert_eq!(params[0], ("kmer_length".to_string(), 31));
+    }
+
+    #[test]
+    fn test_read_directory_overflowing_part() {
+        // One stream whose part starts at u64::MAX, so offset + size wraps
+        let mut footer = vec![0x01, 0x01, b's', 0x00, 0x01, 0x01, 0x00, 0x08];
+        footer.extend_from_slice(&u64::MAX.to_be_bytes());
+        footer.extend_from_slice(&[0x01, 0x02]);
+        let mut data = footer.clone();
+        data.extend_from_slice(&(footer.len() as u64).to_le_bytes());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("overflow.agc");
+        std::fs::write(&path, &data).unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert!(matches!(
+            read_directory(&mut file),
+            Err(AgcError::InvalidArchive(msg)) if msg.contains("past end of file")
+        ));
+    }
+
+    #[test]
+    fn test_cursor_varint() {
+        let data = [0x02, 0x02, 0x78, 0x00, b'a', 0x00];
+        let mut cur = Cursor::new(&data);
+        assert_eq!(cur.read_u64().unwrap(), 0x0278);
+        assert_eq!(cur.read_u64().unwrap(), 0);
+        assert_eq!(cur.read_str().unwrap(), "a");
+    }
+}
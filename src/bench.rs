@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use crate::{AgcError, AgcFile};
+
+/// Result of `AgcFile::benchmark_sample`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThroughputReport {
+    /// Bases decompressed
+    pub bytes: u64,
+    /// Wall-clock time spent fetching
+    pub elapsed: Duration,
+    /// Decompression rate in MB/s (10^6 bytes per second)
+    pub mb_per_sec: f64,
+}
+
+impl ThroughputReport {
+    fn new(bytes: u64, elapsed: Duration) -> Self {
+        // A sub-nanosecond timer reading on a tiny sample would divide by zero
+        let secs = elapsed.as_secs_f64().max(1e-9);
+        ThroughputReport {
+            bytes,
+            elapsed,
+            mb_per_sec: bytes as f64 / 1e6 / secs,
+        }
+    }
+}
+
+impl AgcFile {
+    /// Measure how fast the contigs of a sample decompress
+    ///
+    /// Every contig is fetched in chunks and the bytes discarded, so memory
+    /// use stays bounded. Contig lengths are looked up (or taken from the
+    /// cache) before timing starts. Results depend on prefetching, the page
+    /// cache and, with the `lru` feature, the contig cache; run twice to see
+    /// warm numbers.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    pub fn benchmark_sample(&self, sample: &str) -> Result<ThroughputReport, AgcError> {
+        let contigs = self.list_ctg(Some(sample))?;
+        for contig in &contigs {
+            self.ctg_len(Some(sample), contig)?;
+        }
+
+        let mut bytes = 0u64;
+        let started = Instant::now();
+        for contig in &contigs {
+            for chunk in self.seq_chunks(Some(sample), contig, self.stream_chunk_len())? {
+                bytes += chunk?.len() as u64;
+            }
+        }
+        Ok(ThroughputReport::new(bytes, started.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_throughput_report() {
+        let report = ThroughputReport::new(2_000_000, Duration::from_secs(2));
+        assert_eq!(report.mb_per_sec, 1.0);
+
+        let report = ThroughputReport::new(10, Duration::ZERO);
+        assert!(report.mb_per_sec.is_finite() && report.mb_per_sec > 0.0);
+    }
+
+    #[test]
+    fn test_benchmark_sample() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let report = agc
+            .benchmark_sample(&sample)
+            .expect("Failed to benchmark sample");
+
+        let total: u64 = agc
+            .sample_rows()
+            .unwrap()
+            .into_iter()
+            .find(|row| row.name == sample)
+            .unwrap()
+            .total_length;
+        assert_eq!(report.bytes, total);
+        assert!(report.mb_per_sec > 0.0);
+    }
+}
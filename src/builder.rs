@@ -0,0 +1,288 @@
+use std::fs::File;
+
+use crate::casefold::NameFolding;
+use crate::{AgcError, AgcFile};
+
+/// Builder for opening an `AgcFile` with non-default options
+///
+/// ```no_run
+/// use libagc_sys::AgcFile;
+///
+/// let agc = AgcFile::builder("data.agc")
+///     .prefetching(true)
+///     .strict_bounds(true)
+///     .open()?;
+/// # Ok::<(), libagc_sys::AgcError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct AgcFileBuilder {
+    filename: String,
+    prefetching: bool,
+    strict_bounds: bool,
+    case_insensitive_names: bool,
+    max_fetch_len: Option<usize>,
+    file_lock: bool,
+    #[cfg(feature = "log")]
+    slow_query_threshold: Option<std::time::Duration>,
+}
+
+impl AgcFileBuilder {
+    pub(crate) fn new(filename: &str) -> Self {
+        AgcFileBuilder {
+            filename: filename.to_string(),
+            prefetching: false,
+            strict_bounds: false,
+            case_insensitive_names: false,
+            max_fetch_len: None,
+            file_lock: false,
+            #[cfg(feature = "log")]
+            slow_query_threshold: None,
+        }
+    }
+
+    /// Preload the whole file into memory (default false)
+    pub fn prefetching(mut self, prefetching: bool) -> Self {
+        self.prefetching = prefetching;
+        self
+    }
+
+    /// Reject fetches that extend past the contig end (default false)
+    ///
+    /// By default an over-long range is clipped by AGC and returns fewer
+    /// bases than asked for. In strict mode every fetch checks the range
+    /// against the contig length first, which is looked up once per contig
+    /// and cached, and fails with `AgcError::InvalidRange` instead.
+    pub fn strict_bounds(mut self, strict: bool) -> Self {
+        self.strict_bounds = strict;
+        self
+    }
+
+    /// Match sample and contig names regardless of case (default false)
+    ///
+    /// Opening lists every sample and contig once to build the lookup
+    /// tables, so `chrm` finds `chrM`. An exact match always wins; when
+    /// an archive has names that differ only by case, such as `chrM` and
+    /// `chrm`, any other casing goes to the one listed first.
+    pub fn case_insensitive_names(mut self, enabled: bool) -> Self {
+        self.case_insensitive_names = enabled;
+        self
+    }
+
+    /// Reject any single fetch spanning more than `max` bases (default
+    /// unlimited)
+    ///
+    /// Such fetches fail with `AgcError::FetchTooLarge` before a buffer is
+    /// allocated, which protects a service from a whole-genome request
+    /// running it out of memory. The limit applies to every fetch,
+    /// including the whole contigs read by FASTA export and, with the
+    /// `lru` feature, the contig cache, which fetches regions of longer
+    /// contigs directly instead of caching them. Methods that stream a
+    /// contig fetch it in chunks of a megabase or `max`, if smaller.
+    pub fn max_fetch_len(mut self, max: usize) -> Self {
+        self.max_fetch_len = Some(max);
+        self
+    }
+
+    /// Hold a shared advisory lock on the archive file (default false)
+    ///
+    /// Opening waits until no process holds an exclusive lock on the file,
+    /// then keeps a shared lock until the `AgcFile` is dropped. A tool
+    /// regenerating archives in place can take an exclusive lock before
+    /// rewriting, which waits for readers to finish and keeps new readers
+    /// from seeing a half-written file. The lock is advisory: it only
+    /// guards against writers that also lock, and on some network file
+    /// systems it may not be honoured at all.
+    pub fn with_file_lock(mut self, enabled: bool) -> Self {
+        self.file_lock = enabled;
+        self
+    }
+
+    /// Log a warning for every AGC call at least this slow (default off)
+    ///
+    /// *Requires the `log` feature.* Applies to the contig length and
+    /// sequence lookups behind `get_ctg_len` and `get_ctg_seq` and their
+    /// variants. Each warning goes to the `libagc_sys::slow_query` log
+    /// target and names the sample, contig and, for sequence fetches, the
+    /// range, which helps find pathologically slow regions.
+    #[cfg(feature = "log")]
+    pub fn slow_query_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// Open the archive
+    pub fn open(self) -> Result<AgcFile, AgcError> {
+        // Locked before AGC reads the file, so a writer holding an
+        // exclusive lock finishes first
+        let file_lock = if self.file_lock {
+            let file = File::open(&self.filename)?;
+            file.lock_shared()?;
+            Some(file)
+        } else {
+            None
+        };
+        let mut agc = AgcFile::open(&self.filename, self.prefetching)?;
+        agc.file_lock = file_lock;
+        agc.strict_bounds = self.strict_bounds;
+        agc.max_fetch_len = self.max_fetch_len;
+        #[cfg(feature = "log")]
+        {
+            agc.slow_query_threshold = self.slow_query_threshold;
+        }
+        if self.case_insensitive_names {
+            agc.name_folding = Some(NameFolding::build(&agc)?);
+        }
+        Ok(agc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_strict_bounds() {
+        let lax = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .open()
+            .expect("Failed to open file");
+        let sample = lax
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = lax.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = lax.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        let clipped = lax
+            .get_ctg_seq(Some(&sample), &contig, 0, len + 10)
+            .expect("Lax mode should clip");
+        assert_eq!(clipped.len(), len as usize);
+
+        let strict = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .strict_bounds(true)
+            .open()
+            .expect("Failed to open file");
+        assert!(strict
+            .get_ctg_seq(Some(&sample), &contig, 0, len + 10)
+            .is_err());
+        assert!(matches!(
+            strict.get_ctg_seq_shared(Some(&sample), &contig, 0, len + 10),
+            Err(AgcError::InvalidRange { .. })
+        ));
+        assert_eq!(
+            strict.get_ctg_seq(Some(&sample), &contig, 0, len).unwrap(),
+            clipped
+        );
+    }
+
+    #[test]
+    fn test_with_file_lock() {
+        let open = || {
+            AgcFile::builder(TEST_FILE)
+                .with_file_lock(true)
+                .open()
+                .expect("Failed to open file")
+        };
+        let first = open();
+        let second = open();
+        assert!(!first.list_sample().unwrap().is_empty());
+
+        // While readers hold the shared lock a writer cannot lock exclusively
+        let writer = File::open(TEST_FILE).unwrap();
+        assert!(writer.try_lock().is_err());
+        drop(first);
+        assert!(writer.try_lock().is_err());
+        drop(second);
+        writer
+            .try_lock()
+            .expect("Dropping the readers should release the lock");
+    }
+
+    #[test]
+    fn test_max_fetch_len() {
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(10)
+            .open()
+            .expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        assert_eq!(
+            agc.get_ctg_seq(Some(&sample), &contig, 5, 15)
+                .unwrap()
+                .len(),
+            10
+        );
+        assert!(matches!(
+            agc.get_ctg_seq_shared(Some(&sample), &contig, 5, 16),
+            Err(AgcError::FetchTooLarge {
+                requested: 11,
+                max: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_max_fetch_len_streaming() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(4)
+            .open()
+            .expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        // Streaming methods fetch chunks within the limit
+        assert_eq!(
+            agc.minimizer_sketch(Some(&sample), &contig, 5, 3).unwrap(),
+            plain
+                .minimizer_sketch(Some(&sample), &contig, 5, 3)
+                .unwrap()
+        );
+        assert_eq!(
+            agc.get_region_downsampled(Some(&sample), &contig, 0, len, 3)
+                .unwrap(),
+            plain
+                .get_region_downsampled(Some(&sample), &contig, 0, len, 3)
+                .unwrap()
+        );
+        assert_eq!(
+            agc.telomere_report(&sample, crate::VERTEBRATE_TELOMERE)
+                .unwrap(),
+            plain
+                .telomere_report(&sample, crate::VERTEBRATE_TELOMERE)
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "lru")]
+    #[test]
+    fn test_max_fetch_len_contig_cache() {
+        use std::sync::atomic::Ordering;
+
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(10)
+            .open()
+            .expect("Failed to open file")
+            .with_contig_cache(2);
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        assert!(agc.get_ctg_len(Some(&sample), &contig).unwrap() > 10);
+
+        // A contig over the limit is not loaded whole, so each fetch goes
+        // to AGC for just its span
+        let first = agc.get_ctg_seq(Some(&sample), &contig, 0, 5).unwrap();
+        assert_eq!(first.len(), 5);
+        assert_eq!(agc.ffi_seq_calls.load(Ordering::Relaxed), 1);
+        let again = agc.get_ctg_seq(Some(&sample), &contig, 0, 5).unwrap();
+        assert_eq!(again, first);
+        assert_eq!(agc.ffi_seq_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(agc.last_fetch_info().map(|info| info.requested), Some(5));
+    }
+}
@@ -0,0 +1,101 @@
+//! Full-contig LRU cache, enabled by the `lru` feature
+
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
+use crate::{AgcError, AgcFile};
+
+/// Full contig sequences keyed by (sample, contig)
+pub(crate) type ContigCache = Mutex<LruCache<(Option<String>, String), Arc<[u8]>>>;
+
+impl AgcFile {
+    /// Cache up to `capacity` full contigs for region fetches
+    ///
+    /// The first fetch from a contig decompresses the whole contig; later
+    /// fetches from it are sliced from memory until it is evicted. This
+    /// suits repeated queries into a few contigs, e.g. a genome browser
+    /// panning along a chromosome, at the cost of holding `capacity` full
+    /// contigs in memory. A capacity of 0 disables the cache.
+    ///
+    /// # Arguments
+    /// * `capacity` - maximum number of cached contigs
+    pub fn with_contig_cache(mut self, capacity: usize) -> Self {
+        self.contig_cache = NonZeroUsize::new(capacity).map(|c| Mutex::new(LruCache::new(c)));
+        self
+    }
+
+    /// Get `[start, end)` of a contig from the cache, loading the contig on a miss
+    ///
+    /// The range is clipped to the contig, as AGC does. A contig longer
+    /// than `max_fetch_len` is never loaded whole: the range is fetched
+    /// directly and not cached.
+    pub(crate) fn cached_seq_bytes(
+        &self,
+        cache: &ContigCache,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<Vec<u8>, AgcError> {
+        let key = (sample.map(str::to_string), name.to_string());
+        let cached = cache.lock().unwrap().get(&key).cloned();
+        let seq = match cached {
+            Some(seq) => seq,
+            None => {
+                let len = self.resolved_ctg_len(sample, name)?;
+                if self.max_fetch_len.is_some_and(|max| len as usize > max) {
+                    return self.ffi_seq_bytes(sample, name, start, end);
+                }
+                let seq: Arc<[u8]> = Arc::from(self.ffi_seq_bytes(sample, name, 0, len)?);
+                cache.lock().unwrap().put(key, Arc::clone(&seq));
+                seq
+            }
+        };
+
+        let end = (end as usize).min(seq.len());
+        let start = (start as usize).min(end);
+        Ok(seq[start..end].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_contig_cache_avoids_ffi() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let agc = AgcFile::open(TEST_FILE, true)
+            .expect("Failed to open file")
+            .with_contig_cache(2);
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        let first = agc.get_ctg_seq(Some(&sample), &contig, 0, 5).unwrap();
+        let calls = agc.ffi_seq_calls.load(Ordering::Relaxed);
+        assert_eq!(calls, 1);
+
+        let second = agc.get_ctg_seq(Some(&sample), &contig, 2, 8).unwrap();
+        assert_eq!(
+            agc.ffi_seq_calls.load(Ordering::Relaxed),
+            calls,
+            "A cached contig should not be fetched again"
+        );
+
+        assert_eq!(
+            first,
+            plain.get_ctg_seq(Some(&sample), &contig, 0, 5).unwrap()
+        );
+        assert_eq!(
+            second,
+            plain.get_ctg_seq(Some(&sample), &contig, 2, 8).unwrap()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{AgcError, AgcFile};
+
+/// Case-insensitive lookup tables for sample and contig names
+#[derive(Debug, Default, Clone)]
+pub(crate) struct NameFolding {
+    samples: FoldTable,
+    /// Contig names per archive sample name
+    contigs: HashMap<String, FoldTable>,
+    /// Contig names across all samples, for queries without a sample
+    any_contigs: FoldTable,
+}
+
+/// Names of one kind, looked up exactly and then lowercased
+///
+/// Among names differing only by case, the lowercased entry goes to the
+/// one listed first.
+#[derive(Debug, Default, Clone)]
+struct FoldTable {
+    exact: HashSet<String>,
+    folded: HashMap<String, String>,
+}
+
+impl NameFolding {
+    /// List every sample and contig of an archive and build the tables
+    pub(crate) fn build(agc: &AgcFile) -> Result<Self, AgcError> {
+        let samples = agc.list_sample()?;
+        let mut folding = NameFolding {
+            samples: FoldTable::new(&samples),
+            ..Default::default()
+        };
+
+        let mut all_contigs = Vec::new();
+        for sample in samples {
+            let contigs = agc.list_ctg(Some(&sample))?;
+            folding.contigs.insert(sample, FoldTable::new(&contigs));
+            all_contigs.extend(contigs);
+        }
+        folding.any_contigs = FoldTable::new(&all_contigs);
+        Ok(folding)
+    }
+
+    /// Translate a sample name to the archive's casing, if known
+    pub(crate) fn sample<'a>(&'a self, sample: &'a str) -> &'a str {
+        self.samples.lookup(sample).unwrap_or(sample)
+    }
+
+    /// Translate a contig name to the archive's casing, if known
+    ///
+    /// `sample` must already be the archive's sample name.
+    pub(crate) fn contig<'a>(&'a self, sample: Option<&str>, name: &'a str) -> &'a str {
+        let table = match sample {
+            Some(sample) => self.contigs.get(sample),
+            None => Some(&self.any_contigs),
+        };
+        table.and_then(|t| t.lookup(name)).unwrap_or(name)
+    }
+}
+
+impl FoldTable {
+    fn new(names: &[String]) -> Self {
+        let mut table = FoldTable::default();
+        for name in names {
+            table.exact.insert(name.clone());
+            table
+                .folded
+                .entry(name.to_lowercase())
+                .or_insert_with(|| name.clone());
+        }
+        table
+    }
+
+    /// The archive's name for `name`, or None if no name matches
+    fn lookup<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        if self.exact.contains(name) {
+            return Some(name);
+        }
+        self.folded.get(&name.to_lowercase()).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_fold_table_precedence() {
+        let names = ["chrM".to_string(), "chrm".to_string(), "chr1".to_string()];
+        let table = FoldTable::new(&names);
+        assert_eq!(table.lookup("chrM"), Some("chrM"));
+        assert_eq!(table.lookup("chrm"), Some("chrm"));
+        assert_eq!(table.lookup("CHRM"), Some("chrM"));
+        assert_eq!(table.lookup("ChrM"), Some("chrM"));
+        assert_eq!(table.lookup("CHR1"), Some("chr1"));
+        assert_eq!(table.lookup("chr2"), None);
+    }
+
+    #[test]
+    fn test_case_insensitive_names() {
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .case_insensitive_names(true)
+            .open()
+            .expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let expected = agc.get_ctg_seq(Some(&sample), &contig, 0, 10).unwrap();
+
+        let (upper_sample, upper_contig) = (sample.to_uppercase(), contig.to_uppercase());
+        assert_eq!(
+            agc.get_ctg_seq(Some(&upper_sample), &upper_contig, 0, 10)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            agc.get_ctg_len(None, &format!("{}#{}", upper_sample, upper_contig))
+                .unwrap(),
+            agc.get_ctg_len(Some(&sample), &contig).unwrap()
+        );
+        assert_eq!(
+            agc.n_ctg(&upper_sample).unwrap(),
+            agc.n_ctg(&sample).unwrap()
+        );
+
+        let exact = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        if upper_contig != contig {
+            assert!(exact.get_ctg_len(Some(&sample), &upper_contig).is_err());
+        }
+    }
+}
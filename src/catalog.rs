@@ -0,0 +1,195 @@
+use std::io;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::seq::BaseCounts;
+use crate::{AgcError, AgcFile};
+
+/// Tables and indices of a catalog database
+const CATALOG_SCHEMA: &str = "
+CREATE TABLE archive (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE samples (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    is_reference INTEGER NOT NULL,
+    n_contigs INTEGER NOT NULL,
+    total_length INTEGER NOT NULL
+);
+CREATE TABLE contigs (
+    sample_id INTEGER NOT NULL REFERENCES samples(id),
+    name TEXT NOT NULL,
+    length INTEGER NOT NULL,
+    gc REAL,
+    n_fraction REAL,
+    PRIMARY KEY (sample_id, name)
+);
+CREATE INDEX contigs_name ON contigs(name);
+";
+
+fn db_error(e: rusqlite::Error) -> AgcError {
+    AgcError::Io(io::Error::other(format!("SQLite error: {}", e)))
+}
+
+impl AgcFile {
+    /// Write the archive's samples, contigs and summary to a SQLite file
+    ///
+    /// *Requires the `sqlite` feature.* The database gets three tables:
+    /// `archive` with `key`/`value` rows for the path, archive id,
+    /// reference and counts; `samples` with one row per sample; and
+    /// `contigs` with each contig's length, G+C fraction of its A/C/G/T
+    /// bases and `n_fraction` of `N` bases, keyed by `sample_id` and `name` and
+    /// indexed on `name` for lookups across samples. Fractions are NULL
+    /// where undefined. Every contig is streamed once, and everything is
+    /// written in one transaction, so a failed export leaves no tables.
+    ///
+    /// # Arguments
+    /// * `db_path` - database file; created if missing, and must not
+    ///   already hold the catalog tables
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn export_catalog_db(&self, db_path: impl AsRef<Path>) -> Result<(), AgcError> {
+        let mut db = Connection::open(db_path).map_err(db_error)?;
+        let tx = db.transaction().map_err(db_error)?;
+        tx.execute_batch(CATALOG_SCHEMA).map_err(db_error)?;
+
+        let rows = self.sample_rows()?;
+        let reference = self.reference_sample()?;
+        let n_contigs: usize = rows.iter().map(|row| row.n_contigs).sum();
+        let summary = [
+            ("path", self.path.display().to_string()),
+            ("archive_id", self.archive_id()?),
+            ("reference", reference),
+            ("n_samples", rows.len().to_string()),
+            ("n_contigs", n_contigs.to_string()),
+        ];
+        for (key, value) in summary {
+            tx.execute(
+                "INSERT INTO archive (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .map_err(db_error)?;
+        }
+
+        {
+            let mut insert_sample = tx
+                .prepare(
+                    "INSERT INTO samples (id, name, is_reference, n_contigs, total_length)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(db_error)?;
+            let mut insert_contig = tx
+                .prepare(
+                    "INSERT INTO contigs (sample_id, name, length, gc, n_fraction)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(db_error)?;
+
+            for (id, row) in rows.iter().enumerate() {
+                let id = id as i64 + 1;
+                insert_sample
+                    .execute(params![
+                        id,
+                        row.name,
+                        row.is_reference,
+                        row.n_contigs as i64,
+                        row.total_length as i64
+                    ])
+                    .map_err(db_error)?;
+
+                for contig in self.list_ctg(Some(&row.name))? {
+                    let mut counts = BaseCounts::default();
+                    for chunk in
+                        self.seq_chunks(Some(&row.name), &contig, self.stream_chunk_len())?
+                    {
+                        counts.add(&chunk?);
+                    }
+                    let n_fraction =
+                        (counts.length > 0).then(|| counts.n as f64 / counts.length as f64);
+                    insert_contig
+                        .execute(params![
+                            id,
+                            contig,
+                            counts.length as i64,
+                            counts.gc_content(),
+                            n_fraction
+                        ])
+                        .map_err(db_error)?;
+                }
+            }
+        }
+        tx.commit().map_err(db_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_export_catalog_db() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catalog.db");
+        agc.export_catalog_db(&path)
+            .expect("Failed to export catalog");
+
+        let db = Connection::open(&path).unwrap();
+        let count = |table: &str| -> i64 {
+            db.query_row(
+                &format!("SELECT COUNT(*) FROM {}", table),
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(count("samples"), agc.n_sample() as i64);
+        assert_eq!(count("contigs"), agc.total_n_ctg().unwrap() as i64);
+        assert_eq!(count("archive"), 5);
+
+        let reference = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&reference)).unwrap().remove(0);
+        let length: i64 = db
+            .query_row(
+                "SELECT c.length FROM contigs c JOIN samples s ON s.id = c.sample_id
+                 WHERE s.name = ?1 AND c.name = ?2",
+                params![reference, contig],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            length,
+            agc.get_ctg_len(Some(&reference), &contig).unwrap() as i64
+        );
+        for contig in agc.list_ctg(Some(&reference)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&reference), &contig).unwrap();
+            let n = seq.iter().filter(|b| b.eq_ignore_ascii_case(&b'N')).count();
+            let n_fraction: f64 = db
+                .query_row(
+                    "SELECT c.n_fraction FROM contigs c JOIN samples s ON s.id = c.sample_id
+                     WHERE s.name = ?1 AND c.name = ?2",
+                    params![reference, contig],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!((n_fraction - n as f64 / seq.len() as f64).abs() < 1e-12);
+        }
+        let stored: String = db
+            .query_row(
+                "SELECT value FROM archive WHERE key = 'reference'",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored, reference);
+
+        // The catalog tables exist, so a second export fails
+        assert!(agc.export_catalog_db(&path).is_err());
+    }
+}
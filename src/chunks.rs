@@ -0,0 +1,737 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::vec;
+
+use crate::{AgcError, AgcFile};
+
+/// Lazy iterator over fixed-size chunks of a contig
+///
+/// Chunk `i` covers `[i * step, i * step + chunk_len)`, clipped to the
+/// contig, where `step = chunk_len - overlap`. The last chunk always ends
+/// at the contig end and may be shorter. Created by `AgcFile::seq_chunks`
+/// and `AgcFile::seq_chunks_overlapping`.
+pub struct SeqChunks<'a> {
+    agc: &'a AgcFile,
+    sample: Option<String>,
+    name: String,
+    len: i32,
+    chunk_len: i32,
+    step: i32,
+    /// Start of the next chunk, or None once the contig end was reached
+    next: Option<i32>,
+}
+
+impl Iterator for SeqChunks<'_> {
+    type Item = Result<Vec<u8>, AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next?;
+        let end = start.saturating_add(self.chunk_len).min(self.len);
+        self.next = if end < self.len {
+            Some(start + self.step)
+        } else {
+            None
+        };
+
+        Some(
+            self.agc
+                .ctg_seq_bytes(self.sample.as_deref(), &self.name, start, end),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = match self.next {
+            None => 0,
+            Some(start) => {
+                let rest = (self.len - start - self.chunk_len).max(0) as usize;
+                1 + rest.div_ceil(self.step as usize)
+            }
+        };
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for SeqChunks<'_> {}
+
+/// Lazy iterator over fixed windows across every contig of a sample
+///
+/// Yields `(contig, start, end, seq)` with 0-based, half-open coordinates.
+/// Windows start every `step` bases; the window reaching the contig end is
+/// shortened to fit and is the last one for that contig. Created by
+/// `AgcFile::tiled_windows`.
+pub struct TiledWindows<'a> {
+    agc: &'a AgcFile,
+    sample: String,
+    names: vec::IntoIter<String>,
+    window: i32,
+    step: i32,
+    /// Contig being tiled: name, length and start of its next window
+    current: Option<(String, i32, i32)>,
+    /// Error to report before anything else, from setting up the iterator
+    error: Option<AgcError>,
+}
+
+impl Iterator for TiledWindows<'_> {
+    type Item = Result<(String, i32, i32, Vec<u8>), AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        loop {
+            if let Some((name, len, next)) = &mut self.current {
+                if *next < *len {
+                    let start = *next;
+                    let end = start.saturating_add(self.window).min(*len);
+                    *next = if end == *len { end } else { start + self.step };
+
+                    let name = name.clone();
+                    return Some(
+                        self.agc
+                            .ctg_seq_bytes(Some(&self.sample), &name, start, end)
+                            .map(|seq| (name, start, end, seq)),
+                    );
+                }
+            }
+
+            let name = self.names.next()?;
+            match self.agc.ctg_len(Some(&self.sample), &name) {
+                Ok(len) => self.current = Some((name, len, 0)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Lazy iterator over the same contig of two samples in lockstep
+///
+/// Yields `(a, b)` chunks covering the same `[start, start + chunk_len)`
+/// of each sample, and stops at the end of the shorter contig. Created by
+/// `AgcFile::zip_contig`.
+pub struct ZippedChunks<'a> {
+    agc: &'a AgcFile,
+    contig: String,
+    a: String,
+    b: String,
+    /// Contig length in each sample, zero if setup failed
+    lens: (i32, i32),
+    chunk_len: i32,
+    /// Start of the next chunk pair
+    next: i32,
+    /// Error to report before anything else, from setting up the iterator
+    error: Option<AgcError>,
+}
+
+impl ZippedChunks<'_> {
+    /// Length of the contig in the first and in the second sample
+    pub fn lengths(&self) -> (i32, i32) {
+        self.lens
+    }
+
+    /// Whether the contig lengths differ, so the longer contig's tail
+    /// beyond the shorter one is not yielded
+    pub fn is_truncated(&self) -> bool {
+        self.lens.0 != self.lens.1
+    }
+}
+
+impl Iterator for ZippedChunks<'_> {
+    type Item = Result<(Vec<u8>, Vec<u8>), AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        let len = self.lens.0.min(self.lens.1);
+        if self.next >= len {
+            return None;
+        }
+        let start = self.next;
+        let end = start.saturating_add(self.chunk_len).min(len);
+        self.next = end;
+
+        let fetch = |sample: &str| {
+            self.agc
+                .ctg_seq_bytes(Some(sample), &self.contig, start, end)
+        };
+        Some(fetch(&self.a).and_then(|a| Ok((a, fetch(&self.b)?))))
+    }
+}
+
+impl AgcFile {
+    /// Iterate over fixed windows across every contig of a sample
+    ///
+    /// With `step == window` the windows tile each contig; a smaller step
+    /// gives overlapping windows and a larger one leaves gaps. Only one
+    /// window is held in memory at a time. The iterator borrows `self` and
+    /// cannot outlive it.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `window` - window length, at least 1
+    /// * `step` - distance between window starts, at least 1
+    ///
+    /// # Returns
+    /// An iterator of `(contig, start, end, seq)`; setup errors, such as an
+    /// invalid window or unknown sample, are its first item
+    pub fn tiled_windows(&self, sample: &str, window: i32, step: i32) -> TiledWindows<'_> {
+        let names = if window < 1 || step < 1 {
+            Err(AgcError::InvalidArgument(format!(
+                "window {} and step {} must be at least 1",
+                window, step
+            )))
+        } else {
+            self.list_ctg(Some(sample)).map_err(AgcError::from)
+        };
+        let (names, error) = match names {
+            Ok(names) => (names, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+
+        TiledWindows {
+            agc: self,
+            sample: sample.to_string(),
+            names: names.into_iter(),
+            window,
+            step,
+            current: None,
+            error,
+        }
+    }
+
+    /// Stream the same contig of two samples side by side
+    ///
+    /// Each item holds the same range of both samples, so the contigs can
+    /// be compared at genome scale with only one chunk of each in memory.
+    /// When the lengths differ, iteration stops at the end of the shorter
+    /// contig and `ZippedChunks::is_truncated` is set.
+    ///
+    /// # Arguments
+    /// * `contig` - contig name, in both samples
+    /// * `a` - first sample
+    /// * `b` - second sample
+    /// * `chunk_len` - bases per chunk, at least 1
+    ///
+    /// # Returns
+    /// An iterator of `(a, b)` chunk pairs; setup errors, such as a contig
+    /// missing from either sample, are its first item
+    pub fn zip_contig(&self, contig: &str, a: &str, b: &str, chunk_len: usize) -> ZippedChunks<'_> {
+        let lens = if chunk_len == 0 {
+            Err(AgcError::InvalidArgument(
+                "chunk length must be at least 1".to_string(),
+            ))
+        } else {
+            self.ctg_len(Some(a), contig)
+                .and_then(|len_a| Ok((len_a, self.ctg_len(Some(b), contig)?)))
+        };
+        let (lens, error) = match lens {
+            Ok(lens) => (lens, None),
+            Err(e) => ((0, 0), Some(e)),
+        };
+
+        ZippedChunks {
+            agc: self,
+            contig: contig.to_string(),
+            a: a.to_string(),
+            b: b.to_string(),
+            lens,
+            chunk_len: chunk_len.min(i32::MAX as usize) as i32,
+            next: 0,
+            error,
+        }
+    }
+
+    /// Iterate over consecutive chunks of a contig
+    ///
+    /// Only one chunk is held in memory at a time. The iterator borrows
+    /// `self` and cannot outlive it.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `chunk_len` - bases per chunk, at least 1 and no more than the
+    ///   handle's `max_fetch_len`, if set
+    pub fn seq_chunks(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        chunk_len: usize,
+    ) -> Result<SeqChunks<'_>, AgcError> {
+        self.seq_chunks_overlapping(sample, name, chunk_len, 0)
+    }
+
+    /// Iterate over chunks of a contig that share `overlap` bases
+    ///
+    /// Each chunk starts with the last `overlap` bases of the previous one,
+    /// so a match of up to `overlap + 1` bases straddling a boundary lies
+    /// wholly within some chunk. Like [`AgcFile::seq_chunks`], the iterator
+    /// borrows `self`.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `chunk_len` - bases per chunk, at least 1
+    /// * `overlap` - bases shared by successive chunks, less than `chunk_len`
+    pub fn seq_chunks_overlapping(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        chunk_len: usize,
+        overlap: usize,
+    ) -> Result<SeqChunks<'_>, AgcError> {
+        if chunk_len == 0 || overlap >= chunk_len {
+            return Err(AgcError::InvalidArgument(format!(
+                "chunk length {} must be at least 1 and greater than overlap {}",
+                chunk_len, overlap
+            )));
+        }
+
+        let len = self.ctg_len(sample, name)?;
+        Ok(SeqChunks {
+            agc: self,
+            sample: sample.map(str::to_string),
+            name: name.to_string(),
+            len,
+            chunk_len: chunk_len.min(i32::MAX as usize) as i32,
+            step: (chunk_len - overlap).min(i32::MAX as usize) as i32,
+            next: if len > 0 { Some(0) } else { None },
+        })
+    }
+
+    /// Get `[start, end)` of a contig, giving up if `cancel` is set
+    ///
+    /// The region is fetched in chunks of about a megabase and `cancel` is
+    /// checked before each one. A single AGC call cannot be interrupted, so
+    /// setting the flag takes effect at the next chunk boundary, and a
+    /// region within one chunk is fetched whole once started.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `cancel` - set from another thread to abandon the fetch
+    ///
+    /// # Returns
+    /// Result containing the sequence, or `AgcError::Cancelled` if the flag
+    /// was set before the last chunk
+    pub fn get_ctg_seq_cancellable(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        cancel: &AtomicBool,
+    ) -> Result<Vec<u8>, AgcError> {
+        self.ctg_seq_bytes_until(sample, name, start, end, self.stream_chunk_len(), || {
+            cancel.load(Ordering::Relaxed)
+        })
+    }
+
+    /// Get every `stride`-th base of `[start, end)`
+    ///
+    /// The bases at `start`, `start + stride`, ... are kept, for zoomed-out
+    /// overviews and coarse tracks. The region is fetched in chunks of
+    /// about a megabase, so the full-resolution region is never held at
+    /// once. As with `get_ctg_seq`, a range past the contig end is clipped
+    /// unless strict bounds are enabled.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `stride` - distance between kept bases, at least 1
+    ///
+    /// # Returns
+    /// Result containing `ceil((end - start) / stride)` bases for a range
+    /// within the contig
+    pub fn get_region_downsampled(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        stride: usize,
+    ) -> Result<Vec<u8>, AgcError> {
+        self.downsampled_chunked(sample, name, start, end, stride, self.stream_chunk_len())
+    }
+
+    /// `get_region_downsampled` with chunks of about `chunk_len` bases
+    fn downsampled_chunked(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        stride: usize,
+        chunk_len: usize,
+    ) -> Result<Vec<u8>, AgcError> {
+        if stride == 0 {
+            return Err(AgcError::InvalidArgument(
+                "stride must be at least 1".to_string(),
+            ));
+        }
+
+        // Chunks span whole strides, so each starts on a kept base
+        let stride = stride.min(i32::MAX as usize);
+        let chunk_len = (chunk_len / stride).max(1) * stride;
+        let kept = self.map_seq(sample, name, start, end, chunk_len, |chunk| {
+            chunk.iter().step_by(stride).copied().collect::<Vec<u8>>()
+        })?;
+        Ok(kept.concat())
+    }
+
+    /// Run a function over `[start, end)` chunk by chunk
+    ///
+    /// `f` sees consecutive, non-overlapping chunks of `chunk_len` bases in
+    /// order, the last one possibly shorter, so only one chunk is held at a
+    /// time. Results depend on where chunks are cut: a computation looking
+    /// across bases, such as a k-mer count or rolling hash, misses the
+    /// windows spanning a boundary unless `f` carries state between calls.
+    /// A range past the contig end is clipped unless strict bounds are
+    /// enabled, and an empty range calls `f` on nothing.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `chunk_len` - bases per chunk, at least 1
+    /// * `f` - function applied to each chunk
+    ///
+    /// # Returns
+    /// Result containing `f`'s result for each chunk, in order
+    pub fn map_seq<F, T>(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        chunk_len: usize,
+        mut f: F,
+    ) -> Result<Vec<T>, AgcError>
+    where
+        F: FnMut(&[u8]) -> T,
+    {
+        if chunk_len == 0 {
+            return Err(AgcError::InvalidArgument(
+                "chunk length must be at least 1".to_string(),
+            ));
+        }
+        if start < 0 || end < start {
+            return self
+                .ctg_seq_bytes(sample, name, start, end)
+                .map(|seq| vec![f(&seq)]);
+        }
+
+        let chunk_len = chunk_len.min(i32::MAX as usize) as i32;
+        let mut results = Vec::new();
+        let mut pos = start;
+        while pos < end {
+            let chunk_end = pos.saturating_add(chunk_len).min(end);
+            let chunk = self.ctg_seq_bytes(sample, name, pos, chunk_end)?;
+            if !chunk.is_empty() {
+                results.push(f(&chunk));
+            }
+            if chunk.len() < (chunk_end - pos) as usize {
+                break;
+            }
+            pos = chunk_end;
+        }
+        Ok(results)
+    }
+
+    /// Fetch `[start, end)` in chunks, stopping when `stop` returns true
+    /// before a chunk
+    fn ctg_seq_bytes_until(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        chunk_len: usize,
+        mut stop: impl FnMut() -> bool,
+    ) -> Result<Vec<u8>, AgcError> {
+        if start < 0 || end < start {
+            return self.ctg_seq_bytes(sample, name, start, end);
+        }
+
+        // Reserved for the clipped range: `end` may be `i32::MAX` for "to
+        // the end"
+        let len = self.ctg_len(sample, name)?;
+        let chunk_len = chunk_len.min(i32::MAX as usize) as i32;
+        let mut seq = Vec::with_capacity((end.min(len) - start).max(0) as usize);
+        let mut pos = start;
+        loop {
+            if stop() {
+                return Err(AgcError::Cancelled);
+            }
+            let chunk_end = pos.saturating_add(chunk_len).min(end);
+            let chunk = self.ctg_seq_bytes(sample, name, pos, chunk_end)?;
+            let clipped = chunk.len() < (chunk_end - pos) as usize;
+            seq.extend_from_slice(&chunk);
+            pos = chunk_end;
+            if pos >= end || clipped {
+                return Ok(seq);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    fn reference_contig(agc: &AgcFile) -> (String, String, i32) {
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        (sample, contig, len)
+    }
+
+    #[test]
+    fn test_seq_chunks_cover_contig() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+
+        let chunks = agc.seq_chunks(Some(&sample), &contig, 7).unwrap();
+        let n = chunks.len();
+        let joined: Vec<u8> = chunks.map(Result::unwrap).collect::<Vec<_>>().concat();
+        assert_eq!(n, (len as usize).div_ceil(7));
+        assert_eq!(
+            String::from_utf8(joined).unwrap(),
+            agc.get_ctg_seq(Some(&sample), &contig, 0, len).unwrap()
+        );
+
+        assert!(agc.seq_chunks(Some(&sample), &contig, 0).is_err());
+        assert!(agc
+            .seq_chunks_overlapping(Some(&sample), &contig, 4, 4)
+            .is_err());
+    }
+
+    #[test]
+    fn test_zip_contig() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let mut checked = 0;
+        for (a, b) in agc.sample_pairs().unwrap() {
+            let contigs_b = agc.list_ctg(Some(&b)).unwrap();
+            for contig in agc.list_ctg(Some(&a)).unwrap() {
+                if !contigs_b.contains(&contig) {
+                    continue;
+                }
+                let full_a = agc.ctg_full_bytes(Some(&a), &contig).unwrap();
+                let full_b = agc.ctg_full_bytes(Some(&b), &contig).unwrap();
+                let shorter = full_a.len().min(full_b.len());
+
+                let zipped = agc.zip_contig(&contig, &a, &b, 3);
+                assert_eq!(zipped.lengths(), (full_a.len() as i32, full_b.len() as i32));
+                assert_eq!(zipped.is_truncated(), full_a.len() != full_b.len());
+                let (mut joined_a, mut joined_b) = (Vec::new(), Vec::new());
+                for pair in zipped {
+                    let (chunk_a, chunk_b) = pair.unwrap();
+                    assert_eq!(chunk_a.len(), chunk_b.len());
+                    joined_a.extend(chunk_a);
+                    joined_b.extend(chunk_b);
+                }
+                assert_eq!(joined_a, full_a[..shorter]);
+                assert_eq!(joined_b, full_b[..shorter]);
+                checked += 1;
+            }
+        }
+        assert!(checked > 0, "Expected a contig shared by two samples");
+
+        let sample = agc.reference_sample().unwrap();
+        let mut missing = agc.zip_contig("no_such_contig", &sample, &sample, 3);
+        assert!(missing.next().unwrap().is_err());
+        assert!(agc
+            .zip_contig("any", &sample, &sample, 0)
+            .next()
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_get_region_downsampled() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+        let full = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+
+        let same = agc
+            .get_region_downsampled(Some(&sample), &contig, 0, len, 1)
+            .unwrap();
+        assert_eq!(same, full);
+
+        let halved = agc
+            .get_region_downsampled(Some(&sample), &contig, 0, len, 2)
+            .unwrap();
+        assert_eq!(halved.len(), (len as usize).div_ceil(2));
+        assert_eq!(halved, full.iter().step_by(2).copied().collect::<Vec<u8>>());
+
+        for (stride, chunk_len) in [(3, 4), (3, 1), (5, 100)] {
+            let expected: Vec<u8> = full[1..].iter().step_by(stride).copied().collect();
+            let chunked = agc
+                .downsampled_chunked(Some(&sample), &contig, 1, len, stride, chunk_len)
+                .unwrap();
+            assert_eq!(chunked, expected, "stride {} chunk {}", stride, chunk_len);
+        }
+        assert!(agc
+            .get_region_downsampled(Some(&sample), &contig, 0, len, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_map_seq() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+        let full = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+        let gc = |seq: &[u8]| seq.iter().filter(|b| b"GCgc".contains(b)).count();
+
+        for chunk_len in [1, 7, len as usize, len as usize + 5] {
+            let counts = agc
+                .map_seq(Some(&sample), &contig, 0, len, chunk_len, gc)
+                .unwrap();
+            assert_eq!(counts.len(), (len as usize).div_ceil(chunk_len));
+            assert_eq!(counts.iter().sum::<usize>(), gc(&full));
+
+            let chunks = agc
+                .map_seq(
+                    Some(&sample),
+                    &contig,
+                    0,
+                    len + 3,
+                    chunk_len,
+                    <[u8]>::to_vec,
+                )
+                .unwrap();
+            assert_eq!(chunks.concat(), full, "chunk length {}", chunk_len);
+        }
+
+        let none = agc.map_seq(Some(&sample), &contig, 5, 5, 7, gc).unwrap();
+        assert!(none.is_empty());
+        assert!(agc.map_seq(Some(&sample), &contig, 0, len, 0, gc).is_err());
+        assert!(agc.map_seq(Some(&sample), &contig, 5, 2, 7, gc).is_err());
+    }
+
+    #[test]
+    fn test_get_ctg_seq_cancellable() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+        let full = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+
+        let running = AtomicBool::new(false);
+        let seq = agc
+            .get_ctg_seq_cancellable(Some(&sample), &contig, 0, len, &running)
+            .unwrap();
+        assert_eq!(seq, full);
+        let chunked = agc
+            .ctg_seq_bytes_until(Some(&sample), &contig, 0, len, 7, || false)
+            .unwrap();
+        assert_eq!(chunked, full);
+
+        // An open end reserves only what the contig holds
+        let tail = agc
+            .ctg_seq_bytes_until(Some(&sample), &contig, 5, i32::MAX, 7, || false)
+            .unwrap();
+        assert_eq!(tail, full[5..]);
+        assert!(tail.capacity() <= full.len());
+
+        let cancelled = AtomicBool::new(true);
+        assert!(matches!(
+            agc.get_ctg_seq_cancellable(Some(&sample), &contig, 0, len, &cancelled),
+            Err(AgcError::Cancelled)
+        ));
+
+        // Cancel after the second chunk: no third chunk is fetched
+        assert!(len > 14, "Fixture contig too short");
+        let mut checks = 0;
+        let calls = agc.ffi_seq_calls.load(Ordering::Relaxed);
+        let result = agc.ctg_seq_bytes_until(Some(&sample), &contig, 0, len, 7, || {
+            checks += 1;
+            checks > 2
+        });
+        assert!(matches!(result, Err(AgcError::Cancelled)));
+        assert_eq!(agc.ffi_seq_calls.load(Ordering::Relaxed) - calls, 2);
+    }
+
+    #[test]
+    fn test_tiled_windows() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+        let windows_of = |window, step| -> Vec<(i32, i32)> {
+            agc.tiled_windows(&sample, window, step)
+                .map(Result::unwrap)
+                .filter(|(name, ..)| *name == contig)
+                .map(|(_, start, end, _)| (start, end))
+                .collect()
+        };
+        assert!(len > 4, "Fixture contig too short");
+
+        let tiled = windows_of(4, 4);
+        assert_eq!(tiled.len(), (len as usize).div_ceil(4));
+        assert_eq!(tiled.last().unwrap().1, len);
+
+        let overlapping = windows_of(4, 2);
+        assert_eq!(overlapping.len(), 1 + ((len - 4) as usize).div_ceil(2));
+
+        let spaced = windows_of(2, 5);
+        assert_eq!(spaced.len(), (len as usize).div_ceil(5));
+        assert!(spaced.iter().all(|(start, end)| end - start <= 2));
+
+        for item in agc.tiled_windows(&sample, 4, 4).take(3) {
+            let (name, start, end, seq) = item.unwrap();
+            assert_eq!(
+                String::from_utf8(seq).unwrap(),
+                agc.get_ctg_seq(Some(&sample), &name, start, end).unwrap()
+            );
+        }
+
+        let mut invalid = agc.tiled_windows(&sample, 0, 4);
+        assert!(matches!(
+            invalid.next(),
+            Some(Err(AgcError::InvalidArgument(_)))
+        ));
+        assert!(invalid.next().is_none());
+    }
+
+    #[test]
+    fn test_seq_chunks_overlapping_boundary_motif() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let (sample, contig, len) = reference_contig(&agc);
+        let full = agc
+            .get_ctg_seq(Some(&sample), &contig, 0, len)
+            .unwrap()
+            .into_bytes();
+        let (chunk_len, overlap) = (8, 3);
+        assert!(len as usize >= chunk_len + 2, "Fixture contig too short");
+
+        // A 4-base motif straddling the first plain chunk boundary
+        let motif = &full[chunk_len - 2..chunk_len + 2];
+        let plain: Vec<Vec<u8>> = agc
+            .seq_chunks(Some(&sample), &contig, chunk_len)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert!(plain[0].ends_with(&motif[..2]) && plain[1].starts_with(&motif[2..]));
+
+        let chunks: Vec<Vec<u8>> = agc
+            .seq_chunks_overlapping(Some(&sample), &contig, chunk_len, overlap)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert!(chunks
+            .iter()
+            .any(|chunk| chunk.windows(4).any(|w| w == motif)));
+
+        // Successive chunks share exactly `overlap` bases and the last ends at the contig end
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0][chunk_len - overlap..], pair[1][..overlap]);
+        }
+        assert!(full.ends_with(chunks.last().unwrap()));
+    }
+}
@@ -0,0 +1,76 @@
+use std::io;
+use std::process::Command;
+
+use crate::{AgcError, AgcFile};
+
+/// Name of the AGC command-line tool, looked up on `PATH`
+const AGC_CLI: &str = "agc";
+
+/// Concatenate the sequence lines of FASTA output, dropping headers and
+/// line breaks
+fn fasta_bases(output: &[u8]) -> Vec<u8> {
+    output
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.starts_with(b">"))
+        .flat_map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .copied()
+        .collect()
+}
+
+impl AgcFile {
+    /// Check that a contig decodes the same here as with the `agc` tool
+    ///
+    /// *Requires the `cli-compare` feature.* Runs `agc getctg` on this
+    /// handle's archive, naming the contig as `name@sample` when a sample
+    /// is given, and compares the bases it prints with the full contig
+    /// as this crate fetches it. A mismatch points at the FFI wrapper,
+    /// e.g. a coordinate or buffer bug, or at a library built from a
+    /// different AGC version than the tool.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing whether the sequences are identical, or
+    /// `AgcError::Io` if `agc` cannot be run, with kind `NotFound` when it
+    /// is not on `PATH`, or if it exits unsuccessfully
+    pub fn decode_matches_cli(&self, sample: Option<&str>, name: &str) -> Result<bool, AgcError> {
+        let seq = self.ctg_full_bytes(sample, name)?;
+
+        let contig = match sample {
+            Some(sample) => format!("{}@{}", name, sample),
+            None => name.to_string(),
+        };
+        let output = Command::new(AGC_CLI)
+            .arg("getctg")
+            .arg(&self.path)
+            .arg(&contig)
+            .output()?;
+        if !output.status.success() {
+            return Err(AgcError::Io(io::Error::other(format!(
+                "{} getctg {} failed ({}): {}",
+                AGC_CLI,
+                contig,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))));
+        }
+
+        Ok(fasta_bases(&output.stdout) == seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fasta_bases() {
+        assert_eq!(fasta_bases(b">chr1\nACGT\nacg\n"), b"ACGTacg");
+        assert_eq!(fasta_bases(b">chr1 sample\r\nAC\r\nGT\r\n"), b"ACGT");
+        assert_eq!(fasta_bases(b"ACGT"), b"ACGT");
+        assert!(fasta_bases(b">empty\n").is_empty());
+        assert!(fasta_bases(b"").is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{AgcError, AgcFile};
+
+/// Bases fetched per sample per step when comparing flanks
+const FLANK_CHUNK_LEN: i32 = 1 << 16;
+
+/// Length of the prefix shared by every sequence
+fn common_prefix_len(seqs: &[Vec<u8>]) -> usize {
+    let (first, rest) = seqs.split_first().unwrap();
+    first
+        .iter()
+        .enumerate()
+        .take_while(|&(i, base)| rest.iter().all(|s| s.get(i) == Some(base)))
+        .count()
+}
+
+/// Length of the suffix shared by every sequence
+fn common_suffix_len(seqs: &[Vec<u8>]) -> usize {
+    let (first, rest) = seqs.split_first().unwrap();
+    first
+        .iter()
+        .rev()
+        .enumerate()
+        .take_while(|&(i, base)| {
+            rest.iter()
+                .all(|s| s.len() > i && s[s.len() - 1 - i] == *base)
+        })
+        .count()
+}
+
+impl AgcFile {
+    /// Find the longest prefix and suffix of a contig shared by samples
+    ///
+    /// The samples' copies are read in lockstep from each end, a chunk at a
+    /// time, and reading stops at the first difference, so conserved
+    /// flanks are found without loading whole contigs. Bases are compared
+    /// as stored, so case matters. When the copies are identical, both
+    /// flanks are the whole contig.
+    ///
+    /// # Arguments
+    /// * `contig` - contig name
+    /// * `samples` - sample names, at least one
+    ///
+    /// # Returns
+    /// Result containing `(prefix, suffix)`, or `AgcError::ContigNotFound`
+    /// if a sample lacks the contig
+    pub fn contig_shared_flanks(
+        &self,
+        contig: &str,
+        samples: &[&str],
+    ) -> Result<(Vec<u8>, Vec<u8>), AgcError> {
+        self.shared_flanks_chunked(contig, samples, FLANK_CHUNK_LEN)
+    }
+
+    fn shared_flanks_chunked(
+        &self,
+        contig: &str,
+        samples: &[&str],
+        chunk_len: i32,
+    ) -> Result<(Vec<u8>, Vec<u8>), AgcError> {
+        let lens = self.lengths_across(contig, samples)?;
+        let min_len = *lens.iter().min().unwrap();
+
+        let mut prefix = Vec::new();
+        let mut pos = 0;
+        while pos < min_len {
+            let end = pos.saturating_add(chunk_len).min(min_len);
+            let chunks = samples
+                .iter()
+                .map(|&s| self.ctg_seq_bytes(Some(s), contig, pos, end))
+                .collect::<Result<Vec<_>, _>>()?;
+            let n = common_prefix_len(&chunks);
+            prefix.extend_from_slice(&chunks[0][..n]);
+            if n < (end - pos) as usize {
+                break;
+            }
+            pos = end;
+        }
+
+        // Chunks taken back from each contig end, nearest the end first
+        let mut suffix_chunks = Vec::new();
+        let mut from_end = 0;
+        while from_end < min_len {
+            let n_bases = chunk_len.min(min_len - from_end);
+            let chunks = samples
+                .iter()
+                .zip(&lens)
+                .map(|(&s, &len)| {
+                    let end = len - from_end;
+                    self.ctg_seq_bytes(Some(s), contig, end - n_bases, end)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let n = common_suffix_len(&chunks);
+            let first = &chunks[0];
+            suffix_chunks.push(first[first.len() - n..].to_vec());
+            if n < n_bases as usize {
+                break;
+            }
+            from_end += n_bases;
+        }
+        suffix_chunks.reverse();
+        Ok((prefix, suffix_chunks.concat()))
+    }
+
+    /// Get the shortest length of a contig across samples
+    ///
+    /// Any window ending at or before this length exists in every one of
+    /// the samples, which bounds aligned cross-sample comparisons.
+    ///
+    /// # Arguments
+    /// * `contig` - contig name
+    /// * `samples` - sample names, at least one
+    ///
+    /// # Returns
+    /// Result containing the minimum length, or `AgcError::ContigNotFound`
+    /// if a sample lacks the contig
+    pub fn common_length(&self, contig: &str, samples: &[&str]) -> Result<i32, AgcError> {
+        Ok(*self.lengths_across(contig, samples)?.iter().min().unwrap())
+    }
+
+    /// Look up a contig's length in each sample, rejecting an empty list
+    fn lengths_across(&self, contig: &str, samples: &[&str]) -> Result<Vec<i32>, AgcError> {
+        if samples.is_empty() {
+            return Err(AgcError::InvalidArgument(
+                "at least one sample is required".to_string(),
+            ));
+        }
+        samples
+            .iter()
+            .map(|&s| self.ctg_len(Some(s), contig))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Samples that have `contig`
+    fn samples_with(agc: &AgcFile, contig: &str) -> Vec<String> {
+        agc.list_sample()
+            .unwrap()
+            .into_iter()
+            .filter(|s| agc.ctg_len(Some(s), contig).is_ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_common_prefix_suffix_len() {
+        let seqs = vec![b"ACGTTA".to_vec(), b"ACGATA".to_vec(), b"ACTA".to_vec()];
+        assert_eq!(common_prefix_len(&seqs), 2);
+        assert_eq!(common_suffix_len(&seqs), 2);
+        let same = vec![b"ACGT".to_vec(), b"ACGT".to_vec()];
+        assert_eq!(common_prefix_len(&same), 4);
+        assert_eq!(common_suffix_len(&same), 4);
+    }
+
+    #[test]
+    fn test_contig_shared_flanks() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&reference)).unwrap() {
+            let full = agc.ctg_full_bytes(Some(&reference), &contig).unwrap();
+            let (prefix, suffix) = agc
+                .contig_shared_flanks(&contig, &[&reference, &reference])
+                .unwrap();
+            assert_eq!((&prefix, &suffix), (&full, &full));
+
+            let samples = samples_with(&agc, &contig);
+            let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+            let seqs: Vec<Vec<u8>> = samples
+                .iter()
+                .map(|&s| agc.ctg_full_bytes(Some(s), &contig).unwrap())
+                .collect();
+            let expected = (
+                seqs[0][..common_prefix_len(&seqs)].to_vec(),
+                seqs[0][seqs[0].len() - common_suffix_len(&seqs)..].to_vec(),
+            );
+            for chunk_len in [3, FLANK_CHUNK_LEN] {
+                let flanks = agc
+                    .shared_flanks_chunked(&contig, &samples, chunk_len)
+                    .unwrap();
+                assert_eq!(flanks, expected, "{} by {}", contig, chunk_len);
+            }
+        }
+        assert!(agc.contig_shared_flanks("chr1", &[]).is_err());
+    }
+
+    #[test]
+    fn test_common_length() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&reference)).unwrap() {
+            let samples = samples_with(&agc, &contig);
+            let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+            let min = samples
+                .iter()
+                .map(|&s| agc.ctg_len(Some(s), &contig).unwrap())
+                .min()
+                .unwrap();
+            assert_eq!(agc.common_length(&contig, &samples).unwrap(), min);
+        }
+
+        let missing = agc.common_length("no_such_contig", &[&reference]);
+        assert!(matches!(missing, Err(AgcError::ContigNotFound { .. })));
+        assert!(agc.common_length("chr1", &[]).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Compressed FASTA export, enabled by the `compression` feature
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::{AgcError, AgcFile, LineEnding};
+
+/// Compression codec for exported FASTA
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// gzip, readable by `zcat` and most bioinformatics tools
+    #[default]
+    Gzip,
+    /// Zstandard
+    Zstd,
+}
+
+impl Codec {
+    /// Pick the codec from a file extension: `.gz` for gzip, `.zst` or
+    /// `.zstd` for Zstandard
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Codec> {
+        match path.as_ref().extension()?.to_str()? {
+            "gz" => Some(Codec::Gzip),
+            "zst" | "zstd" => Some(Codec::Zstd),
+            _ => None,
+        }
+    }
+}
+
+impl AgcFile {
+    /// Extract all contigs of a sample to a compressed FASTA file
+    ///
+    /// The codec is picked from the extension of `path` (see
+    /// `Codec::from_path`), defaulting to gzip.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `path` - output file path
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    pub fn extract_sample_to_gz(
+        &self,
+        sample: &str,
+        path: impl AsRef<Path>,
+        line_width: usize,
+    ) -> Result<(), AgcError> {
+        let codec = Codec::from_path(&path).unwrap_or_default();
+        self.extract_sample_compressed(sample, path, line_width, codec)
+    }
+
+    /// Extract all contigs of a sample to a FASTA file through a compressor
+    ///
+    /// Records are compressed as they are written, so no uncompressed copy
+    /// is kept in memory or on disk.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `path` - output file path
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `codec` - compression codec
+    pub fn extract_sample_compressed(
+        &self,
+        sample: &str,
+        path: impl AsRef<Path>,
+        line_width: usize,
+        codec: Codec,
+    ) -> Result<(), AgcError> {
+        let out = BufWriter::new(File::create(path)?);
+
+        match codec {
+            Codec::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(out, flate2::Compression::default());
+                self.write_sample_fasta(sample, &mut encoder, line_width, LineEnding::Lf)?;
+                encoder.finish()?.flush()?;
+            }
+            Codec::Zstd => {
+                let mut encoder = zstd::stream::write::Encoder::new(out, 0)?;
+                self.write_sample_fasta(sample, &mut encoder, line_width, LineEnding::Lf)?;
+                encoder.finish()?.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_codec_from_path() {
+        assert_eq!(Codec::from_path("out.fa.gz"), Some(Codec::Gzip));
+        assert_eq!(Codec::from_path("out.fa.zst"), Some(Codec::Zstd));
+        assert_eq!(Codec::from_path("out.fa"), None);
+    }
+
+    #[test]
+    fn test_extract_sample_compressed_round_trip() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let mut plain = Vec::new();
+        agc.write_sample_fasta(&sample, &mut plain, 60, LineEnding::Lf)
+            .expect("Failed to write FASTA");
+
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        let gz = dir.path().join("sample.fa.gz");
+        agc.extract_sample_to_gz(&sample, &gz, 60)
+            .expect("Failed to write gzip FASTA");
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(File::open(&gz).unwrap())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, plain);
+
+        let zst = dir.path().join("sample.fa.zst");
+        agc.extract_sample_to_gz(&sample, &zst, 60)
+            .expect("Failed to write zstd FASTA");
+        let decoded = zstd::stream::decode_all(File::open(&zst).unwrap()).unwrap();
+        assert_eq!(decoded, plain);
+    }
+}
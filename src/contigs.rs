@@ -0,0 +1,936 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::vec;
+
+use crate::{AgcError, AgcFile};
+
+/// Every contig name of an archive with the samples that have it
+#[derive(Debug, Default)]
+pub(crate) struct ContigOwners {
+    /// Names and their samples, in first-seen order
+    entries: Vec<(String, Vec<String>)>,
+    /// Position of each name in `entries`
+    index: HashMap<String, usize>,
+}
+
+/// A contig name and its length
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContigInfo {
+    /// Contig name
+    pub name: String,
+    /// Contig length in bases
+    pub length: i32,
+}
+
+/// Contig names found in only one of two samples
+///
+/// Returned by `AgcFile::contig_set_diff`; together the two sets are the
+/// symmetric difference of the samples' contig names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContigSetDiff {
+    /// Contigs of the first sample missing from the second
+    pub only_in_a: BTreeSet<String>,
+    /// Contigs of the second sample missing from the first
+    pub only_in_b: BTreeSet<String>,
+}
+
+impl ContigSetDiff {
+    /// Whether both samples have the same contig names
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty()
+    }
+}
+
+/// Lazy iterator over the contigs of a sample
+///
+/// The contig names are listed once when the iterator is created; each
+/// length is only looked up when its item is reached, so stopping early
+/// avoids sizing the remaining contigs. Created by `AgcFile::contigs`.
+pub struct Contigs<'a> {
+    agc: &'a AgcFile,
+    sample: Option<String>,
+    names: vec::IntoIter<String>,
+}
+
+impl Iterator for Contigs<'_> {
+    type Item = Result<ContigInfo, AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.names.next()?;
+        Some(
+            self.agc
+                .ctg_len(self.sample.as_deref(), &name)
+                .map(|length| ContigInfo { name, length }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.names.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Contigs<'_> {}
+
+impl AgcFile {
+    /// Iterate over the contigs of a sample with their lengths
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    ///
+    /// # Returns
+    /// Result containing a lazy iterator borrowing this handle; it cannot
+    /// outlive the `AgcFile`
+    pub fn contigs(&self, sample: Option<&str>) -> Result<Contigs<'_>, AgcError> {
+        Ok(Contigs {
+            agc: self,
+            sample: sample.map(str::to_string),
+            names: self.list_ctg(sample)?.into_iter(),
+        })
+    }
+
+    /// Iterate over the contigs of a sample at least `min_len` bases long
+    ///
+    /// Lazy like [`AgcFile::contigs`]: each contig is sized as it is
+    /// reached and skipped if it is too short. Errors are passed through,
+    /// including a failure to list the contigs, which is the only item.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `min_len` - minimum contig length to yield
+    pub fn contigs_longer_than(
+        &self,
+        sample: Option<&str>,
+        min_len: i32,
+    ) -> impl Iterator<Item = Result<ContigInfo, AgcError>> + '_ {
+        let (contigs, setup_error) = match self.contigs(sample) {
+            Ok(contigs) => (Some(contigs), None),
+            Err(e) => (None, Some(Err(e))),
+        };
+        setup_error.into_iter().chain(
+            contigs
+                .into_iter()
+                .flatten()
+                .filter(move |info| !matches!(info, Ok(info) if info.length < min_len)),
+        )
+    }
+
+    /// Get name and length of the first `n` contigs of a sample
+    ///
+    /// Only the returned contigs are sized, which keeps previews of
+    /// archives with many contigs cheap.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `n` - maximum number of contigs to return
+    pub fn first_contigs(
+        &self,
+        sample: Option<&str>,
+        n: usize,
+    ) -> Result<Vec<ContigInfo>, AgcError> {
+        self.contigs(sample)?.take(n).collect()
+    }
+
+    /// Find the first contig of a sample matching a predicate
+    ///
+    /// Lazy like [`AgcFile::contigs`]: contigs are sized one at a time in
+    /// listing order and the search stops at the first match, so contigs
+    /// after it are never sized.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `pred` - test applied to each contig's name and length
+    ///
+    /// # Returns
+    /// Result containing the first matching contig, or None if none match
+    pub fn find_contig<F>(
+        &self,
+        sample: Option<&str>,
+        pred: F,
+    ) -> Result<Option<ContigInfo>, AgcError>
+    where
+        F: Fn(&ContigInfo) -> bool,
+    {
+        for info in self.contigs(sample)? {
+            let info = info?;
+            if pred(&info) {
+                return Ok(Some(info));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the one sample holding a contig named without a sample
+    ///
+    /// AGC only answers a sample-less lookup when the name is unique
+    /// across the archive; this says which sample that is, or why there
+    /// is none. A qualified `sample#contig` name is looked up in its own
+    /// sample. The samples of every contig name are listed on the first
+    /// sample-less lookup and kept for later ones.
+    ///
+    /// # Arguments
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the sample and the contig length,
+    /// `AgcError::AmbiguousContig` if several samples have the contig, or
+    /// `AgcError::ContigNotFound` if none do
+    pub fn resolve_contig(&self, name: &str) -> Result<(String, i32), AgcError> {
+        let (sample, name) = self.resolve_name(None, name)?;
+        if let Some(sample) = sample {
+            let len = self.resolved_ctg_len(Some(sample), name)?;
+            return Ok((sample.to_string(), len));
+        }
+        let mut owners = self.contig_owners(name)?;
+        match owners.len() {
+            0 => Err(AgcError::ContigNotFound {
+                sample: None,
+                name: name.to_string(),
+            }),
+            1 => {
+                let sample = owners.remove(0);
+                let len = self.resolved_ctg_len(Some(&sample), name)?;
+                Ok((sample, len))
+            }
+            _ => Err(AgcError::AmbiguousContig {
+                name: name.to_string(),
+                samples: owners,
+            }),
+        }
+    }
+
+    /// Samples that have a contig of this exact name, in listing order
+    pub(crate) fn contig_owners(&self, name: &str) -> Result<Vec<String>, AgcError> {
+        let owners = self.contig_owner_index()?;
+        Ok(owners
+            .index
+            .get(name)
+            .map_or_else(Vec::new, |&i| owners.entries[i].1.clone()))
+    }
+
+    /// Every contig name of the archive, each once, in first-seen order
+    pub(crate) fn all_contig_names(&self) -> Result<Vec<String>, AgcError> {
+        let owners = self.contig_owner_index()?;
+        Ok(owners
+            .entries
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect())
+    }
+
+    /// The samples of every contig name, listed once per handle
+    ///
+    /// An archive's listings never change, so one pass over every sample
+    /// serves all later sample-less lookups.
+    fn contig_owner_index(&self) -> Result<&ContigOwners, AgcError> {
+        if let Some(owners) = self.contig_owners.get() {
+            return Ok(owners);
+        }
+
+        let mut owners = ContigOwners::default();
+        for sample in self.list_sample()? {
+            for contig in self.list_ctg(Some(&sample))? {
+                let i = *owners.index.entry(contig.clone()).or_insert_with(|| {
+                    owners.entries.push((contig, Vec::new()));
+                    owners.entries.len() - 1
+                });
+                owners.entries[i].1.push(sample.clone());
+            }
+        }
+        Ok(self.contig_owners.get_or_init(|| owners))
+    }
+
+    /// Get the length of every contig of a sample, keyed by name
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    pub fn contig_length_map(
+        &self,
+        sample: Option<&str>,
+    ) -> Result<HashMap<String, i32>, AgcError> {
+        self.contigs(sample)?
+            .map(|info| info.map(|info| (info.name, info.length)))
+            .collect()
+    }
+
+    /// Get the length of every contig of the reference sample
+    ///
+    /// These are the coordinate bounds for clamping features lifted onto
+    /// the reference. The reference name is only looked up once.
+    pub fn reference_length_map(&self) -> Result<HashMap<String, i32>, AgcError> {
+        let reference = self.reference_sample()?;
+        self.contig_length_map(Some(&reference))
+    }
+
+    /// Get the contigs of a sample sorted by length
+    ///
+    /// Contigs of equal length are ordered by name, in ascending order
+    /// either way, so the result is deterministic.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `descending` - longest first if true, shortest first otherwise
+    pub fn contigs_by_length(
+        &self,
+        sample: &str,
+        descending: bool,
+    ) -> Result<Vec<ContigInfo>, AgcError> {
+        let mut contigs = self.contigs(Some(sample))?.collect::<Result<Vec<_>, _>>()?;
+        sort_by_length(&mut contigs, descending);
+        Ok(contigs)
+    }
+
+    /// Get the contig lengths of a sample, sorted, without their names
+    ///
+    /// For cumulative-length plots and N50 curves; use `contigs_by_length`
+    /// when the names are needed too.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `descending` - longest first if true, shortest first otherwise
+    ///
+    /// # Returns
+    /// Result containing the lengths; a sample without contigs has none,
+    /// which plots as an empty curve rather than an error
+    pub fn sorted_contig_lengths(
+        &self,
+        sample: &str,
+        descending: bool,
+    ) -> Result<Vec<i32>, AgcError> {
+        let mut lengths = self
+            .contigs(Some(sample))?
+            .map(|info| info.map(|info| info.length))
+            .collect::<Result<Vec<_>, _>>()?;
+        if descending {
+            lengths.sort_unstable_by(|a, b| b.cmp(a));
+        } else {
+            lengths.sort_unstable();
+        }
+        Ok(lengths)
+    }
+
+    /// Get the longest contig of a sample
+    ///
+    /// Ties go to the contig listed first.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the longest contig, or `AgcError::EmptySample` if
+    /// the sample has no contigs
+    pub fn longest_contig(&self, sample: &str) -> Result<ContigInfo, AgcError> {
+        longest(
+            self.contigs(Some(sample))?.collect::<Result<_, _>>()?,
+            sample,
+        )
+    }
+
+    /// Get the position of a contig within its sample's listing
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the zero-based index into `list_ctg(sample)`
+    pub fn ctg_index(&self, sample: Option<&str>, name: &str) -> Result<usize, AgcError> {
+        let (sample, name) = self.resolve_name(sample, name)?;
+        self.list_ctg(sample)?
+            .iter()
+            .position(|ctg| ctg == name)
+            .ok_or_else(|| AgcError::ContigNotFound {
+                sample: sample.map(str::to_string),
+                name: name.to_string(),
+            })
+    }
+
+    /// Get the name of the contig at a position in its sample's listing
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `index` - zero-based index into `list_ctg(sample)`
+    ///
+    /// # Returns
+    /// Result containing the contig name, or an error if `index` is out of range
+    pub fn ctg_name_at(&self, sample: Option<&str>, index: usize) -> Result<String, AgcError> {
+        let mut names = self.list_ctg(sample)?;
+        if index >= names.len() {
+            return Err(AgcError::InvalidArgument(format!(
+                "contig index {} out of range for {} contigs",
+                index,
+                names.len()
+            )));
+        }
+        Ok(names.swap_remove(index))
+    }
+
+    /// Get the union of contig names across all samples
+    ///
+    /// # Returns
+    /// Result containing every contig name that occurs in any sample,
+    /// sorted and deduplicated
+    pub fn unique_contig_names(&self) -> Result<BTreeSet<String>, AgcError> {
+        let mut names = BTreeSet::new();
+        for sample in self.list_sample()? {
+            names.extend(self.list_ctg(Some(&sample))?);
+        }
+        Ok(names)
+    }
+
+    /// Check whether two samples have the same contig names
+    ///
+    /// Order is ignored and lengths are not compared.
+    ///
+    /// # Arguments
+    /// * `a` - first sample name
+    /// * `b` - second sample name
+    ///
+    /// # Returns
+    /// Result containing true if every contig name occurs in both samples
+    pub fn same_contig_set(&self, a: &str, b: &str) -> Result<bool, AgcError> {
+        Ok(self.contig_set_diff(a, b)?.is_empty())
+    }
+
+    /// List the contigs of a sample that the reference sample lacks
+    ///
+    /// These are its non-reference, accessory sequences. Names are
+    /// compared exactly.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the contig names, in the sample's listing order
+    pub fn novel_contigs(&self, sample: &str) -> Result<Vec<String>, AgcError> {
+        let reference = self.reference_sample()?;
+        let known: BTreeSet<String> = self.list_ctg(Some(&reference))?.into_iter().collect();
+        Ok(self
+            .list_ctg(Some(sample))?
+            .into_iter()
+            .filter(|name| !known.contains(name))
+            .collect())
+    }
+
+    /// Compare the contig names of two samples
+    ///
+    /// # Arguments
+    /// * `a` - first sample name
+    /// * `b` - second sample name
+    ///
+    /// # Returns
+    /// Result containing the names found in only one of the samples
+    pub fn contig_set_diff(&self, a: &str, b: &str) -> Result<ContigSetDiff, AgcError> {
+        let names_a: BTreeSet<String> = self.list_ctg(Some(a))?.into_iter().collect();
+        let names_b: BTreeSet<String> = self.list_ctg(Some(b))?.into_iter().collect();
+        Ok(ContigSetDiff {
+            only_in_a: names_a.difference(&names_b).cloned().collect(),
+            only_in_b: names_b.difference(&names_a).cloned().collect(),
+        })
+    }
+
+    /// Jaccard index of two samples' contig names
+    ///
+    /// The shared names over all names of either sample: a cheap,
+    /// name-level proxy for how much content the samples share.
+    ///
+    /// # Arguments
+    /// * `a` - first sample name
+    /// * `b` - second sample name
+    ///
+    /// # Returns
+    /// Result containing a value in `[0, 1]`; 1 if neither sample has
+    /// contigs
+    pub fn contig_jaccard(&self, a: &str, b: &str) -> Result<f64, AgcError> {
+        let names_a: BTreeSet<String> = self.list_ctg(Some(a))?.into_iter().collect();
+        let names_b: BTreeSet<String> = self.list_ctg(Some(b))?.into_iter().collect();
+        Ok(jaccard(&names_a, &names_b))
+    }
+
+    /// Contig names present in every sample, the core of a pangenome
+    ///
+    /// The intersection of all samples' contig sets; in an archive of one
+    /// sample that is all its contigs.
+    ///
+    /// # Returns
+    /// Result containing the core names in the first sample's listing
+    /// order, empty for an archive without samples
+    pub fn core_contigs(&self) -> Result<Vec<String>, AgcError> {
+        let lists = self
+            .list_sample()?
+            .iter()
+            .map(|sample| self.list_ctg(Some(sample)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(intersect_all(lists))
+    }
+}
+
+/// Names of the first list found in every other list, keeping the first
+/// list's order and dropping repeats
+fn intersect_all(lists: Vec<Vec<String>>) -> Vec<String> {
+    let mut lists = lists.into_iter();
+    let first = match lists.next() {
+        Some(first) => first,
+        None => return Vec::new(),
+    };
+    let others: Vec<HashSet<String>> = lists.map(|list| list.into_iter().collect()).collect();
+    let mut seen = HashSet::new();
+    first
+        .into_iter()
+        .filter(|name| others.iter().all(|set| set.contains(name)) && seen.insert(name.clone()))
+        .collect()
+}
+
+/// `|a ∩ b| / |a ∪ b|`, taken as 1 for two empty sets
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    let union = a.len() + b.len() - shared;
+    if union == 0 {
+        return 1.0;
+    }
+    shared as f64 / union as f64
+}
+
+/// Sort contigs by length, breaking ties by ascending name
+fn sort_by_length(contigs: &mut [ContigInfo], descending: bool) {
+    contigs.sort_by(|a, b| {
+        let by_length = if descending {
+            b.length.cmp(&a.length)
+        } else {
+            a.length.cmp(&b.length)
+        };
+        by_length.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Longest of a sample's contigs, first listed on ties
+fn longest(contigs: Vec<ContigInfo>, sample: &str) -> Result<ContigInfo, AgcError> {
+    contigs
+        .into_iter()
+        .reduce(|best, info| {
+            if info.length > best.length {
+                info
+            } else {
+                best
+            }
+        })
+        .ok_or_else(|| AgcError::EmptySample(sample.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_resolve_contig() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let mut owners: HashMap<String, Vec<String>> = HashMap::new();
+        for (sample, contig, _) in agc.inventory().unwrap() {
+            owners.entry(contig).or_default().push(sample);
+        }
+
+        for (contig, samples) in &owners {
+            match agc.resolve_contig(contig) {
+                Ok((sample, len)) => {
+                    assert_eq!(samples, std::slice::from_ref(&sample));
+                    assert_eq!(len, agc.get_ctg_len(Some(&sample), contig).unwrap());
+                }
+                Err(AgcError::AmbiguousContig {
+                    name,
+                    samples: found,
+                }) => {
+                    assert_eq!(&name, contig);
+                    assert_eq!(&found, samples);
+                    assert!(samples.len() > 1);
+                }
+                Err(e) => panic!("Unexpected error for {}: {}", contig, e),
+            }
+        }
+
+        assert!(matches!(
+            agc.resolve_contig("no_such_contig"),
+            Err(AgcError::ContigNotFound { sample: None, .. })
+        ));
+
+        // A qualified name picks its sample, even for a shared contig
+        for (contig, samples) in &owners {
+            for sample in samples {
+                let qualified = format!("{}#{}", sample, contig);
+                let len = agc.get_ctg_len(Some(sample), contig).unwrap();
+                assert_eq!(
+                    agc.resolve_contig(&qualified).unwrap(),
+                    (sample.clone(), len)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_less_miss_lists_once() {
+        use std::sync::atomic::Ordering;
+
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        assert!(agc.ctg_len(None, "no_such_contig").is_err());
+        let calls = agc.ffi_list_ctg_calls.load(Ordering::Relaxed);
+        assert_eq!(calls, agc.n_sample() as usize);
+
+        // Later misses and merged listings reuse the first pass
+        assert!(agc.ctg_len(None, "another_missing_contig").is_err());
+        agc.list_ctg(None).unwrap();
+        assert_eq!(agc.ffi_list_ctg_calls.load(Ordering::Relaxed), calls);
+    }
+
+    #[test]
+    fn test_merged_list_ctg_limit() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let merged = agc.list_ctg(None).unwrap().len();
+        let samples = agc.list_sample().unwrap();
+        let largest = samples
+            .iter()
+            .map(|s| agc.list_ctg(Some(s)).unwrap().len())
+            .max()
+            .unwrap();
+        assert!(merged > largest);
+
+        // Every sample fits the limit, but the merged list does not
+        let agc = agc.with_max_list_len(largest);
+        for sample in &samples {
+            assert!(agc.list_ctg(Some(sample)).is_ok());
+        }
+        let err = agc.list_ctg(None).unwrap_err();
+        assert!(err.contains("limit"), "Unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let set =
+            |names: &[&str]| -> BTreeSet<String> { names.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(jaccard(&set(&["a", "b"]), &set(&["a", "b"])), 1.0);
+        assert_eq!(jaccard(&set(&["a", "b"]), &set(&["c"])), 0.0);
+        assert_eq!(jaccard(&set(&["a", "b", "c"]), &set(&["b", "c", "d"])), 0.5);
+        assert_eq!(jaccard(&set(&[]), &set(&[])), 1.0);
+    }
+
+    #[test]
+    fn test_intersect_all() {
+        let list =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|s| s.to_string()).collect() };
+        let samples = vec![
+            list(&["chr2", "chr1", "chrUn_1"]),
+            list(&["chr1", "chr2"]),
+            list(&["chr1", "alt_3", "chr2", "chr2"]),
+        ];
+        assert_eq!(intersect_all(samples), ["chr2", "chr1"]);
+        assert_eq!(
+            intersect_all(vec![list(&["chr1", "chr2"])]),
+            ["chr1", "chr2"]
+        );
+        assert!(intersect_all(vec![list(&["chr1"]), list(&["chr2"])]).is_empty());
+        assert!(intersect_all(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_core_contigs() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let core = agc.core_contigs().unwrap();
+        for sample in agc.list_sample().unwrap() {
+            let contigs = agc.list_ctg(Some(&sample)).unwrap();
+            assert!(core.iter().all(|name| contigs.contains(name)));
+        }
+        let first = agc.list_ctg(Some(&agc.list_sample().unwrap()[0])).unwrap();
+        for name in first {
+            let everywhere = agc
+                .list_sample()
+                .unwrap()
+                .iter()
+                .all(|s| agc.list_ctg(Some(s)).unwrap().contains(&name));
+            assert_eq!(core.contains(&name), everywhere);
+        }
+    }
+
+    #[test]
+    fn test_contig_jaccard() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+        for a in &samples {
+            assert_eq!(agc.contig_jaccard(a, a).unwrap(), 1.0);
+            for b in &samples {
+                let index = agc.contig_jaccard(a, b).unwrap();
+                assert!((0.0..=1.0).contains(&index));
+                assert_eq!(index, agc.contig_jaccard(b, a).unwrap());
+                if agc.contig_set_diff(a, b).unwrap().is_empty() {
+                    assert_eq!(index, 1.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_contigs_iterator() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let names = agc.list_ctg(Some(&sample)).expect("Failed to list contigs");
+
+        let contigs = agc
+            .contigs(Some(&sample))
+            .expect("Failed to iterate contigs");
+        assert_eq!(contigs.len(), names.len());
+
+        for info in contigs {
+            let info = info.expect("Failed to get contig info");
+            assert_eq!(
+                info.length,
+                agc.get_ctg_len(Some(&sample), &info.name).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_contigs_longer_than() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let lengths = agc.sorted_contig_lengths(&sample, false).unwrap();
+
+        for min_len in [
+            0,
+            lengths[lengths.len() / 2],
+            lengths[lengths.len() - 1] + 1,
+        ] {
+            let kept: Vec<ContigInfo> = agc
+                .contigs_longer_than(Some(&sample), min_len)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            let expected = lengths.iter().filter(|&&len| len >= min_len).count();
+            assert_eq!(kept.len(), expected);
+            assert!(kept.iter().all(|info| info.length >= min_len));
+        }
+    }
+
+    #[test]
+    fn test_find_contig() {
+        use std::sync::atomic::Ordering;
+
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+
+        let first = agc
+            .find_contig(Some(&sample), |_| true)
+            .expect("Failed to search")
+            .expect("Sample should have a contig");
+        assert_eq!(first.name, contigs[0]);
+        // Only the matching contig was sized
+        assert_eq!(agc.ffi_len_calls.load(Ordering::Relaxed), 1);
+
+        let last = contigs.last().unwrap();
+        let found = agc
+            .find_contig(Some(&sample), |info| &info.name == last)
+            .unwrap();
+        assert_eq!(found.map(|info| info.name), Some(last.clone()));
+        // Every contig up to the last was sized, the first from the cache
+        assert_eq!(agc.ffi_len_calls.load(Ordering::Relaxed), contigs.len());
+        assert_eq!(agc.find_contig(Some(&sample), |_| false).unwrap(), None);
+    }
+
+    #[test]
+    fn test_first_contigs() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let total = agc.n_ctg(&sample).expect("Failed to count contigs") as usize;
+
+        for n in [0, 1, total + 10] {
+            let first = agc
+                .first_contigs(Some(&sample), n)
+                .expect("Failed to get first contigs");
+            assert_eq!(first.len(), std::cmp::min(n, total));
+        }
+    }
+
+    #[test]
+    fn test_reference_length_map() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        let lengths = agc.reference_length_map().expect("Failed to get lengths");
+
+        let contigs = agc.list_ctg(Some(&reference)).unwrap();
+        assert_eq!(lengths.len(), contigs.len());
+        for contig in &contigs {
+            assert_eq!(
+                lengths[contig],
+                agc.get_ctg_len(Some(&reference), contig).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_by_length_ties() {
+        let contig = |name: &str, length| ContigInfo {
+            name: name.to_string(),
+            length,
+        };
+        let mut contigs = vec![contig("c", 5), contig("b", 9), contig("a", 5)];
+
+        sort_by_length(&mut contigs, true);
+        let names: Vec<&str> = contigs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["b", "a", "c"]);
+
+        sort_by_length(&mut contigs, false);
+        let names: Vec<&str> = contigs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_contigs_by_length() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let n_ctg = agc.n_ctg(&sample).unwrap() as usize;
+
+        let ascending = agc.contigs_by_length(&sample, false).unwrap();
+        assert_eq!(ascending.len(), n_ctg);
+        assert!(ascending.windows(2).all(|w| w[0].length <= w[1].length));
+
+        let descending = agc.contigs_by_length(&sample, true).unwrap();
+        assert!(descending.windows(2).all(|w| w[0].length >= w[1].length));
+        assert_eq!(
+            descending[0].length,
+            agc.longest_contig(&sample).unwrap().length
+        );
+    }
+
+    #[test]
+    fn test_sorted_contig_lengths() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+
+        let ascending = agc.sorted_contig_lengths(&sample, false).unwrap();
+        assert_eq!(ascending.len(), agc.n_ctg(&sample).unwrap() as usize);
+        assert!(ascending.windows(2).all(|w| w[0] <= w[1]));
+
+        let descending = agc.sorted_contig_lengths(&sample, true).unwrap();
+        assert!(descending.iter().eq(ascending.iter().rev()));
+        let by_length: Vec<i32> = agc
+            .contigs_by_length(&sample, true)
+            .unwrap()
+            .iter()
+            .map(|c| c.length)
+            .collect();
+        assert_eq!(descending, by_length);
+    }
+
+    #[test]
+    fn test_longest_contig() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let longest = agc
+            .longest_contig(&sample)
+            .expect("Failed to get longest contig");
+
+        for info in agc.contigs(Some(&sample)).unwrap() {
+            assert!(info.unwrap().length <= longest.length);
+        }
+    }
+
+    #[test]
+    fn test_longest_empty_sample() {
+        let contig = |name: &str, length| ContigInfo {
+            name: name.to_string(),
+            length,
+        };
+        assert!(matches!(
+            longest(Vec::new(), "empty"),
+            Err(AgcError::EmptySample(sample)) if sample == "empty"
+        ));
+        assert_eq!(
+            longest(vec![contig("a", 5), contig("b", 9), contig("c", 9)], "s").unwrap(),
+            contig("b", 9)
+        );
+    }
+
+    #[test]
+    fn test_ctg_index_round_trip() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let names = agc.list_ctg(Some(&sample)).expect("Failed to list contigs");
+
+        for (i, name) in names.iter().enumerate() {
+            let index = agc.ctg_index(Some(&sample), name).unwrap();
+            assert_eq!(index, i);
+            assert_eq!(&agc.ctg_name_at(Some(&sample), index).unwrap(), name);
+        }
+
+        assert!(matches!(
+            agc.ctg_index(Some(&sample), "no_such_contig"),
+            Err(AgcError::ContigNotFound { .. })
+        ));
+        assert!(agc.ctg_name_at(Some(&sample), names.len()).is_err());
+    }
+
+    #[test]
+    fn test_unique_contig_names() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let names = agc.unique_contig_names().expect("Failed to collect names");
+
+        let mut total = 0;
+        for sample in agc.list_sample().unwrap() {
+            let contigs = agc.list_ctg(Some(&sample)).unwrap();
+            total += contigs.len();
+            assert!(contigs.iter().all(|c| names.contains(c)));
+        }
+        assert!(!names.is_empty());
+        assert!(names.len() <= total);
+    }
+
+    #[test]
+    fn test_novel_contigs() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        assert!(agc.novel_contigs(&reference).unwrap().is_empty());
+        // Sample `a` of the fixture carries `chr1a`, which the reference lacks
+        assert_eq!(agc.novel_contigs("a").unwrap(), ["chr1a"]);
+
+        for sample in agc.list_sample().unwrap() {
+            let novel = agc.novel_contigs(&sample).unwrap();
+            let diff = agc.contig_set_diff(&sample, &reference).unwrap();
+            assert_eq!(
+                novel.iter().cloned().collect::<BTreeSet<_>>(),
+                diff.only_in_a
+            );
+            let listed = agc.list_ctg(Some(&sample)).unwrap();
+            assert!(novel.iter().all(|c| listed.contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_same_contig_set() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+        let names =
+            |s: &str| -> BTreeSet<String> { agc.list_ctg(Some(s)).unwrap().into_iter().collect() };
+
+        for a in &samples {
+            assert!(agc.same_contig_set(a, a).unwrap());
+            for b in &samples {
+                let diff = agc.contig_set_diff(a, b).unwrap();
+                let (names_a, names_b) = (names(a), names(b));
+                assert_eq!(agc.same_contig_set(a, b).unwrap(), names_a == names_b);
+                assert_eq!(diff.is_empty(), names_a == names_b);
+                assert!(diff.only_in_a.iter().all(|c| !names_b.contains(c)));
+                assert!(diff.only_in_b.iter().all(|c| !names_a.contains(c)));
+                assert_eq!(
+                    names_a.len() - diff.only_in_a.len(),
+                    names_b.len() - diff.only_in_b.len()
+                );
+            }
+        }
+    }
+}
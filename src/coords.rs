@@ -0,0 +1,182 @@
+use crate::{AgcError, AgcFile};
+
+/// How positions in different samples of an archive relate
+///
+/// Returned by `AgcFile::coordinate_system`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    /// Every contig has its own coordinates; position 1000 of a sample's
+    /// `chr1` need not correspond to position 1000 of the reference's
+    Independent,
+    /// Positions in every sample are positions on the reference
+    ReferenceAligned,
+}
+
+/// Coordinate convention of intervals written to reports
+///
+/// The crate works in 0-based, half-open `[start, end)` coordinates
+/// throughout; this only changes how intervals are written out. A 0-based
+/// interval `[start, end)` is `[start + 1, end]` in 1-based, closed
+/// coordinates, so only the start moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoordinateBase {
+    /// 0-based, half-open, as in BED
+    #[default]
+    ZeroBased,
+    /// 1-based, closed, as in GFF, VCF and AGP
+    OneBased,
+}
+
+impl CoordinateBase {
+    /// Convert a 0-based, half-open interval to this convention
+    ///
+    /// # Arguments
+    /// * `start` - 0-based start
+    /// * `end` - exclusive end
+    ///
+    /// # Returns
+    /// The interval's start and end in this convention
+    pub fn interval(self, start: i32, end: i32) -> (i32, i32) {
+        (self.position(start), end)
+    }
+
+    /// Convert a 0-based position to this convention
+    pub fn position(self, pos: i32) -> i32 {
+        match self {
+            CoordinateBase::ZeroBased => pos,
+            CoordinateBase::OneBased => pos + 1,
+        }
+    }
+}
+
+/// Lookup from positions on a sample's concatenated contigs back to
+/// contig coordinates
+///
+/// Built by `AgcFile::build_coordinate_index` from the layout of
+/// `AgcFile::contig_offsets`; each `locate` is a binary search over the
+/// contigs, so the index suits many lookups, e.g. for whole-genome plots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinateIndex {
+    /// `(contig, global_start, global_end)` in layout order
+    contigs: Vec<(String, u64, u64)>,
+}
+
+impl CoordinateIndex {
+    fn new(contigs: Vec<(String, u64, u64)>) -> Self {
+        CoordinateIndex { contigs }
+    }
+
+    /// Find the contig position at a global position
+    ///
+    /// # Arguments
+    /// * `global_pos` - 0-based position on the concatenated contigs
+    ///
+    /// # Returns
+    /// The contig and 0-based offset within it, or None past the last
+    /// contig; empty contigs hold no position and are never returned
+    pub fn locate(&self, global_pos: u64) -> Option<(String, i32)> {
+        let i = self
+            .contigs
+            .partition_point(|(_, _, end)| *end <= global_pos);
+        self.contigs
+            .get(i)
+            .map(|(name, start, _)| (name.clone(), (global_pos - start) as i32))
+    }
+
+    /// Total length of the concatenated contigs
+    pub fn total_length(&self) -> u64 {
+        self.contigs.last().map_or(0, |(_, _, end)| *end)
+    }
+}
+
+impl AgcFile {
+    /// Build a reusable index from global positions to contig coordinates
+    ///
+    /// Contigs are laid out end to end as by [`AgcFile::contig_offsets`],
+    /// which is read once here.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the index
+    pub fn build_coordinate_index(&self, sample: &str) -> Result<CoordinateIndex, AgcError> {
+        Ok(CoordinateIndex::new(self.contig_offsets(sample)?))
+    }
+
+    /// Get how positions in non-reference samples relate to the reference
+    ///
+    /// AGC compresses each contig as segments matched against the
+    /// reference's k-mers but stores no alignment, and contigs keep their
+    /// own lengths and coordinates. Every AGC archive therefore reports
+    /// `CoordinateSystem::Independent`: mapping a position from one sample
+    /// to another needs a liftover from an actual alignment.
+    ///
+    /// # Returns
+    /// Result containing the coordinate system of the archive
+    pub fn coordinate_system(&self) -> Result<CoordinateSystem, AgcError> {
+        Ok(CoordinateSystem::Independent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_coordinate_system() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        assert_eq!(
+            agc.coordinate_system().unwrap(),
+            CoordinateSystem::Independent
+        );
+    }
+
+    #[test]
+    fn test_coordinate_index_locate() {
+        let index = CoordinateIndex::new(vec![
+            ("chr1".to_string(), 0, 10),
+            ("empty".to_string(), 10, 10),
+            ("chr2".to_string(), 10, 15),
+            ("chr3".to_string(), 15, 30),
+        ]);
+        assert_eq!(index.total_length(), 30);
+        assert_eq!(index.locate(0), Some(("chr1".to_string(), 0)));
+        assert_eq!(index.locate(9), Some(("chr1".to_string(), 9)));
+        assert_eq!(index.locate(10), Some(("chr2".to_string(), 0)));
+        assert_eq!(index.locate(14), Some(("chr2".to_string(), 4)));
+        assert_eq!(index.locate(15), Some(("chr3".to_string(), 0)));
+        assert_eq!(index.locate(29), Some(("chr3".to_string(), 14)));
+        assert_eq!(index.locate(30), None);
+        assert_eq!(CoordinateIndex::new(Vec::new()).locate(0), None);
+    }
+
+    #[test]
+    fn test_build_coordinate_index() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let index = agc.build_coordinate_index(&sample).unwrap();
+        let offsets = agc.contig_offsets(&sample).unwrap();
+
+        for (name, start, end) in &offsets {
+            if start < end {
+                assert_eq!(index.locate(*start), Some((name.clone(), 0)));
+                assert_eq!(
+                    index.locate(end - 1),
+                    Some((name.clone(), (end - start - 1) as i32))
+                );
+            }
+        }
+        assert_eq!(index.locate(index.total_length()), None);
+    }
+
+    #[test]
+    fn test_coordinate_base() {
+        assert_eq!(CoordinateBase::default(), CoordinateBase::ZeroBased);
+        assert_eq!(CoordinateBase::ZeroBased.interval(10, 20), (10, 20));
+        assert_eq!(CoordinateBase::OneBased.interval(10, 20), (11, 20));
+        assert_eq!(CoordinateBase::OneBased.position(0), 1);
+    }
+}
@@ -0,0 +1,292 @@
+use std::ffi::NulError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Error type for AGC operations
+#[derive(Debug)]
+pub enum AgcError {
+    /// A call into the AGC library failed
+    Ffi(String),
+    /// The AGC library returned something its API rules out, such as more
+    /// bases than were requested; usually a library version mismatch
+    FfiContract(String),
+    /// An argument could not be passed to the AGC library
+    InvalidArgument(String),
+    /// The archive structure could not be read
+    InvalidArchive(String),
+    /// The file looks like the start of an archive whose footer has not
+    /// been written yet, e.g. because AGC is still writing it
+    IncompleteArchive(String),
+    /// The contig does not exist (in the given sample, if any)
+    ContigNotFound {
+        sample: Option<String>,
+        name: String,
+    },
+    /// A contig was named without a sample, but several samples have it
+    AmbiguousContig { name: String, samples: Vec<String> },
+    /// A region string could not be parsed
+    InvalidRegion(String),
+    /// A coordinate range is empty, reversed or outside the contig
+    InvalidRange {
+        name: String,
+        start: i64,
+        end: i64,
+        len: i32,
+    },
+    /// A sample name is provided by more than one archive
+    DuplicateSample(String),
+    /// The sample does not exist
+    SampleNotFound(String),
+    /// The sample has no contigs, so an aggregate over them is undefined
+    EmptySample(String),
+    /// AGC named a reference sample that is not among its samples
+    InconsistentReference(String),
+    /// The caller asked for the operation to stop before it finished
+    Cancelled,
+    /// A fetch spans more bases than the handle's `max_fetch_len`
+    FetchTooLarge { requested: usize, max: usize },
+    /// AGC reported a contig length that does not fit in an `i32`
+    CoordinateOverflow { name: String, value: i64 },
+    /// A base cannot be represented in the requested encoding
+    InvalidBase { position: usize, base: u8 },
+    /// An I/O error while reading the archive directly
+    Io(io::Error),
+}
+
+impl fmt::Display for AgcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgcError::Ffi(msg) => write!(f, "{}", msg),
+            AgcError::FfiContract(msg) => write!(f, "AGC library contract violated: {}", msg),
+            AgcError::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            AgcError::InvalidArchive(msg) => write!(f, "Invalid AGC archive: {}", msg),
+            AgcError::IncompleteArchive(msg) => write!(f, "Incomplete AGC archive: {}", msg),
+            AgcError::ContigNotFound {
+                sample: Some(sample),
+                name,
+            } => write!(f, "Contig not found: {} in sample {}", name, sample),
+            AgcError::ContigNotFound { sample: None, name } => {
+                write!(f, "Contig not found: {}", name)
+            }
+            AgcError::AmbiguousContig { name, samples } => write!(
+                f,
+                "Contig {} is in several samples ({}); give a sample",
+                name,
+                samples.join(", ")
+            ),
+            AgcError::InvalidRegion(region) => write!(f, "Invalid region: {}", region),
+            AgcError::InvalidRange {
+                name,
+                start,
+                end,
+                len,
+            } => write!(
+                f,
+                "Invalid range {}-{} for contig {} of length {}",
+                start, end, name, len
+            ),
+            AgcError::DuplicateSample(sample) => write!(f, "Duplicate sample: {}", sample),
+            AgcError::SampleNotFound(sample) => write!(f, "Sample not found: {}", sample),
+            AgcError::EmptySample(sample) => write!(f, "Sample has no contigs: {}", sample),
+            AgcError::InconsistentReference(sample) => {
+                write!(f, "Reference sample {} is not in the sample list", sample)
+            }
+            AgcError::Cancelled => write!(f, "Operation cancelled"),
+            AgcError::FetchTooLarge { requested, max } => write!(
+                f,
+                "Fetch of {} bases exceeds the limit of {}",
+                requested, max
+            ),
+            AgcError::CoordinateOverflow { name, value } => write!(
+                f,
+                "Length of contig {} overflows i32 (AGC returned {})",
+                name, value
+            ),
+            AgcError::InvalidBase { position, base } => write!(
+                f,
+                "Invalid base {:?} at position {}",
+                *base as char, position
+            ),
+            AgcError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AgcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AgcError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for AgcError {
+    fn from(msg: String) -> Self {
+        AgcError::Ffi(msg)
+    }
+}
+
+impl From<NulError> for AgcError {
+    fn from(e: NulError) -> Self {
+        AgcError::InvalidArgument(e.to_string())
+    }
+}
+
+impl From<io::Error> for AgcError {
+    fn from(e: io::Error) -> Self {
+        AgcError::Io(e)
+    }
+}
+
+/// Convert for use in `io::Result` code
+///
+/// `AgcError::Io` gives back the wrapped error. Every other variant is
+/// wrapped whole, so the message is kept and `get_ref`/`into_inner` can
+/// downcast to the `AgcError`, with a kind chosen by its cause: lookups
+/// of missing names are `NotFound`, bad arguments `InvalidInput`, archive
+/// problems `InvalidData`, cancellation `Interrupted`, and AGC failures
+/// `Other`.
+impl From<AgcError> for io::Error {
+    fn from(e: AgcError) -> Self {
+        let kind = match e {
+            AgcError::Io(e) => return e,
+            AgcError::ContigNotFound { .. } | AgcError::SampleNotFound(_) => {
+                io::ErrorKind::NotFound
+            }
+            AgcError::InvalidArgument(_)
+            | AgcError::AmbiguousContig { .. }
+            | AgcError::InvalidRegion(_)
+            | AgcError::InvalidRange { .. }
+            | AgcError::InvalidBase { .. }
+            | AgcError::DuplicateSample(_)
+            | AgcError::EmptySample(_)
+            | AgcError::FetchTooLarge { .. } => io::ErrorKind::InvalidInput,
+            AgcError::InvalidArchive(_)
+            | AgcError::IncompleteArchive(_)
+            | AgcError::InconsistentReference(_)
+            | AgcError::CoordinateOverflow { .. } => io::ErrorKind::InvalidData,
+            AgcError::Cancelled => io::ErrorKind::Interrupted,
+            AgcError::Ffi(_) | AgcError::FfiContract(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// Why an archive could not be opened, returned by `AgcFile::try_open`
+///
+/// Separates failures worth retrying from those that are not: only
+/// `IncompleteArchive` is expected to go away by itself, once the process
+/// writing the archive finishes.
+#[derive(Debug)]
+pub enum OpenError {
+    /// The file does not exist
+    NotFound(PathBuf),
+    /// The file exists but may not be read
+    PermissionDenied(PathBuf),
+    /// The file is not an AGC archive
+    NotAnArchive(String),
+    /// The file looks like an archive that is still being written
+    IncompleteArchive(String),
+    /// Any other failure, including AGC itself refusing the file
+    Other(String),
+}
+
+impl OpenError {
+    /// Whether opening the same path again later may succeed
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, OpenError::IncompleteArchive(_))
+    }
+
+    /// Classify an error from checking the file at `path`
+    pub(crate) fn classify(e: AgcError, path: &Path) -> Self {
+        match e {
+            AgcError::Io(e) if e.kind() == io::ErrorKind::NotFound => {
+                OpenError::NotFound(path.to_path_buf())
+            }
+            AgcError::Io(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                OpenError::PermissionDenied(path.to_path_buf())
+            }
+            AgcError::InvalidArchive(msg) => OpenError::NotAnArchive(msg),
+            AgcError::IncompleteArchive(msg) => OpenError::IncompleteArchive(msg),
+            e => OpenError::Other(e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for OpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenError::NotFound(path) => write!(f, "File not found: {}", path.display()),
+            OpenError::PermissionDenied(path) => {
+                write!(f, "Permission denied: {}", path.display())
+            }
+            OpenError::NotAnArchive(msg) => write!(f, "Invalid AGC archive: {}", msg),
+            OpenError::IncompleteArchive(msg) => write!(f, "Incomplete AGC archive: {}", msg),
+            OpenError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OpenError {}
+
+impl From<OpenError> for AgcError {
+    fn from(e: OpenError) -> Self {
+        match e {
+            OpenError::NotFound(path) => AgcError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found: {}", path.display()),
+            )),
+            OpenError::PermissionDenied(path) => AgcError::Io(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Permission denied: {}", path.display()),
+            )),
+            OpenError::NotAnArchive(msg) => AgcError::InvalidArchive(msg),
+            OpenError::IncompleteArchive(msg) => AgcError::IncompleteArchive(msg),
+            OpenError::Other(msg) => AgcError::Ffi(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_io_error() {
+        let cases = [
+            (
+                AgcError::ContigNotFound {
+                    sample: Some("a".to_string()),
+                    name: "chr9".to_string(),
+                },
+                io::ErrorKind::NotFound,
+            ),
+            (
+                AgcError::InvalidRegion("chr1:x".to_string()),
+                io::ErrorKind::InvalidInput,
+            ),
+            (
+                AgcError::InvalidArchive("bad magic".to_string()),
+                io::ErrorKind::InvalidData,
+            ),
+            (AgcError::Cancelled, io::ErrorKind::Interrupted),
+            (AgcError::Ffi("boom".to_string()), io::ErrorKind::Other),
+        ];
+        for (e, kind) in cases {
+            let message = e.to_string();
+            let io_err = io::Error::from(e);
+            assert_eq!(io_err.kind(), kind);
+            assert_eq!(io_err.to_string(), message);
+            assert!(io_err
+                .get_ref()
+                .is_some_and(|inner| inner.downcast_ref::<AgcError>().is_some()));
+        }
+
+        let original = io::Error::new(io::ErrorKind::UnexpectedEof, "short read");
+        let io_err = io::Error::from(AgcError::Io(original));
+        assert_eq!(io_err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(io_err.to_string(), "short read");
+    }
+}
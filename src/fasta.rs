@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::policy::Collector;
+use crate::{AgcError, AgcFile, Collected, ErrorPolicy, SAMPLE_SEPARATOR};
+
+/// Line terminator used when writing FASTA
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`, for Windows tools that require it
+    CrLf,
+}
+
+impl LineEnding {
+    /// The terminator bytes
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// Header style for FASTA records covering several samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderFormat {
+    /// `>contig`; names may repeat across samples
+    #[default]
+    Contig,
+    /// `>sample#contig`, unique across samples (PanSN style)
+    Qualified,
+    /// `>contig sample=sample`, keeping the contig name as the record ID
+    Annotated,
+}
+
+impl HeaderFormat {
+    /// Format the header line of a record, without the leading `>`
+    pub fn header(&self, sample: &str, contig: &str) -> String {
+        match self {
+            HeaderFormat::Contig => contig.to_string(),
+            HeaderFormat::Qualified => format!("{}{}{}", sample, SAMPLE_SEPARATOR, contig),
+            HeaderFormat::Annotated => format!("{} sample={}", contig, sample),
+        }
+    }
+}
+
+/// Writes FASTA records to an output
+pub struct FastaWriter<W: Write> {
+    out: W,
+    line_width: usize,
+    line_ending: LineEnding,
+}
+
+impl<W: Write> FastaWriter<W> {
+    /// Create a writer
+    ///
+    /// # Arguments
+    /// * `out` - output to write to
+    /// * `line_width` - bases per sequence line; 0 writes each sequence on one line
+    pub fn new(out: W, line_width: usize) -> Self {
+        FastaWriter {
+            out,
+            line_width,
+            line_ending: LineEnding::default(),
+        }
+    }
+
+    /// Set the line terminator (default `\n`)
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write a single record
+    ///
+    /// # Arguments
+    /// * `header` - header line without the leading `>`
+    /// * `seq` - sequence bytes
+    pub fn write_record(&mut self, header: &str, seq: &[u8]) -> std::io::Result<()> {
+        let eol = self.line_ending.as_bytes();
+
+        self.out.write_all(b">")?;
+        self.out.write_all(header.as_bytes())?;
+        self.out.write_all(eol)?;
+
+        if self.line_width == 0 {
+            if !seq.is_empty() {
+                self.out.write_all(seq)?;
+                self.out.write_all(eol)?;
+            }
+        } else {
+            for line in seq.chunks(self.line_width) {
+                self.out.write_all(line)?;
+                self.out.write_all(eol)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush and return the underlying output
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// File names `<sample>.fa` for samples, with any `/` replaced by `_`
+///
+/// Two different samples that map to one name, such as `a/b` and `a_b`,
+/// give `AgcError::InvalidArgument`, since one file would overwrite the
+/// other. A sample repeated as is keeps its one name.
+pub(crate) fn sample_file_names<'a>(
+    samples: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<String>, AgcError> {
+    let mut owners: HashMap<String, &str> = HashMap::new();
+    let mut names = Vec::new();
+    for sample in samples {
+        let name = format!("{}.fa", sample.replace('/', "_"));
+        let owner = *owners.entry(name.clone()).or_insert(sample);
+        if owner != sample {
+            return Err(AgcError::InvalidArgument(format!(
+                "Samples {} and {} would both be written to {}",
+                owner, sample, name
+            )));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+impl AgcFile {
+    /// Write all contigs of a sample as FASTA
+    ///
+    /// Records are fetched and written one contig at a time.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `out` - output to write to
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    ///
+    /// # Returns
+    /// Result containing the number of records written or an error
+    pub fn write_sample_fasta<W: Write>(
+        &self,
+        sample: &str,
+        out: &mut W,
+        line_width: usize,
+        line_ending: LineEnding,
+    ) -> Result<usize, AgcError> {
+        let mut writer = FastaWriter::new(out, line_width).line_ending(line_ending);
+        let contigs = self.list_ctg(Some(sample))?;
+
+        for contig in &contigs {
+            let seq = self.ctg_full_bytes(Some(sample), contig)?;
+            writer.write_record(self.display_contig(contig), &seq)?;
+        }
+
+        writer.into_inner()?;
+        Ok(contigs.len())
+    }
+
+    /// Write every contig of every sample as multi-FASTA
+    ///
+    /// Samples are written in archive order and records are fetched and
+    /// written one contig at a time.
+    ///
+    /// # Arguments
+    /// * `out` - output to write to
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    /// * `header_fmt` - how sample and contig names appear in headers
+    ///
+    /// # Returns
+    /// Result containing the number of records written or an error
+    pub fn write_all_fasta<W: Write>(
+        &self,
+        out: &mut W,
+        line_width: usize,
+        line_ending: LineEnding,
+        header_fmt: HeaderFormat,
+    ) -> Result<usize, AgcError> {
+        let written = self.write_all_fasta_with(
+            out,
+            line_width,
+            line_ending,
+            header_fmt,
+            ErrorPolicy::FailFast,
+        )?;
+        Ok(written.items.len())
+    }
+
+    /// Write every contig of every sample as multi-FASTA, handling errors
+    /// per `policy`
+    ///
+    /// Under `ErrorPolicy::Collect` a contig that cannot be fetched, or a
+    /// sample whose contigs cannot be listed, is left out of the output
+    /// and its error kept. Errors writing to `out` abort under either
+    /// policy, since the output would be truncated.
+    ///
+    /// # Arguments
+    /// * `out` - output to write to
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    /// * `header_fmt` - how sample and contig names appear in headers
+    /// * `policy` - whether to stop at or collect per-contig errors
+    ///
+    /// # Returns
+    /// Result containing the `(sample, contig)` of each record written and
+    /// any collected errors
+    pub fn write_all_fasta_with<W: Write>(
+        &self,
+        out: &mut W,
+        line_width: usize,
+        line_ending: LineEnding,
+        header_fmt: HeaderFormat,
+        policy: ErrorPolicy,
+    ) -> Result<Collected<(String, String)>, AgcError> {
+        let mut writer = FastaWriter::new(out, line_width).line_ending(line_ending);
+        let mut written = Collector::new(policy, 0);
+
+        for sample in self.list_sample()? {
+            let contigs = match self.list_ctg(Some(&sample)) {
+                Ok(contigs) => contigs,
+                Err(e) => {
+                    written.push(Err(e.into()))?;
+                    continue;
+                }
+            };
+            for contig in contigs {
+                let seq = match self.ctg_full_bytes(Some(&sample), &contig) {
+                    Ok(seq) => seq,
+                    Err(e) => {
+                        written.push(Err(e))?;
+                        continue;
+                    }
+                };
+                let header = header_fmt.header(&sample, self.display_contig(&contig));
+                writer.write_record(&header, &seq)?;
+                written.push(Ok((sample.clone(), contig)))?;
+            }
+        }
+
+        writer.into_inner()?;
+        Ok(written.finish())
+    }
+
+    /// Extract all contigs of a sample to a FASTA file
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `path` - output file path
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    pub fn extract_sample(
+        &self,
+        sample: &str,
+        path: impl AsRef<Path>,
+        line_width: usize,
+        line_ending: LineEnding,
+    ) -> Result<(), AgcError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_sample_fasta(sample, &mut out, line_width, line_ending)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Extract a subset of samples to one FASTA file each
+    ///
+    /// Every name is checked before anything is written. Each sample goes to
+    /// `<dir>/<sample>.fa`, with any `/` in the name replaced by `_`.
+    /// Samples that would share a file, such as `a/b` and `a_b`, give
+    /// `AgcError::InvalidArgument`.
+    ///
+    /// # Arguments
+    /// * `samples` - names of the samples to extract
+    /// * `dir` - existing output directory
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    ///
+    /// # Returns
+    /// `AgcError::SampleNotFound` listing every absent sample, or the first
+    /// error encountered while writing
+    pub fn extract_samples_to_dir(
+        &self,
+        samples: &[&str],
+        dir: impl AsRef<Path>,
+        line_width: usize,
+        line_ending: LineEnding,
+    ) -> Result<(), AgcError> {
+        self.extract_samples_to_dir_with(
+            samples,
+            dir,
+            line_width,
+            line_ending,
+            ErrorPolicy::FailFast,
+        )?;
+        Ok(())
+    }
+
+    /// Extract a subset of samples to one FASTA file each, handling errors
+    /// per `policy`
+    ///
+    /// Under `ErrorPolicy::FailFast` this is `extract_samples_to_dir`.
+    /// Under `ErrorPolicy::Collect` each absent sample, and each sample
+    /// that fails to be written, is skipped and its error kept, and the
+    /// rest are still extracted.
+    ///
+    /// # Arguments
+    /// * `samples` - names of the samples to extract
+    /// * `dir` - existing output directory
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    /// * `line_ending` - line terminator
+    /// * `policy` - whether to stop at or collect per-sample errors
+    ///
+    /// # Returns
+    /// Result containing the names of the samples written and any
+    /// collected errors; samples that would share a file give
+    /// `AgcError::InvalidArgument` under either policy
+    pub fn extract_samples_to_dir_with(
+        &self,
+        samples: &[&str],
+        dir: impl AsRef<Path>,
+        line_width: usize,
+        line_ending: LineEnding,
+        policy: ErrorPolicy,
+    ) -> Result<Collected<String>, AgcError> {
+        let known = self.list_sample()?;
+        let missing: Vec<&str> = samples
+            .iter()
+            .copied()
+            .filter(|sample| !known.iter().any(|k| k == sample))
+            .collect();
+        if policy == ErrorPolicy::FailFast && !missing.is_empty() {
+            return Err(AgcError::SampleNotFound(missing.join(", ")));
+        }
+
+        let names = sample_file_names(samples.iter().copied())?;
+        let mut written = Collector::new(policy, samples.len());
+        for (&sample, name) in samples.iter().zip(names) {
+            if missing.contains(&sample) {
+                written.push(Err(AgcError::SampleNotFound(sample.to_string())))?;
+                continue;
+            }
+            let path = dir.as_ref().join(name);
+            let result = self.extract_sample(sample, path, line_width, line_ending);
+            written.push(result.map(|()| sample.to_string()))?;
+        }
+        Ok(written.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_write_record_wrapping() {
+        let mut writer = FastaWriter::new(Vec::new(), 4);
+        writer.write_record("ctg", b"ACGTACGTAC").unwrap();
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out, b">ctg\nACGT\nACGT\nAC\n");
+    }
+
+    #[test]
+    fn test_write_record_crlf() {
+        let mut writer = FastaWriter::new(Vec::new(), 0).line_ending(LineEnding::CrLf);
+        writer.write_record("ctg", b"ACGT").unwrap();
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out, b">ctg\r\nACGT\r\n");
+    }
+
+    #[test]
+    fn test_header_formats() {
+        assert_eq!(HeaderFormat::Contig.header("HG002", "chr1"), "chr1");
+        assert_eq!(
+            HeaderFormat::Qualified.header("HG002", "chr1"),
+            "HG002#chr1"
+        );
+        assert_eq!(
+            HeaderFormat::Annotated.header("HG002", "chr1"),
+            "chr1 sample=HG002"
+        );
+    }
+
+    #[test]
+    fn test_write_all_fasta() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+        let n_contigs: usize = samples.iter().map(|s| agc.n_ctg(s).unwrap() as usize).sum();
+        let sample = &samples[0];
+        let contig = agc.list_ctg(Some(sample)).unwrap().remove(0);
+
+        let mut headers = Vec::new();
+        for fmt in [
+            HeaderFormat::Contig,
+            HeaderFormat::Qualified,
+            HeaderFormat::Annotated,
+        ] {
+            let mut out = Vec::new();
+            let n = agc
+                .write_all_fasta(&mut out, 60, LineEnding::Lf, fmt)
+                .expect("Failed to write FASTA");
+            assert_eq!(n, n_contigs);
+
+            let text = String::from_utf8(out).unwrap();
+            assert_eq!(text.matches('>').count(), n_contigs);
+            let first = text.lines().next().unwrap().to_string();
+            assert_eq!(first, format!(">{}", fmt.header(sample, &contig)));
+            headers.push(first);
+        }
+
+        assert_eq!(headers[0], format!(">{}", contig));
+        assert_eq!(headers[1], format!(">{}#{}", sample, contig));
+        assert_eq!(headers[2], format!(">{} sample={}", contig, sample));
+    }
+
+    #[test]
+    fn test_write_all_fasta_with() {
+        // A fetch limit at the shortest contig makes every longer one fail
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let inventory = plain.inventory().unwrap();
+        let shortest = inventory.iter().map(|(_, _, len)| *len).min().unwrap();
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(shortest as usize)
+            .open()
+            .unwrap();
+        let fits: Vec<(String, String)> = inventory
+            .iter()
+            .filter(|(_, _, len)| *len == shortest)
+            .map(|(sample, contig, _)| (sample.clone(), contig.clone()))
+            .collect();
+        assert!(fits.len() < inventory.len());
+
+        let write = |out: &mut Vec<u8>, policy| {
+            agc.write_all_fasta_with(out, 60, LineEnding::Lf, HeaderFormat::Qualified, policy)
+        };
+        assert!(matches!(
+            write(&mut Vec::new(), ErrorPolicy::FailFast),
+            Err(AgcError::FetchTooLarge { .. })
+        ));
+
+        let mut out = Vec::new();
+        let collected = write(&mut out, ErrorPolicy::Collect).expect("Collect should not abort");
+        assert_eq!(collected.items, fits);
+        assert_eq!(collected.errors.len(), inventory.len() - fits.len());
+        assert!(collected
+            .errors
+            .iter()
+            .all(|e| matches!(e, AgcError::FetchTooLarge { .. })));
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches('>').count(), fits.len());
+    }
+
+    #[test]
+    fn test_extract_samples_to_dir() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+        let chosen = [samples[0].as_str(), samples[samples.len() - 1].as_str()];
+        let dir = tempfile::tempdir().unwrap();
+
+        agc.extract_samples_to_dir(&chosen, dir.path(), 60, LineEnding::Lf)
+            .expect("Failed to extract samples");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+        for sample in chosen {
+            let text = std::fs::read_to_string(dir.path().join(format!("{}.fa", sample))).unwrap();
+            let mut expected = Vec::new();
+            agc.write_sample_fasta(sample, &mut expected, 60, LineEnding::Lf)
+                .unwrap();
+            assert_eq!(text.as_bytes(), expected);
+        }
+
+        let empty = tempfile::tempdir().unwrap();
+        let err = agc
+            .extract_samples_to_dir(
+                &[chosen[0], "no_such_a", "no_such_b"],
+                empty.path(),
+                60,
+                LineEnding::Lf,
+            )
+            .unwrap_err();
+        match err {
+            AgcError::SampleNotFound(names) => assert_eq!(names, "no_such_a, no_such_b"),
+            other => panic!("Unexpected error: {}", other),
+        }
+        assert_eq!(std::fs::read_dir(empty.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_sample_file_names() {
+        assert_eq!(
+            sample_file_names(["HG002", "a/b", "HG002"]).unwrap(),
+            ["HG002.fa", "a_b.fa", "HG002.fa"]
+        );
+        assert!(matches!(
+            sample_file_names(["a/b", "HG002", "a_b"]),
+            Err(AgcError::InvalidArgument(msg)) if msg.contains("a/b") && msg.contains("a_b")
+        ));
+
+        // Checked before anything is written, under either policy
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            agc.extract_samples_to_dir_with(
+                &["a/b", "a_b"],
+                dir.path(),
+                60,
+                LineEnding::Lf,
+                ErrorPolicy::Collect,
+            ),
+            Err(AgcError::InvalidArgument(_))
+        ));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_extract_samples_to_dir_collect() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+        let (good, blocked) = (samples[0].as_str(), samples[1].as_str());
+        let dir = tempfile::tempdir().unwrap();
+        // A directory in the way makes writing this one sample fail
+        std::fs::create_dir(dir.path().join(format!("{}.fa", blocked))).unwrap();
+
+        let query = [blocked, "no_such_sample", good];
+        let extract = |policy| {
+            agc.extract_samples_to_dir_with(&query, dir.path(), 60, LineEnding::Lf, policy)
+        };
+        assert!(extract(ErrorPolicy::FailFast).is_err());
+
+        let collected = extract(ErrorPolicy::Collect).expect("Collect should not abort");
+        assert_eq!(collected.items, [good]);
+        assert_eq!(collected.errors.len(), 2);
+        assert!(matches!(collected.errors[0], AgcError::Io(_)));
+        assert!(matches!(collected.errors[1], AgcError::SampleNotFound(_)));
+        assert!(dir.path().join(format!("{}.fa", good)).is_file());
+    }
+
+    #[test]
+    fn test_write_sample_fasta_line_endings() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+
+        let mut lf = Vec::new();
+        agc.write_sample_fasta(&sample, &mut lf, 60, LineEnding::Lf)
+            .expect("Failed to write FASTA");
+        assert!(lf.starts_with(b">"));
+        assert!(!lf.contains(&b'\r'));
+
+        let mut crlf = Vec::new();
+        let n = agc
+            .write_sample_fasta(&sample, &mut crlf, 60, LineEnding::CrLf)
+            .expect("Failed to write FASTA");
+        assert_eq!(n, agc.n_ctg(&sample).unwrap() as usize);
+        let newlines = crlf.iter().filter(|&&b| b == b'\n').count();
+        let terminators = crlf.windows(2).filter(|w| w == b"\r\n").count();
+        assert!(newlines > 0);
+        assert_eq!(newlines, terminators, "Every line should end in \\r\\n");
+    }
+
+    #[test]
+    fn test_multi_sample_exports_crlf() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let samples = agc.list_sample().unwrap();
+
+        let (mut lf, mut crlf) = (Vec::new(), Vec::new());
+        agc.write_all_fasta(&mut lf, 7, LineEnding::Lf, HeaderFormat::Qualified)
+            .unwrap();
+        agc.write_all_fasta(&mut crlf, 7, LineEnding::CrLf, HeaderFormat::Qualified)
+            .unwrap();
+        let lf = String::from_utf8(lf).unwrap();
+        assert_eq!(String::from_utf8(crlf).unwrap(), lf.replace('\n', "\r\n"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let chosen: Vec<&str> = samples.iter().map(String::as_str).collect();
+        agc.extract_samples_to_dir(&chosen, dir.path(), 7, LineEnding::CrLf)
+            .unwrap();
+        for sample in chosen {
+            let mut expected = Vec::new();
+            agc.write_sample_fasta(sample, &mut expected, 7, LineEnding::CrLf)
+                .unwrap();
+            let written = std::fs::read(dir.path().join(format!("{}.fa", sample))).unwrap();
+            assert!(written.windows(2).any(|w| w == b"\r\n"));
+            assert_eq!(written, expected);
+        }
+    }
+}
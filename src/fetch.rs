@@ -0,0 +1,346 @@
+use crate::{check_range, AgcError, AgcFile, Strand};
+
+/// Most bases between two requested positions that `get_bases_at` fetches
+/// rather than starting a new fetch
+const BASES_AT_MAX_GAP: i32 = 4096;
+
+/// Most bases a single `get_bases_at` fetch spans, however dense the
+/// positions
+const BASES_AT_MAX_SPAN: i32 = 65_536;
+
+/// What AGC returned for a sequence request, compared with what was asked
+///
+/// AGC clips requests that run past the end of a contig without reporting
+/// it; comparing `returned` with `requested` makes the clipping visible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchInfo {
+    /// Sample the request was made for (None for unspecified)
+    pub sample: Option<String>,
+    /// Contig name, after alias resolution
+    pub name: String,
+    /// 0-based start offset requested
+    pub start: i32,
+    /// 0-based exclusive end offset requested
+    pub end: i32,
+    /// Number of bases requested, `end - start`
+    pub requested: usize,
+    /// Number of bases AGC returned
+    pub returned: usize,
+}
+
+impl FetchInfo {
+    /// Whether AGC returned fewer bases than requested
+    pub fn is_clipped(&self) -> bool {
+        self.returned < self.requested
+    }
+}
+
+/// A fetched region together with where it came from
+///
+/// Returned by `AgcFile::get_region_annotated`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedSeq {
+    /// Bases of the region
+    pub seq: Vec<u8>,
+    /// Sample the bases were read from (None for unspecified)
+    pub sample: Option<String>,
+    /// Contig name, after alias resolution
+    pub contig: String,
+    /// 0-based start offset
+    pub start: i32,
+    /// 0-based exclusive end offset of the bases returned, which is short
+    /// of the requested end when AGC clipped the range
+    pub end: i32,
+    /// Strand of `seq` relative to the contig
+    pub strand: Strand,
+    /// Id of the archive, see `AgcFile::archive_id`
+    pub archive_id: String,
+}
+
+impl AgcFile {
+    /// Get a region bundled with its sample, contig, interval and archive
+    ///
+    /// Names are reported as resolved: a `sample#contig` name is split and
+    /// a contig alias is replaced by the archive's name. The bases are on
+    /// the forward strand. The archive id is computed on the first call.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    ///
+    /// # Returns
+    /// Result containing the bases and their provenance
+    pub fn get_region_annotated(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<AnnotatedSeq, AgcError> {
+        let seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        let (sample, contig) = self.resolve_name(sample, name)?;
+        Ok(AnnotatedSeq {
+            sample: sample.map(str::to_string),
+            contig: contig.to_string(),
+            start,
+            end: start + seq.len() as i32,
+            strand: Strand::Plus,
+            archive_id: self.archive_id()?,
+            seq,
+        })
+    }
+
+    /// Get the base at each of many positions of a contig
+    ///
+    /// Positions may come in any order and repeat. They are sorted and
+    /// grouped so that each fetch covers a cluster of nearby positions,
+    /// with at most `BASES_AT_MAX_GAP` bases between neighbours, instead
+    /// of costing one fetch per position; scattered positions still fetch
+    /// only a base or a short span each. A cluster spans at most
+    /// `BASES_AT_MAX_SPAN` bases, or `max_fetch_len` if that is smaller,
+    /// so dense positions along a chromosome never fetch it whole.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `positions` - 0-based positions
+    ///
+    /// # Returns
+    /// Result containing the base at each position, in the order given,
+    /// `AgcError::InvalidRange` if a position lies outside the contig, or
+    /// `AgcError::Ffi` if AGC returns fewer bases than the contig's length
+    /// promised
+    pub fn get_bases_at(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        positions: &[i32],
+    ) -> Result<Vec<u8>, AgcError> {
+        let len = self.ctg_len(sample, name)?;
+        for &pos in positions {
+            check_range(name, pos as i64, pos as i64 + 1, len)?;
+        }
+
+        let max_span = self
+            .max_fetch_len
+            .map_or(BASES_AT_MAX_SPAN, |max| {
+                BASES_AT_MAX_SPAN.min(max.min(i32::MAX as usize) as i32)
+            })
+            .max(1);
+        let mut bases = vec![0; positions.len()];
+        for cluster in position_clusters(positions, BASES_AT_MAX_GAP, max_span) {
+            let start = positions[cluster[0]];
+            let end = positions[cluster[cluster.len() - 1]] + 1;
+            let seq = self.ctg_seq_bytes(sample, name, start, end)?;
+            for i in cluster {
+                bases[i] = base_at(name, &seq, start, positions[i])?;
+            }
+        }
+        Ok(bases)
+    }
+
+    /// Get the details of the most recent sequence request sent to AGC
+    ///
+    /// Every sequence accessor ends in the same call to AGC, so this covers
+    /// them all; fetches served from the contig cache do not reach AGC and
+    /// are not recorded. When several threads share the handle, the info is
+    /// from whichever request finished last.
+    ///
+    /// # Returns
+    /// The last request's info, or None if no sequence has been fetched yet
+    pub fn last_fetch_info(&self) -> Option<FetchInfo> {
+        self.last_fetch.lock().unwrap().clone()
+    }
+}
+
+/// Indices of `positions` grouped into clusters, in increasing position,
+/// where neighbours are at most `max_gap` bases apart and each cluster
+/// spans at most `max_span` bases
+fn position_clusters(positions: &[i32], max_gap: i32, max_span: i32) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..positions.len()).collect();
+    order.sort_by_key(|&i| positions[i]);
+
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    for i in order {
+        match clusters.last_mut() {
+            Some(cluster)
+                if positions[i] - positions[cluster[cluster.len() - 1]] <= max_gap
+                    && positions[i] - positions[cluster[0]] < max_span =>
+            {
+                cluster.push(i)
+            }
+            _ => clusters.push(vec![i]),
+        }
+    }
+    clusters
+}
+
+/// The base at `pos` of a span fetched from `start`, if AGC returned it
+fn base_at(name: &str, seq: &[u8], start: i32, pos: i32) -> Result<u8, AgcError> {
+    seq.get((pos - start) as usize).copied().ok_or_else(|| {
+        AgcError::Ffi(format!(
+            "AGC returned {} bases of {} from {}, short of position {}",
+            seq.len(),
+            name,
+            start,
+            pos
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_last_fetch_info() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        assert_eq!(agc.last_fetch_info(), None);
+
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        agc.get_ctg_seq(Some(&sample), &contig, 0, len).unwrap();
+        let info = agc.last_fetch_info().expect("Fetch should be recorded");
+        assert_eq!(info.sample.as_deref(), Some(sample.as_str()));
+        assert_eq!(info.name, contig);
+        assert_eq!((info.start, info.end), (0, len));
+        assert_eq!(info.requested, len as usize);
+        assert!(!info.is_clipped());
+    }
+
+    #[test]
+    fn test_position_clusters() {
+        let positions = [500, 3, 10, 9000, 3, 505];
+        assert_eq!(
+            position_clusters(&positions, 100, 1000),
+            [vec![1, 4, 2], vec![0, 5], vec![3]]
+        );
+        assert_eq!(position_clusters(&positions, 10_000, 10_000).len(), 1);
+        assert!(position_clusters(&[], 100, 1000).is_empty());
+
+        // Dense positions are split once a cluster would span too much
+        let dense: Vec<i32> = (0..10).map(|i| i * 1000).collect();
+        let clusters = position_clusters(&dense, 4096, 3000);
+        assert_eq!(
+            clusters,
+            [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9]]
+        );
+        assert_eq!(position_clusters(&[7, 7, 8], 10, 1), [vec![0, 1], vec![2]]);
+    }
+
+    #[test]
+    fn test_base_at_short_read() {
+        assert_eq!(base_at("chr1", b"ACGT", 10, 13).unwrap(), b'T');
+        assert!(matches!(
+            base_at("chr1", b"ACGT", 10, 14),
+            Err(AgcError::Ffi(msg)) if msg.contains("chr1")
+        ));
+    }
+
+    #[test]
+    fn test_get_bases_at() {
+        use std::sync::atomic::Ordering;
+
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+        let last = seq.len() as i32 - 1;
+
+        let positions = [last, 0, last / 2, 0];
+        let calls = agc.ffi_seq_calls.load(Ordering::Relaxed);
+        let bases = agc
+            .get_bases_at(Some(&sample), &contig, &positions)
+            .unwrap();
+        let expected: Vec<u8> = positions.iter().map(|&p| seq[p as usize]).collect();
+        assert_eq!(bases, expected);
+        if last <= BASES_AT_MAX_GAP {
+            assert_eq!(agc.ffi_seq_calls.load(Ordering::Relaxed), calls + 1);
+        }
+
+        assert!(agc
+            .get_bases_at(Some(&sample), &contig, &[])
+            .unwrap()
+            .is_empty());
+        for bad in [-1, last + 1] {
+            assert!(matches!(
+                agc.get_bases_at(Some(&sample), &contig, &[0, bad]),
+                Err(AgcError::InvalidRange { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_get_bases_at_max_fetch_len() {
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(3)
+            .open()
+            .expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let seq = plain.ctg_full_bytes(Some(&sample), &contig).unwrap();
+
+        // The positions span the whole contig, well past the limit
+        let positions: Vec<i32> = (0..len).step_by(2).chain([len - 1]).collect();
+        assert!(len > 3);
+        let bases = agc
+            .get_bases_at(Some(&sample), &contig, &positions)
+            .expect("Clusters should stay within max_fetch_len");
+        let expected: Vec<u8> = positions.iter().map(|&p| seq[p as usize]).collect();
+        assert_eq!(bases, expected);
+    }
+
+    #[test]
+    fn test_get_region_annotated() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        let region = agc
+            .get_region_annotated(Some(&sample), &contig, 2, 12)
+            .expect("Failed to fetch region");
+        assert_eq!(
+            region.seq,
+            agc.ctg_seq_bytes(Some(&sample), &contig, 2, 12).unwrap()
+        );
+        assert_eq!(region.sample.as_deref(), Some(sample.as_str()));
+        assert_eq!(region.contig, contig);
+        assert_eq!((region.start, region.end), (2, 12));
+        assert_eq!(region.strand, Strand::Plus);
+        assert_eq!(region.archive_id, agc.archive_id().unwrap());
+
+        let qualified = format!("{}#{}", sample, contig);
+        let clipped = agc
+            .get_region_annotated(None, &qualified, len - 3, len + 5)
+            .unwrap();
+        assert_eq!(clipped.sample.as_deref(), Some(sample.as_str()));
+        assert_eq!(clipped.contig, contig);
+        assert_eq!((clipped.start, clipped.end), (len - 3, len));
+    }
+
+    #[test]
+    fn test_last_fetch_info_records_clipping() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        let seq = agc
+            .get_ctg_seq(Some(&sample), &contig, 0, len + 10)
+            .unwrap();
+        let info = agc.last_fetch_info().unwrap();
+        assert_eq!(info.requested, len as usize + 10);
+        assert_eq!(info.returned, seq.len());
+        assert!(info.is_clipped());
+    }
+}
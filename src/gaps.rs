@@ -0,0 +1,722 @@
+use std::io::Write;
+
+use crate::coords::CoordinateBase;
+use crate::{AgcError, AgcFile};
+
+/// A fetched region with the gaps inside it
+///
+/// Returned by `AgcFile::get_region_gap_aware`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionWithGaps {
+    /// Bases of the region
+    pub seq: Vec<u8>,
+    /// Runs of `N` in `seq`, as half-open `(start, end)` offsets into it;
+    /// a gap crossing a region edge is cut at that edge
+    pub gaps: Vec<(i32, i32)>,
+}
+
+/// Finds runs of `N`/`n` in a sequence fed to it in consecutive pieces
+///
+/// A run still open at the end of one piece carries over into the next,
+/// so gaps spanning chunk boundaries are reported whole.
+struct GapScanner {
+    min_gap: usize,
+    /// Contig position of the next byte to be fed
+    pos: i32,
+    /// Start of the run of `N` being extended, if any
+    run_start: Option<i32>,
+    gaps: Vec<(i32, i32)>,
+}
+
+impl GapScanner {
+    fn new(min_gap: usize) -> Self {
+        GapScanner {
+            min_gap: min_gap.max(1),
+            pos: 0,
+            run_start: None,
+            gaps: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, piece: &[u8]) {
+        for &base in piece {
+            let is_gap = base == b'N' || base == b'n';
+            match (is_gap, self.run_start) {
+                (true, None) => self.run_start = Some(self.pos),
+                (false, Some(start)) => {
+                    self.close(start);
+                    self.run_start = None;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn close(&mut self, start: i32) {
+        if (self.pos - start) as usize >= self.min_gap {
+            self.gaps.push((start, self.pos));
+        }
+    }
+
+    fn finish(mut self) -> Vec<(i32, i32)> {
+        if let Some(start) = self.run_start {
+            self.close(start);
+        }
+        self.gaps
+    }
+}
+
+/// Splits a sequence fed in consecutive pieces into its gap-free fragments
+///
+/// A run of `N` shorter than `min_gap` stays inside its fragment. A run
+/// still open at the end of a piece is held back until it is known to be
+/// long enough to split on.
+struct GapSplitter {
+    min_gap: usize,
+    /// Contig position of the next byte to be fed
+    pos: i32,
+    /// Fragment being built, with its start
+    current: Vec<u8>,
+    current_start: i32,
+    /// Run of `N` following `current`, not yet known to be a gap
+    pending: Vec<u8>,
+    fragments: Vec<(i32, Vec<u8>)>,
+}
+
+impl GapSplitter {
+    fn new(min_gap: usize) -> Self {
+        GapSplitter {
+            min_gap: min_gap.max(1),
+            pos: 0,
+            current: Vec::new(),
+            current_start: 0,
+            pending: Vec::new(),
+            fragments: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, piece: &[u8]) {
+        for &base in piece {
+            if base == b'N' || base == b'n' {
+                self.pending.push(base);
+            } else {
+                if self.pending.len() >= self.min_gap {
+                    self.close();
+                    self.pending.clear();
+                }
+                if self.current.is_empty() {
+                    self.current_start = self.pos - self.pending.len() as i32;
+                }
+                self.current.append(&mut self.pending);
+                self.current.push(base);
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Move the fragment being built, if any, to the output
+    fn close(&mut self) {
+        if !self.current.is_empty() {
+            let fragment = std::mem::take(&mut self.current);
+            self.fragments.push((self.current_start, fragment));
+        }
+    }
+
+    fn finish(mut self) -> Vec<(i32, Vec<u8>)> {
+        if self.pending.len() < self.min_gap && !self.current.is_empty() {
+            self.current.append(&mut self.pending);
+        }
+        self.close();
+        self.fragments
+    }
+}
+
+/// Fraction of the bases of `contigs`, each given as its length and gaps,
+/// that lie in gaps, or None if there are no bases
+fn fraction_in_gaps(contigs: &[(i32, Vec<(i32, i32)>)]) -> Option<f64> {
+    let total: u64 = contigs.iter().map(|(len, _)| *len as u64).sum();
+    let gapped: u64 = contigs
+        .iter()
+        .flat_map(|(_, gaps)| gaps)
+        .map(|(start, end)| (end - start) as u64)
+        .sum();
+    (total > 0).then(|| gapped as f64 / total as f64)
+}
+
+/// Write the AGP v2 rows of one object (a contig) from its gaps
+///
+/// The stretches between gaps become `W` components named
+/// `<object>.<n>`, numbered from 1; each gap becomes an `N` row of type
+/// `scaffold` with linkage evidence `unspecified`. AGP does not allow an
+/// object to start or end with a gap, so leading and trailing `N` runs
+/// stay in the adjacent component, and an all-`N` contig is a single
+/// component. Object coordinates are written in `base`; component
+/// coordinates are always 1-based.
+fn write_agp_object<W: Write>(
+    out: &mut W,
+    object: &str,
+    len: i32,
+    gaps: &[(i32, i32)],
+    base: CoordinateBase,
+) -> std::io::Result<()> {
+    let mut part = 0;
+    let mut component = 0;
+    let mut pos = 0;
+    let ends = gaps
+        .iter()
+        .filter(|&&(start, end)| start > 0 && end < len)
+        .map(|&(start, end)| (start, Some(end)));
+    for (start, gap_end) in ends.chain(std::iter::once((len, None))) {
+        if start > pos {
+            part += 1;
+            component += 1;
+            let (beg, end) = base.interval(pos, start);
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\tW\t{}.{}\t1\t{}\t+",
+                object,
+                beg,
+                end,
+                part,
+                object,
+                component,
+                start - pos
+            )?;
+        }
+        if let Some(end) = gap_end {
+            part += 1;
+            let (beg, gap_end) = base.interval(start, end);
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\tN\t{}\tscaffold\tyes\tunspecified",
+                object,
+                beg,
+                gap_end,
+                part,
+                end - start
+            )?;
+            pos = end;
+        }
+    }
+    Ok(())
+}
+
+impl AgcFile {
+    /// Find the runs of `N` in a contig, i.e. its assembly gaps
+    ///
+    /// The contig is streamed in chunks, so memory use does not grow with
+    /// its length. Lowercase `n` counts as gap too.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `min_gap` - shortest run to report; 0 is treated as 1
+    ///
+    /// # Returns
+    /// Result containing the 0-based, half-open `(start, end)` of each gap,
+    /// in order
+    pub fn gap_regions(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        min_gap: usize,
+    ) -> Result<Vec<(i32, i32)>, AgcError> {
+        let mut scanner = GapScanner::new(min_gap);
+        for chunk in self.seq_chunks(sample, name, self.stream_chunk_len())? {
+            scanner.feed(&chunk?);
+        }
+        Ok(scanner.finish())
+    }
+
+    /// Find the gaps of a contig, in a chosen coordinate convention
+    ///
+    /// Like [`AgcFile::gap_regions`], which is `CoordinateBase::ZeroBased`;
+    /// with `CoordinateBase::OneBased` each gap is a closed `(start, end)`
+    /// counting from 1, as GFF and AGP expect.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `min_gap` - shortest run to report; 0 is treated as 1
+    /// * `base` - coordinate convention of the result
+    ///
+    /// # Returns
+    /// Result containing the `(start, end)` of each gap, in order
+    pub fn gap_regions_with_base(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        min_gap: usize,
+        base: CoordinateBase,
+    ) -> Result<Vec<(i32, i32)>, AgcError> {
+        Ok(self
+            .gap_regions(sample, name, min_gap)?
+            .into_iter()
+            .map(|(start, end)| base.interval(start, end))
+            .collect())
+    }
+
+    /// Get `[start, end)` of a contig together with the gaps inside it
+    ///
+    /// Saves callers that skip or soft-clip around gaps from rescanning
+    /// the bases. Every run of `N` counts, whatever its length.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    ///
+    /// # Returns
+    /// Result containing the bases and their gaps, relative to `start`
+    pub fn get_region_gap_aware(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<RegionWithGaps, AgcError> {
+        let seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        let mut scanner = GapScanner::new(1);
+        scanner.feed(&seq);
+        Ok(RegionWithGaps {
+            gaps: scanner.finish(),
+            seq,
+        })
+    }
+
+    /// Split a contig at its gaps into the gap-free fragments between them
+    ///
+    /// This is the complement of [`AgcFile::gap_regions`]: the fragments
+    /// and the gaps of at least `min_gap` together cover the contig. A
+    /// shorter run of `N` stays inside its fragment, unless nothing but `N`
+    /// lies between it and a gap or the contig end. The contig is streamed
+    /// once.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `min_gap` - shortest run of `N` to split on; 0 is treated as 1
+    ///
+    /// # Returns
+    /// Result containing each fragment's 0-based start and bases, in order
+    pub fn split_by_gaps(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        min_gap: usize,
+    ) -> Result<Vec<(i32, Vec<u8>)>, AgcError> {
+        let mut splitter = GapSplitter::new(min_gap);
+        for chunk in self.seq_chunks(sample, name, self.stream_chunk_len())? {
+            splitter.feed(&chunk?);
+        }
+        Ok(splitter.finish())
+    }
+
+    /// Write a BED3 track of a sample's gaps
+    ///
+    /// One `contig\tstart\tend` line per run of `N`, over every contig of
+    /// the sample in listing order, with the 0-based half-open coordinates
+    /// BED requires. Contigs are streamed one at a time.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `min_gap` - shortest run to report; 0 is treated as 1
+    /// * `out` - output to write to
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn write_gaps_bed<W: Write>(
+        &self,
+        sample: &str,
+        min_gap: usize,
+        out: &mut W,
+    ) -> Result<(), AgcError> {
+        for contig in self.list_ctg(Some(sample))? {
+            for (start, end) in self.gap_regions(Some(sample), &contig, min_gap)? {
+                writeln!(out, "{}\t{}\t{}", contig, start, end)?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Get the fraction of a sample's bases that lie in assembly gaps
+    ///
+    /// Gaps are runs of `N` at least `min_gap` long, found as by
+    /// [`AgcFile::gap_regions`] while streaming each contig once.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `min_gap` - shortest run to count; 0 is treated as 1
+    ///
+    /// # Returns
+    /// Result containing a fraction in `[0, 1]`, or
+    /// `AgcError::EmptySample` if the sample has no bases
+    pub fn gap_fraction(&self, sample: &str, min_gap: usize) -> Result<f64, AgcError> {
+        let mut contigs = Vec::new();
+        for contig in self.list_ctg(Some(sample))? {
+            let len = self.ctg_len(Some(sample), &contig)?;
+            contigs.push((len, self.gap_regions(Some(sample), &contig, min_gap)?));
+        }
+        fraction_in_gaps(&contigs).ok_or_else(|| AgcError::EmptySample(sample.to_string()))
+    }
+
+    /// Write an AGP v2 file describing how a sample's contigs are built
+    ///
+    /// Each contig is an AGP object; every run of `N` inside it is a gap
+    /// row and the stretches in between are its components, named
+    /// `<contig>.<n>`. Runs at either end of a contig stay in its first
+    /// or last component, since AGP objects cannot start or end with a
+    /// gap. Contigs are streamed one at a time. Coordinates are
+    /// 1-based, as the AGP specification requires.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `out` - output to write to
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn write_agp<W: Write>(&self, sample: &str, out: &mut W) -> Result<(), AgcError> {
+        self.write_agp_with_base(sample, out, CoordinateBase::OneBased)
+    }
+
+    /// Write AGP rows for a sample with object coordinates in a chosen base
+    ///
+    /// Like [`AgcFile::write_agp`], which is `CoordinateBase::OneBased`.
+    /// `CoordinateBase::ZeroBased` writes BED-style half-open object
+    /// coordinates, which is not valid AGP but lines up with BED tracks.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `out` - output to write to
+    /// * `base` - coordinate convention of the object columns
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn write_agp_with_base<W: Write>(
+        &self,
+        sample: &str,
+        out: &mut W,
+        base: CoordinateBase,
+    ) -> Result<(), AgcError> {
+        writeln!(out, "##agp-version\t2.0")?;
+        for contig in self.list_ctg(Some(sample))? {
+            let len = self.ctg_len(Some(sample), &contig)?;
+            let gaps = self.gap_regions(Some(sample), &contig, 1)?;
+            write_agp_object(out, &contig, len, &gaps, base)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Gaps of a whole sequence scanned at once
+    fn scan(seq: &[u8], min_gap: usize) -> Vec<(i32, i32)> {
+        let mut scanner = GapScanner::new(min_gap);
+        scanner.feed(seq);
+        scanner.finish()
+    }
+
+    #[test]
+    fn test_gap_scanner() {
+        let seq = b"NNACGTNNNNNACGTnNNACNN";
+        assert_eq!(scan(seq, 1), [(0, 2), (6, 11), (15, 18), (20, 22)]);
+        assert_eq!(scan(seq, 3), [(6, 11), (15, 18)]);
+        assert_eq!(scan(b"ACGT", 1), []);
+    }
+
+    #[test]
+    fn test_gap_scanner_across_chunks() {
+        let seq = b"ACGNNNNNNNNACGTNNNAAANNNNN";
+        for chunk_len in 1..seq.len() {
+            let mut scanner = GapScanner::new(2);
+            for piece in seq.chunks(chunk_len) {
+                scanner.feed(piece);
+            }
+            assert_eq!(scanner.finish(), scan(seq, 2), "chunk length {}", chunk_len);
+        }
+    }
+
+    /// Split a whole sequence, fed in pieces of `chunk_len`
+    fn split(seq: &[u8], min_gap: usize, chunk_len: usize) -> Vec<(i32, Vec<u8>)> {
+        let mut splitter = GapSplitter::new(min_gap);
+        for piece in seq.chunks(chunk_len) {
+            splitter.feed(piece);
+        }
+        splitter.finish()
+    }
+
+    #[test]
+    fn test_gap_splitter() {
+        let scaffold = b"ACGTNNNNNACNGNNNNNNTT";
+        for chunk_len in 1..=scaffold.len() {
+            assert_eq!(
+                split(scaffold, 3, chunk_len),
+                [
+                    (0, b"ACGT".to_vec()),
+                    (9, b"ACNG".to_vec()),
+                    (19, b"TT".to_vec())
+                ],
+                "chunk length {}",
+                chunk_len
+            );
+        }
+        assert_eq!(split(b"NNACGTNN", 4, 3), [(0, b"NNACGTNN".to_vec())]);
+        assert_eq!(split(b"NNNNACNNNN", 2, 4), [(4, b"AC".to_vec())]);
+        assert!(split(b"NNNN", 1, 2).is_empty());
+    }
+
+    #[test]
+    fn test_split_by_gaps() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            let fragments = agc.split_by_gaps(Some(&sample), &contig, 1).unwrap();
+            let gaps = agc.gap_regions(Some(&sample), &contig, 1).unwrap();
+
+            let covered: usize = fragments.iter().map(|(_, f)| f.len()).sum::<usize>()
+                + gaps.iter().map(|(s, e)| (e - s) as usize).sum::<usize>();
+            assert_eq!(covered, seq.len());
+            for (start, fragment) in &fragments {
+                let start = *start as usize;
+                assert_eq!(&seq[start..start + fragment.len()], fragment.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_region_gap_aware() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let (contig, (gap_start, gap_end)) = agc
+            .list_ctg(Some(&sample))
+            .unwrap()
+            .into_iter()
+            .find_map(|c| {
+                let gaps = agc.gap_regions(Some(&sample), &c, 2).unwrap();
+                gaps.first().copied().map(|gap| (c, gap))
+            })
+            .expect("Fixture should have a contig with a gap");
+
+        // Start inside the gap and end past it
+        let (start, end) = (gap_start + 1, gap_end + 3);
+        let region = agc
+            .get_region_gap_aware(Some(&sample), &contig, start, end)
+            .unwrap();
+        assert_eq!(
+            region.seq,
+            agc.ctg_seq_bytes(Some(&sample), &contig, start, end)
+                .unwrap()
+        );
+        assert_eq!(region.gaps[0], (0, gap_end - start));
+        for &(s, e) in &region.gaps {
+            assert!(region.seq[s as usize..e as usize]
+                .iter()
+                .all(|b| b.eq_ignore_ascii_case(&b'N')));
+        }
+    }
+
+    /// Check AGP rows tile each object and return each object's length
+    fn parse_agp(agp: &str) -> Vec<(String, i64)> {
+        let mut lines = agp.lines();
+        assert_eq!(lines.next(), Some("##agp-version\t2.0"));
+        let mut objects: Vec<(String, i64)> = Vec::new();
+        let mut part = 0;
+        for line in lines {
+            let cols: Vec<&str> = line.split('\t').collect();
+            assert_eq!(cols.len(), 9, "{}", line);
+            let (beg, end): (i64, i64) = (cols[1].parse().unwrap(), cols[2].parse().unwrap());
+            if objects.last().map(|(o, _)| o.as_str()) != Some(cols[0]) {
+                objects.push((cols[0].to_string(), 0));
+                part = 0;
+            }
+            let object = objects.last_mut().unwrap();
+            part += 1;
+            assert_eq!(beg, object.1 + 1, "{}", line);
+            assert_eq!(cols[3].parse::<i32>().unwrap(), part);
+            let piece_len: i64 = match cols[4] {
+                "W" => {
+                    assert_eq!(cols[6], "1");
+                    cols[7].parse().unwrap()
+                }
+                "N" => cols[5].parse().unwrap(),
+                other => panic!("unexpected component type {}", other),
+            };
+            assert_eq!(end - beg + 1, piece_len, "{}", line);
+            object.1 = end;
+        }
+        objects
+    }
+
+    #[test]
+    fn test_write_agp_object() {
+        let seq = b"NNACGTNNNNACGTACNN";
+        let mut agp = b"##agp-version\t2.0\n".to_vec();
+        let one = CoordinateBase::OneBased;
+        write_agp_object(&mut agp, "chr1", seq.len() as i32, &scan(seq, 1), one).unwrap();
+        write_agp_object(&mut agp, "chr2", 4, &[], one).unwrap();
+        write_agp_object(&mut agp, "chr3", 5, &scan(b"NNNNN", 1), one).unwrap();
+        let agp = String::from_utf8(agp).unwrap();
+        assert_eq!(
+            parse_agp(&agp),
+            [
+                ("chr1".to_string(), 18),
+                ("chr2".to_string(), 4),
+                ("chr3".to_string(), 5)
+            ]
+        );
+        // Leading and trailing N runs belong to the end components
+        let rows: Vec<&str> = agp.lines().skip(1).collect();
+        assert_eq!(
+            rows,
+            [
+                "chr1\t1\t6\t1\tW\tchr1.1\t1\t6\t+",
+                "chr1\t7\t10\t2\tN\t4\tscaffold\tyes\tunspecified",
+                "chr1\t11\t18\t3\tW\tchr1.2\t1\t8\t+",
+                "chr2\t1\t4\t1\tW\tchr2.1\t1\t4\t+",
+                "chr3\t1\t5\t1\tW\tchr3.1\t1\t5\t+",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_agp() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let mut out = Vec::new();
+        agc.write_agp(&sample, &mut out)
+            .expect("Failed to write AGP");
+
+        let expected: Vec<(String, i64)> = agc
+            .list_ctg(Some(&sample))
+            .unwrap()
+            .into_iter()
+            .map(|c| {
+                let len = agc.get_ctg_len(Some(&sample), &c).unwrap() as i64;
+                (c, len)
+            })
+            .collect();
+        assert_eq!(parse_agp(&String::from_utf8(out).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_fraction_in_gaps() {
+        // 40 bases, of which 4 + 6 are in runs of at least 3
+        let contigs: Vec<(i32, Vec<(i32, i32)>)> = [
+            b"ACGTNNNNACGTNNACGTAC".as_slice(),
+            b"NNNNNNACGTACGTACGTAC".as_slice(),
+        ]
+        .iter()
+        .map(|seq| (seq.len() as i32, scan(seq, 3)))
+        .collect();
+        assert_eq!(fraction_in_gaps(&contigs), Some(0.25));
+        assert_eq!(fraction_in_gaps(&[(10, Vec::new())]), Some(0.0));
+        assert_eq!(fraction_in_gaps(&[(4, vec![(0, 4)])]), Some(1.0));
+        assert_eq!(fraction_in_gaps(&[]), None);
+    }
+
+    #[test]
+    fn test_gap_fraction() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let mut total = 0u64;
+        let mut gapped = 0u64;
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            total += seq.len() as u64;
+            gapped += scan(&seq, 1)
+                .iter()
+                .map(|(s, e)| (e - s) as u64)
+                .sum::<u64>();
+        }
+
+        let fraction = agc.gap_fraction(&sample, 0).unwrap();
+        assert!((0.0..=1.0).contains(&fraction));
+        assert!((fraction - gapped as f64 / total as f64).abs() < 1e-12);
+        assert!(agc.gap_fraction(&sample, 1_000_000).unwrap() <= fraction);
+    }
+
+    #[test]
+    fn test_write_gaps_bed() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            let mut out = Vec::new();
+            agc.write_gaps_bed(&sample, 2, &mut out)
+                .expect("Failed to write BED");
+
+            let mut expected = Vec::new();
+            for contig in agc.list_ctg(Some(&sample)).unwrap() {
+                for (start, end) in agc.gap_regions(Some(&sample), &contig, 2).unwrap() {
+                    expected.push((contig.clone(), start, end));
+                }
+            }
+            let parsed: Vec<(String, i32, i32)> = String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let fields: Vec<&str> = line.split('\t').collect();
+                    assert_eq!(fields.len(), 3, "BED3 line: {}", line);
+                    (
+                        fields[0].to_string(),
+                        fields[1].parse().unwrap(),
+                        fields[2].parse().unwrap(),
+                    )
+                })
+                .collect();
+            assert_eq!(parsed, expected);
+        }
+    }
+
+    #[test]
+    fn test_gap_regions() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            let gaps = agc.gap_regions(Some(&sample), &contig, 1).unwrap();
+            assert_eq!(gaps, scan(&seq, 1));
+            for &(start, end) in &gaps {
+                assert!(seq[start as usize..end as usize]
+                    .iter()
+                    .all(|b| b.eq_ignore_ascii_case(&b'N')));
+            }
+
+            let one_based = agc
+                .gap_regions_with_base(Some(&sample), &contig, 1, CoordinateBase::OneBased)
+                .unwrap();
+            let shifted: Vec<(i32, i32)> = gaps.iter().map(|&(s, e)| (s + 1, e)).collect();
+            assert_eq!(one_based, shifted);
+        }
+    }
+
+    #[test]
+    fn test_write_agp_with_base() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let (mut zero, mut one) = (Vec::new(), Vec::new());
+        agc.write_agp_with_base(&sample, &mut zero, CoordinateBase::ZeroBased)
+            .unwrap();
+        agc.write_agp(&sample, &mut one).unwrap();
+        let (zero, one) = (
+            String::from_utf8(zero).unwrap(),
+            String::from_utf8(one).unwrap(),
+        );
+
+        assert_eq!(zero.lines().count(), one.lines().count());
+        for (z, o) in zero.lines().zip(one.lines()).skip(1) {
+            let (z, o): (Vec<&str>, Vec<&str>) = (z.split('\t').collect(), o.split('\t').collect());
+            let start = |cols: &[&str]| cols[1].parse::<i32>().unwrap();
+            assert_eq!(start(&z) + 1, start(&o));
+            assert_eq!(z[2..], o[2..]);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Opening archives over HTTP, enabled by the `http` feature
+//!
+//! AGC opens archives by file name and reads them with random access; the C
+//! API has no hook for supplying byte ranges. Remote archives are therefore
+//! downloaded in full to a temporary file, which is removed when the
+//! returned handle is dropped.
+
+use std::io::{self, Read};
+
+use crate::{AgcError, AgcFile};
+
+/// Reader that reports cumulative progress after every read
+struct ProgressReader<R, F> {
+    inner: R,
+    read: u64,
+    total: Option<u64>,
+    progress: F,
+}
+
+impl<R: Read, F: FnMut(u64, Option<u64>)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        (self.progress)(self.read, self.total);
+        Ok(n)
+    }
+}
+
+impl AgcFile {
+    /// Download an archive over HTTP(S) and open it
+    ///
+    /// The whole archive is downloaded before it is opened; see
+    /// `open_url_with_progress` to follow the download.
+    ///
+    /// # Arguments
+    /// * `url` - URL of the AGC file
+    pub fn open_url(url: &str) -> Result<Self, AgcError> {
+        Self::open_url_with_progress(url, false, |_, _| {})
+    }
+
+    /// Download an archive over HTTP(S), reporting progress, and open it
+    ///
+    /// # Arguments
+    /// * `url` - URL of the AGC file
+    /// * `prefetching` - whether to preload the downloaded file into memory
+    /// * `progress` - called with the bytes downloaded so far and the total
+    ///   size, if the server sent `Content-Length`
+    pub fn open_url_with_progress<F: FnMut(u64, Option<u64>)>(
+        url: &str,
+        prefetching: bool,
+        progress: F,
+    ) -> Result<Self, AgcError> {
+        let response = ureq::get(url)
+            .call()
+            .map_err(|e| io::Error::other(format!("Failed to fetch {}: {}", url, e)))?;
+        let total = response
+            .header("Content-Length")
+            .and_then(|len| len.parse().ok());
+
+        let reader = ProgressReader {
+            inner: response.into_reader(),
+            read: 0,
+            total,
+            progress,
+        };
+        AgcFile::open_reader(reader, prefetching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Serve `body` once on a local port, returning its URL
+    fn serve_once(body: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/test.agc", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_open_url() {
+        let body = std::fs::read(TEST_FILE).unwrap();
+        let size = body.len() as u64;
+        let url = serve_once(body);
+
+        let mut last = (0, None);
+        let agc = AgcFile::open_url_with_progress(&url, false, |read, total| last = (read, total))
+            .expect("Failed to open archive over HTTP");
+        assert_eq!(last, (size, Some(size)));
+
+        let local = AgcFile::open(TEST_FILE, false).unwrap();
+        assert_eq!(agc.list_sample().unwrap(), local.list_sample().unwrap());
+    }
+
+    #[test]
+    fn test_open_url_unreachable() {
+        // Bind and drop a listener to get a local port that refuses connections
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/test.agc", port);
+        assert!(matches!(AgcFile::open_url(&url), Err(AgcError::Io(_))));
+    }
+}
@@ -0,0 +1,133 @@
+use suffix::SuffixTable;
+
+use crate::{AgcError, AgcFile};
+
+/// Substring index of one contig, built by `AgcFile::build_suffix_index`
+///
+/// *Requires the `suffix-index` feature.* Holds the contig and its suffix
+/// table, about five bytes per base, and answers each `find` in time
+/// proportional to the pattern length and the number of hits rather than
+/// the contig length.
+#[derive(Debug, Clone)]
+pub struct ContigIndex {
+    table: SuffixTable<'static, 'static>,
+}
+
+impl ContigIndex {
+    fn new(seq: Vec<u8>) -> Result<Self, AgcError> {
+        let text = String::from_utf8(seq).map_err(|e| {
+            let position = e.utf8_error().valid_up_to();
+            AgcError::InvalidBase {
+                position,
+                base: e.as_bytes()[position],
+            }
+        })?;
+        Ok(ContigIndex {
+            table: SuffixTable::new(text),
+        })
+    }
+
+    /// Start positions of every occurrence of a pattern, in increasing order
+    ///
+    /// Occurrences may overlap, and bases are compared as stored, as in
+    /// [`AgcFile::count_pattern`]. An empty pattern matches nowhere.
+    ///
+    /// # Arguments
+    /// * `pattern` - bases to search for
+    pub fn find(&self, pattern: &[u8]) -> Vec<usize> {
+        let pattern = match std::str::from_utf8(pattern) {
+            Ok(pattern) if !pattern.is_empty() => pattern,
+            _ => return Vec::new(),
+        };
+        let mut positions: Vec<usize> = self
+            .table
+            .positions(pattern)
+            .iter()
+            .map(|&p| p as usize)
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// Length of the indexed contig
+    pub fn len(&self) -> usize {
+        self.table.text().len()
+    }
+
+    /// Whether the indexed contig is empty
+    pub fn is_empty(&self) -> bool {
+        self.table.text().is_empty()
+    }
+}
+
+impl AgcFile {
+    /// Fetch a contig and index it for repeated substring search
+    ///
+    /// *Requires the `suffix-index` feature.* Building the index takes one
+    /// pass over the whole contig, which pays off once it is searched more
+    /// than a few times; for a single search use `count_pattern`.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the index, or `AgcError::InvalidBase` if the
+    /// contig holds non-ASCII bytes
+    pub fn build_suffix_index(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+    ) -> Result<ContigIndex, AgcError> {
+        ContigIndex::new(self.ctg_full_bytes(sample, name)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Positions of `pattern` by scanning every window
+    fn naive_find(seq: &[u8], pattern: &[u8]) -> Vec<usize> {
+        seq.windows(pattern.len())
+            .enumerate()
+            .filter(|(_, w)| *w == pattern)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_contig_index_find() {
+        let index = ContigIndex::new(b"ATATAGCATA".to_vec()).unwrap();
+        assert_eq!(index.len(), 10);
+        assert_eq!(index.find(b"ATA"), [0, 2, 7]);
+        assert_eq!(index.find(b"GC"), [5]);
+        assert!(index.find(b"GG").is_empty());
+        assert!(index.find(b"").is_empty());
+        assert!(index.find(b"ata").is_empty());
+
+        assert!(matches!(
+            ContigIndex::new(vec![b'A', 0xff]),
+            Err(AgcError::InvalidBase { position: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_build_suffix_index() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            let index = agc
+                .build_suffix_index(Some(&sample), &contig)
+                .expect("Failed to build index");
+            assert_eq!(index.len(), seq.len());
+
+            let pattern = &seq[..seq.len().min(4)];
+            assert_eq!(index.find(pattern), naive_find(&seq, pattern));
+            assert_eq!(index.find(b"ACGT"), naive_find(&seq, b"ACGT"));
+        }
+    }
+}
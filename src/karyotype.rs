@@ -0,0 +1,335 @@
+use std::cmp::Ordering;
+
+use crate::{AgcError, AgcFile, ContigInfo};
+
+/// Shortest contig `AgcFile::karyotype` takes for a chromosome
+///
+/// Below the size of any animal mitochondrion (about 16 kb), but above
+/// the contigs of draft assemblies that are numbered `1`, `2`, ... like
+/// chromosomes.
+pub const MIN_CHROMOSOME_LEN: i32 = 10_000;
+
+/// A chromosome and its length, for ideogram rendering
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chromosome {
+    /// Contig name as stored in the archive
+    pub name: String,
+    /// Length in bases
+    pub length: i32,
+}
+
+/// Whether a contig name looks like a chromosome
+///
+/// Accepts an optional `chr` prefix (any case) followed by a number from 1
+/// to 99 without leading zeros, or one of `X`, `Y`, `W`, `Z`, `M` and `MT`.
+/// Unplaced and alternate contigs such as `chr1_KI270706v1_random` or
+/// `chrUn_GL000195v1` are rejected.
+///
+/// # Arguments
+/// * `name` - contig name
+pub fn is_chromosome_name(name: &str) -> bool {
+    let id = strip_chr(name);
+    match chromosome_rank(id) {
+        Some((0, n)) => n <= 99 && !id.starts_with('0'),
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// Compare chromosome names in natural order
+///
+/// Numbered chromosomes come first in numeric order, then `X`, `Y`, `W`,
+/// `Z` and the mitochondrion (`M` or `MT`), then any other names, compared
+/// with runs of digits taken as numbers. A `chr` prefix is ignored, so
+/// `chr2` sorts before `10`.
+///
+/// # Arguments
+/// * `a` - first name
+/// * `b` - second name
+pub fn natural_chromosome_cmp(a: &str, b: &str) -> Ordering {
+    let key = |name| chromosome_rank(strip_chr(name)).unwrap_or((6, 0));
+    key(a)
+        .cmp(&key(b))
+        .then_with(|| natural_cmp(strip_chr(a), strip_chr(b)))
+        .then_with(|| a.cmp(b))
+}
+
+impl AgcFile {
+    /// Get the chromosomes of a sample in natural order
+    ///
+    /// Contigs are kept when [`is_chromosome_name`] accepts their name and
+    /// they are at least [`MIN_CHROMOSOME_LEN`] bases long; use
+    /// [`AgcFile::karyotype_min_len`] for another minimum.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the chromosomes sorted by
+    /// [`natural_chromosome_cmp`]. A sample without contigs has no
+    /// chromosomes, just like one whose contigs are all unplaced, so both
+    /// give an empty list.
+    pub fn karyotype(&self, sample: &str) -> Result<Vec<Chromosome>, AgcError> {
+        self.karyotype_min_len(sample, MIN_CHROMOSOME_LEN)
+    }
+
+    /// Get the chromosomes of a sample at least `min_len` bases long
+    ///
+    /// Like [`AgcFile::karyotype`] with a minimum length of the caller's
+    /// choosing, e.g. 0 for small test genomes or several megabases to
+    /// leave out the mitochondrion.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `min_len` - shortest contig to keep
+    ///
+    /// # Returns
+    /// Result containing the chromosomes sorted by
+    /// [`natural_chromosome_cmp`]
+    pub fn karyotype_min_len(
+        &self,
+        sample: &str,
+        min_len: i32,
+    ) -> Result<Vec<Chromosome>, AgcError> {
+        self.karyotype_with(sample, |name, len| {
+            len >= min_len && is_chromosome_name(name)
+        })
+    }
+
+    /// Get the contigs of a sample accepted by a filter, in natural order
+    ///
+    /// Use this when an assembly names its chromosomes differently, or to
+    /// drop short contigs by length.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `is_chromosome` - called with each contig's name and length
+    ///
+    /// # Returns
+    /// Result containing the accepted contigs sorted by
+    /// [`natural_chromosome_cmp`], empty for a sample without contigs
+    pub fn karyotype_with<F>(
+        &self,
+        sample: &str,
+        mut is_chromosome: F,
+    ) -> Result<Vec<Chromosome>, AgcError>
+    where
+        F: FnMut(&str, i32) -> bool,
+    {
+        let mut chromosomes = Vec::new();
+        for contig in self.contigs(Some(sample))? {
+            let contig = contig?;
+            if is_chromosome(&contig.name, contig.length) {
+                chromosomes.push(Chromosome {
+                    name: contig.name,
+                    length: contig.length,
+                });
+            }
+        }
+        chromosomes.sort_by(|a, b| natural_chromosome_cmp(&a.name, &b.name));
+        Ok(chromosomes)
+    }
+
+    /// Get every contig of a sample in natural chromosome order
+    ///
+    /// Unlike [`AgcFile::karyotype`], no contig is left out: chromosomes
+    /// come first as `chr1, chr2, ..., chr10, ..., chrX, chrY, chrM`,
+    /// followed by unplaced and other contigs with their numbers compared
+    /// by value, as [`natural_chromosome_cmp`] orders them.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the name and length of each contig
+    pub fn contigs_natural_order(&self, sample: &str) -> Result<Vec<ContigInfo>, AgcError> {
+        let mut contigs = self.contigs(Some(sample))?.collect::<Result<Vec<_>, _>>()?;
+        contigs.sort_by(|a, b| natural_chromosome_cmp(&a.name, &b.name));
+        Ok(contigs)
+    }
+}
+
+/// Strip a `chr` prefix in any case
+fn strip_chr(name: &str) -> &str {
+    match name.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("chr") && name.len() > 3 => &name[3..],
+        _ => name,
+    }
+}
+
+/// Sort rank of a chromosome identifier, or None if it is not one
+///
+/// Numbered chromosomes rank 0 with their number; the named ones rank 1-5.
+fn chromosome_rank(id: &str) -> Option<(u8, u64)> {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) {
+        return id.parse().ok().filter(|&n| n > 0).map(|n| (0, n));
+    }
+    let rank = match id.to_ascii_uppercase().as_str() {
+        "X" => 1,
+        "Y" => 2,
+        "W" => 3,
+        "Z" => 4,
+        "M" | "MT" => 5,
+        _ => return None,
+    };
+    Some((rank, 0))
+}
+
+/// Compare strings with runs of ASCII digits taken as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.iter().take_while(|c| c.is_ascii_digit()).count();
+                let b_len = b.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (a_num, b_num) = (trim_zeros(&a[..a_len]), trim_zeros(&b[..b_len]));
+                let ord = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+/// Drop leading zeros from a run of digits
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let n = digits.iter().take_while(|&&c| c == b'0').count();
+    &digits[n..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_natural_chromosome_order() {
+        let mut names = vec![
+            "chrUn_GL000195v1",
+            "chr10",
+            "chrM",
+            "chr2",
+            "chrY",
+            "chr1_KI270706v1_random",
+            "chrX",
+            "chr22",
+            "chr1",
+            "chr1_KI270707v1_random",
+            "scaffold_10",
+            "scaffold_9",
+        ];
+        names.sort_by(|a, b| natural_chromosome_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "chr1",
+                "chr2",
+                "chr10",
+                "chr22",
+                "chrX",
+                "chrY",
+                "chrM",
+                "chr1_KI270706v1_random",
+                "chr1_KI270707v1_random",
+                "chrUn_GL000195v1",
+                "scaffold_9",
+                "scaffold_10",
+            ]
+        );
+        assert_eq!(natural_chromosome_cmp("2", "chr10"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_contigs_natural_order() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            let contigs = agc.contigs_natural_order(&sample).unwrap();
+            let mut names: Vec<String> = contigs.iter().map(|c| c.name.clone()).collect();
+            assert!(names
+                .windows(2)
+                .all(|w| natural_chromosome_cmp(&w[0], &w[1]) != Ordering::Greater));
+            for contig in &contigs {
+                let len = agc.get_ctg_len(Some(&sample), &contig.name).unwrap();
+                assert_eq!(contig.length, len);
+            }
+
+            let mut listed = agc.list_ctg(Some(&sample)).unwrap();
+            names.sort();
+            listed.sort();
+            assert_eq!(names, listed);
+        }
+    }
+
+    #[test]
+    fn test_is_chromosome_name() {
+        for name in ["chr1", "chr22", "chrX", "chrM", "chrMT", "Chr3", "7", "Y"] {
+            assert!(is_chromosome_name(name), "{} should be a chromosome", name);
+        }
+        for name in [
+            "chr0",
+            "chr01",
+            "chr100",
+            "chrUn",
+            "chr1_random",
+            "chr",
+            "scaffold_1",
+        ] {
+            assert!(
+                !is_chromosome_name(name),
+                "{} should not be a chromosome",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_karyotype() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+
+        let chromosomes = agc
+            .karyotype_min_len(&sample, 0)
+            .expect("Failed to get karyotype");
+        assert!(!chromosomes.is_empty());
+        for chromosome in &chromosomes {
+            assert!(is_chromosome_name(&chromosome.name));
+            let len = agc.get_ctg_len(Some(&sample), &chromosome.name).unwrap();
+            assert_eq!(chromosome.length, len);
+        }
+        assert!(chromosomes
+            .windows(2)
+            .all(|w| natural_chromosome_cmp(&w[0].name, &w[1].name) == Ordering::Less));
+
+        // The default minimum drops the fixture's short contigs
+        let long: Vec<_> = chromosomes
+            .iter()
+            .filter(|c| c.length >= MIN_CHROMOSOME_LEN)
+            .cloned()
+            .collect();
+        assert_eq!(agc.karyotype(&sample).unwrap(), long);
+        let shortest = chromosomes.iter().map(|c| c.length).min().unwrap();
+        let longer = agc.karyotype_min_len(&sample, shortest + 1).unwrap();
+        assert!(longer.len() < chromosomes.len());
+        assert!(longer.iter().all(|c| c.length > shortest));
+
+        let all = agc.karyotype_with(&sample, |_, _| true).unwrap();
+        assert_eq!(all.len(), agc.n_ctg(&sample).unwrap() as usize);
+        let none = agc.karyotype_with(&sample, |_, len| len < 0).unwrap();
+        assert!(none.is_empty());
+    }
+}
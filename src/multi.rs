@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::{AgcError, AgcFile};
+
+/// Several AGC archives presented as one sample/contig namespace
+///
+/// Queries are dispatched to the archive that provides the sample. Sample
+/// names must be unique across archives: adding an archive whose (possibly
+/// prefixed) sample names collide with ones already present fails with
+/// `AgcError::DuplicateSample` and leaves the view unchanged.
+pub struct AgcMultiFile {
+    archives: Vec<AgcFile>,
+    /// Exposed sample name -> (archive index, sample name in that archive)
+    samples: HashMap<String, (usize, String)>,
+    /// Exposed sample names in the order they were added
+    order: Vec<String>,
+}
+
+impl AgcMultiFile {
+    /// Create an empty view
+    pub fn new() -> Self {
+        AgcMultiFile {
+            archives: Vec::new(),
+            samples: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Open several archives as one view
+    ///
+    /// # Arguments
+    /// * `filenames` - paths to the AGC files
+    /// * `prefetching` - whether to preload each file into memory
+    pub fn open(filenames: &[&str], prefetching: bool) -> Result<Self, AgcError> {
+        let mut multi = AgcMultiFile::new();
+        for filename in filenames {
+            multi.add(AgcFile::open(filename, prefetching)?)?;
+        }
+        Ok(multi)
+    }
+
+    /// Add an archive, exposing its samples under their own names
+    pub fn add(&mut self, agc: AgcFile) -> Result<(), AgcError> {
+        self.add_with_prefix(agc, "")
+    }
+
+    /// Add an archive, exposing its samples as `prefix` + sample name
+    ///
+    /// # Arguments
+    /// * `agc` - archive to add
+    /// * `prefix` - prefix prepended to each of its sample names
+    pub fn add_with_prefix(&mut self, agc: AgcFile, prefix: &str) -> Result<(), AgcError> {
+        let names = agc.list_sample()?;
+        let exposed: Vec<String> = names.iter().map(|s| format!("{}{}", prefix, s)).collect();
+
+        if let Some(dup) = exposed.iter().find(|s| self.samples.contains_key(*s)) {
+            return Err(AgcError::DuplicateSample(dup.clone()));
+        }
+
+        let idx = self.archives.len();
+        self.archives.push(agc);
+        for (exposed, name) in exposed.into_iter().zip(names) {
+            self.samples.insert(exposed.clone(), (idx, name));
+            self.order.push(exposed);
+        }
+        Ok(())
+    }
+
+    /// Resolve an exposed sample name to its archive and native name
+    fn resolve(&self, sample: &str) -> Result<(&AgcFile, &str), AgcError> {
+        let (idx, name) = self
+            .samples
+            .get(sample)
+            .ok_or_else(|| AgcError::SampleNotFound(sample.to_string()))?;
+        Ok((&self.archives[*idx], name))
+    }
+
+    /// Get the number of archives in the view
+    pub fn n_archive(&self) -> usize {
+        self.archives.len()
+    }
+
+    /// Get the number of samples across all archives
+    pub fn n_sample(&self) -> usize {
+        self.order.len()
+    }
+
+    /// List all samples, in the order their archives were added
+    pub fn list_sample(&self) -> Vec<String> {
+        self.order.clone()
+    }
+
+    /// List all contigs in a sample
+    pub fn list_ctg(&self, sample: &str) -> Result<Vec<String>, AgcError> {
+        let (agc, name) = self.resolve(sample)?;
+        Ok(agc.list_ctg(Some(name))?)
+    }
+
+    /// Get the number of contigs in a sample
+    pub fn n_ctg(&self, sample: &str) -> Result<i32, AgcError> {
+        let (agc, name) = self.resolve(sample)?;
+        Ok(agc.n_ctg(name)?)
+    }
+
+    /// Get the length of a contig
+    pub fn get_ctg_len(&self, sample: &str, name: &str) -> Result<i32, AgcError> {
+        let (agc, sample) = self.resolve(sample)?;
+        agc.ctg_len(Some(sample), name)
+    }
+
+    /// Get contig sequence
+    ///
+    /// # Arguments
+    /// * `sample` - sample name as exposed by the view
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    pub fn get_ctg_seq(
+        &self,
+        sample: &str,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<String, AgcError> {
+        let (agc, sample) = self.resolve(sample)?;
+        Ok(agc.get_ctg_seq(Some(sample), name, start, end)?)
+    }
+}
+
+impl Default for AgcMultiFile {
+    fn default() -> Self {
+        AgcMultiFile::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_multi_file_prefixed_copies() {
+        let mut multi = AgcMultiFile::new();
+        multi.add(AgcFile::open(TEST_FILE, true).unwrap()).unwrap();
+        multi
+            .add_with_prefix(AgcFile::open(TEST_FILE, false).unwrap(), "copy_")
+            .unwrap();
+
+        let single = AgcFile::open(TEST_FILE, true).unwrap();
+        assert_eq!(multi.n_archive(), 2);
+        assert_eq!(multi.n_sample(), 2 * single.n_sample() as usize);
+
+        let sample = single.list_sample().unwrap().remove(0);
+        let contig = single.list_ctg(Some(&sample)).unwrap().remove(0);
+        let original = multi.get_ctg_seq(&sample, &contig, 0, 10).unwrap();
+        let copy = multi
+            .get_ctg_seq(&format!("copy_{}", sample), &contig, 0, 10)
+            .unwrap();
+        assert_eq!(original, copy);
+    }
+
+    #[test]
+    fn test_multi_file_collision() {
+        let mut multi = AgcMultiFile::open(&[TEST_FILE], true).unwrap();
+        let n_sample = multi.n_sample();
+
+        let result = multi.add(AgcFile::open(TEST_FILE, true).unwrap());
+        assert!(matches!(result, Err(AgcError::DuplicateSample(_))));
+        assert_eq!(
+            multi.n_sample(),
+            n_sample,
+            "Failed add should not change the view"
+        );
+        assert_eq!(multi.n_archive(), 1);
+    }
+
+    #[test]
+    fn test_multi_file_unknown_sample() {
+        let multi = AgcMultiFile::open(&[TEST_FILE], true).unwrap();
+        let result = multi.list_ctg("nonexistent_sample");
+        assert!(matches!(result, Err(AgcError::SampleNotFound(_))));
+    }
+}
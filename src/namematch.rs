@@ -0,0 +1,71 @@
+use regex::Regex;
+
+use crate::{AgcError, AgcFile};
+
+impl AgcFile {
+    /// List every contig of the archive whose name matches a regex
+    ///
+    /// *Requires the `regex` feature.* The pattern is compiled once and
+    /// tested against each sample's contig list in turn. As usual for
+    /// regexes it may match anywhere in a name, so anchor it, as in
+    /// `^chr[0-9]+$`, to select whole names.
+    ///
+    /// # Arguments
+    /// * `pattern` - regular expression in the `regex` crate's syntax
+    ///
+    /// # Returns
+    /// Result containing `(sample, contig)` pairs, samples in archive order
+    /// and contigs in listing order, or `AgcError::InvalidArgument` if the
+    /// pattern does not compile
+    pub fn list_contigs_regex(&self, pattern: &str) -> Result<Vec<(String, String)>, AgcError> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            AgcError::InvalidArgument(format!("invalid contig pattern {:?}: {}", pattern, e))
+        })?;
+
+        let mut matches = Vec::new();
+        for sample in self.list_sample()? {
+            for contig in self.list_ctg(Some(&sample))? {
+                if regex.is_match(&contig) {
+                    matches.push((sample.clone(), contig));
+                }
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_list_contigs_regex() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let numbered = |name: &str| {
+            name.strip_prefix("chr")
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        };
+        let mut expected = Vec::new();
+        for sample in agc.list_sample().unwrap() {
+            for contig in agc.list_ctg(Some(&sample)).unwrap() {
+                if numbered(&contig) {
+                    expected.push((sample.clone(), contig));
+                }
+            }
+        }
+
+        let matches = agc.list_contigs_regex("^chr[0-9]+$").unwrap();
+        assert_eq!(matches, expected);
+        assert!(agc
+            .list_contigs_regex("^no_such_prefix")
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            agc.list_contigs_regex("chr[0-9"),
+            Err(AgcError::InvalidArgument(msg)) if msg.contains("chr[0-9")
+        ));
+    }
+}
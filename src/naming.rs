@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use crate::{AgcError, AgcFile};
+
+/// A chromosome naming scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChrNaming {
+    /// UCSC names such as `chr1` and `chrM`
+    Ucsc,
+    /// Ensembl names such as `1` and `MT`
+    Ensembl,
+    /// GenBank accessions such as `CM000663.2`
+    GenBank,
+}
+
+impl ChrNaming {
+    fn column(self) -> usize {
+        match self {
+            ChrNaming::Ucsc => 0,
+            ChrNaming::Ensembl => 1,
+            ChrNaming::GenBank => 2,
+        }
+    }
+}
+
+/// GRCh38 chromosomes as `[UCSC, Ensembl, GenBank]`
+const GRCH38: &[[&str; 3]] = &[
+    ["chr1", "1", "CM000663.2"],
+    ["chr2", "2", "CM000664.2"],
+    ["chr3", "3", "CM000665.2"],
+    ["chr4", "4", "CM000666.2"],
+    ["chr5", "5", "CM000667.2"],
+    ["chr6", "6", "CM000668.2"],
+    ["chr7", "7", "CM000669.2"],
+    ["chr8", "8", "CM000670.2"],
+    ["chr9", "9", "CM000671.2"],
+    ["chr10", "10", "CM000672.2"],
+    ["chr11", "11", "CM000673.2"],
+    ["chr12", "12", "CM000674.2"],
+    ["chr13", "13", "CM000675.2"],
+    ["chr14", "14", "CM000676.2"],
+    ["chr15", "15", "CM000677.2"],
+    ["chr16", "16", "CM000678.2"],
+    ["chr17", "17", "CM000679.2"],
+    ["chr18", "18", "CM000680.2"],
+    ["chr19", "19", "CM000681.2"],
+    ["chr20", "20", "CM000682.2"],
+    ["chr21", "21", "CM000683.2"],
+    ["chr22", "22", "CM000684.2"],
+    ["chrX", "X", "CM000685.2"],
+    ["chrY", "Y", "CM000686.2"],
+    ["chrM", "MT", "J01415.2"],
+];
+
+/// GRCh37 chromosomes as `[UCSC, Ensembl, GenBank]`; hg19's `chrM` is a
+/// different mitochondrial sequence than GRCh37's `MT`, so neither is listed
+const GRCH37: &[[&str; 3]] = &[
+    ["chr1", "1", "CM000663.1"],
+    ["chr2", "2", "CM000664.1"],
+    ["chr3", "3", "CM000665.1"],
+    ["chr4", "4", "CM000666.1"],
+    ["chr5", "5", "CM000667.1"],
+    ["chr6", "6", "CM000668.1"],
+    ["chr7", "7", "CM000669.1"],
+    ["chr8", "8", "CM000670.1"],
+    ["chr9", "9", "CM000671.1"],
+    ["chr10", "10", "CM000672.1"],
+    ["chr11", "11", "CM000673.1"],
+    ["chr12", "12", "CM000674.1"],
+    ["chr13", "13", "CM000675.1"],
+    ["chr14", "14", "CM000676.1"],
+    ["chr15", "15", "CM000677.1"],
+    ["chr16", "16", "CM000678.1"],
+    ["chr17", "17", "CM000679.1"],
+    ["chr18", "18", "CM000680.1"],
+    ["chr19", "19", "CM000681.1"],
+    ["chr20", "20", "CM000682.1"],
+    ["chr21", "21", "CM000683.1"],
+    ["chr22", "22", "CM000684.1"],
+    ["chrX", "X", "CM000685.1"],
+    ["chrY", "Y", "CM000686.1"],
+];
+
+/// Equivalent names of chromosomes under each `ChrNaming` scheme
+///
+/// Each row holds one chromosome's `[UCSC, Ensembl, GenBank]` names.
+/// Built-in tables cover GRCh38 and GRCh37; any other assembly can be
+/// described with `ChrNameTable::new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChrNameTable {
+    rows: Vec<[String; 3]>,
+}
+
+impl ChrNameTable {
+    /// Build a table from `[UCSC, Ensembl, GenBank]` rows
+    pub fn new(rows: Vec<[String; 3]>) -> Self {
+        ChrNameTable { rows }
+    }
+
+    /// The chromosomes of GRCh38 (hg38)
+    pub fn grch38() -> Self {
+        Self::from_static(GRCH38)
+    }
+
+    /// The chromosomes of GRCh37 (hg19), without the mitochondrion
+    pub fn grch37() -> Self {
+        Self::from_static(GRCH37)
+    }
+
+    fn from_static(rows: &[[&str; 3]]) -> Self {
+        ChrNameTable::new(rows.iter().map(|row| row.map(str::to_string)).collect())
+    }
+
+    /// Translate a name under any scheme to `scheme`, if it is in the table
+    ///
+    /// # Arguments
+    /// * `name` - chromosome name under any of the schemes
+    /// * `scheme` - scheme to translate to
+    pub fn translate(&self, name: &str, scheme: ChrNaming) -> Option<&str> {
+        self.row(name).map(|row| row[scheme.column()].as_str())
+    }
+
+    fn row(&self, name: &str) -> Option<&[String; 3]> {
+        self.rows.iter().find(|row| row.iter().any(|n| n == name))
+    }
+}
+
+impl AgcFile {
+    /// Use one chromosome naming scheme for queries and FASTA headers
+    ///
+    /// Every contig of the archive found in the GRCh38 or GRCh37 table,
+    /// whichever matches more of them (GRCh38 on a tie), can then be
+    /// queried by any of its names, and FASTA export writes it under its
+    /// `scheme` name. UCSC and Ensembl names are the same in both tables,
+    /// so for a GRCh37 archive with UCSC or Ensembl names use
+    /// `with_chr_naming_table` with `ChrNameTable::grch37()` to get the
+    /// right GenBank accessions. Listing methods keep returning the
+    /// archive's names.
+    ///
+    /// # Arguments
+    /// * `scheme` - naming scheme to present
+    ///
+    /// # Returns
+    /// Result containing the handle, or an error if the contigs cannot be
+    /// listed
+    pub fn with_chr_naming(self, scheme: ChrNaming) -> Result<Self, AgcError> {
+        let names = self.archive_contig_names()?;
+        let (grch38, grch37) = (ChrNameTable::grch38(), ChrNameTable::grch37());
+        let matches =
+            |table: &ChrNameTable| names.iter().filter(|n| table.row(n).is_some()).count();
+        let table = if matches(&grch37) > matches(&grch38) {
+            grch37
+        } else {
+            grch38
+        };
+        Ok(self.apply_chr_naming(&names, scheme, &table))
+    }
+
+    /// Use one chromosome naming scheme for queries and FASTA headers,
+    /// translating with a custom table
+    ///
+    /// Like `with_chr_naming`, with `table` in place of the built-in ones.
+    ///
+    /// # Arguments
+    /// * `scheme` - naming scheme to present
+    /// * `table` - names of each chromosome under every scheme
+    ///
+    /// # Returns
+    /// Result containing the handle, or an error if the contigs cannot be
+    /// listed
+    pub fn with_chr_naming_table(
+        self,
+        scheme: ChrNaming,
+        table: &ChrNameTable,
+    ) -> Result<Self, AgcError> {
+        let names = self.archive_contig_names()?;
+        Ok(self.apply_chr_naming(&names, scheme, table))
+    }
+
+    /// The name FASTA export writes for an archive contig
+    pub(crate) fn display_contig<'a>(&'a self, name: &'a str) -> &'a str {
+        self.display_names.get(name).map_or(name, String::as_str)
+    }
+
+    /// Distinct contig names across all samples, in listing order
+    fn archive_contig_names(&self) -> Result<Vec<String>, AgcError> {
+        let mut names = Vec::new();
+        for sample in self.list_sample()? {
+            for contig in self.list_ctg(Some(&sample))? {
+                if !names.contains(&contig) {
+                    names.push(contig);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Alias every other name of each archive contig found in `table` to
+    /// it, and present it under its `scheme` name; names the archive uses
+    /// itself are never aliased away
+    fn apply_chr_naming(
+        mut self,
+        names: &[String],
+        scheme: ChrNaming,
+        table: &ChrNameTable,
+    ) -> Self {
+        let mut aliases = HashMap::new();
+        for (archive_name, row) in names
+            .iter()
+            .filter_map(|n| table.row(n).map(|row| (n, row)))
+        {
+            for alias in row.iter().filter(|n| !names.contains(n)) {
+                aliases.insert(alias.clone(), archive_name.clone());
+            }
+            let display = &row[scheme.column()];
+            if display != archive_name {
+                self.display_names
+                    .insert(archive_name.clone(), display.clone());
+            }
+        }
+        self.with_contig_aliases(aliases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineEnding;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    const SCHEMES: [ChrNaming; 3] = [ChrNaming::Ucsc, ChrNaming::Ensembl, ChrNaming::GenBank];
+
+    #[test]
+    fn test_builtin_tables() {
+        let grch38 = ChrNameTable::grch38();
+        assert_eq!(
+            grch38.translate("chr1", ChrNaming::GenBank),
+            Some("CM000663.2")
+        );
+        assert_eq!(
+            grch38.translate("CM000663.2", ChrNaming::Ensembl),
+            Some("1")
+        );
+        assert_eq!(grch38.translate("MT", ChrNaming::Ucsc), Some("chrM"));
+        assert_eq!(
+            grch38.translate("chrUn_GL000195v1", ChrNaming::Ensembl),
+            None
+        );
+
+        let grch37 = ChrNameTable::grch37();
+        assert_eq!(
+            grch37.translate("X", ChrNaming::GenBank),
+            Some("CM000685.1")
+        );
+        assert_eq!(grch37.translate("chrM", ChrNaming::Ensembl), None);
+    }
+
+    #[test]
+    fn test_with_chr_naming_table() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = plain.reference_sample().unwrap();
+        let contigs = plain.list_ctg(Some(&sample)).unwrap();
+        let table = ChrNameTable::new(
+            contigs
+                .iter()
+                .map(|c| [c.clone(), format!("ens_{}", c), format!("gb_{}", c)])
+                .collect(),
+        );
+
+        for scheme in SCHEMES {
+            let agc = AgcFile::open(TEST_FILE, true)
+                .expect("Failed to open file")
+                .with_chr_naming_table(scheme, &table)
+                .expect("Failed to apply naming");
+            for contig in &contigs {
+                let len = plain.get_ctg_len(Some(&sample), contig).unwrap();
+                let name = table.translate(contig, scheme).unwrap();
+                assert_eq!(agc.get_ctg_len(Some(&sample), name), Ok(len));
+                assert_eq!(agc.get_ctg_len(Some(&sample), contig), Ok(len));
+            }
+
+            let mut fasta = Vec::new();
+            agc.write_sample_fasta(&sample, &mut fasta, 0, LineEnding::Lf)
+                .unwrap();
+            let headers: Vec<String> = String::from_utf8(fasta)
+                .unwrap()
+                .lines()
+                .filter_map(|l| l.strip_prefix('>').map(str::to_string))
+                .collect();
+            let expected: Vec<&str> = contigs
+                .iter()
+                .map(|c| table.translate(c, scheme).unwrap())
+                .collect();
+            assert_eq!(headers, expected);
+        }
+    }
+
+    #[test]
+    fn test_with_chr_naming() {
+        let agc = AgcFile::open(TEST_FILE, true)
+            .expect("Failed to open file")
+            .with_chr_naming(ChrNaming::Ensembl)
+            .expect("Failed to apply naming");
+        let grch38 = ChrNameTable::grch38();
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+            for scheme in SCHEMES {
+                if let Some(name) = grch38.translate(&contig, scheme) {
+                    assert_eq!(agc.get_ctg_len(Some(&sample), name), Ok(len));
+                }
+            }
+        }
+    }
+}
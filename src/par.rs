@@ -0,0 +1,82 @@
+//! Parallel bulk queries, enabled by the `rayon` feature
+
+use rayon::prelude::*;
+
+use crate::{AgcError, AgcFile};
+
+/// `(sample, contig, length)` of each contig of one sample, in listing order
+fn sample_inventory(agc: &AgcFile, sample: String) -> Result<Vec<(String, String, i32)>, AgcError> {
+    let mut entries = Vec::new();
+    for contig in agc.list_ctg(Some(&sample))? {
+        let len = agc.ctg_len(Some(&sample), &contig)?;
+        entries.push((sample.clone(), contig, len));
+    }
+    Ok(entries)
+}
+
+impl AgcFile {
+    /// List every contig of every sample with its length, in parallel
+    ///
+    /// *Requires the `rayon` feature.* Samples are spread over rayon's
+    /// thread pool, and each worker opens its own handle to the archive,
+    /// so no handle is shared between threads. The result is the same as
+    /// [`AgcFile::inventory`], in the same order, however the work was
+    /// split. Worker handles get this handle's options, such as aliases
+    /// and `max_list_len`, as [`AgcFile::for_each_sample`] gives its
+    /// workers.
+    ///
+    /// # Returns
+    /// Result containing the entries, or the first sample's error in
+    /// archive order
+    pub fn par_inventory(&self) -> Result<Vec<(String, String, i32)>, AgcError> {
+        let samples = self.list_sample()?;
+        let (filename, prefetching) = self.reopen_args()?;
+        let options = self.handle_options();
+        let per_sample: Vec<Result<Vec<_>, AgcError>> = samples
+            .into_par_iter()
+            .map_init(
+                || AgcFile::open(filename, prefetching).map(|agc| options.apply(agc)),
+                |agc, sample| match agc {
+                    Ok(agc) => sample_inventory(agc, sample),
+                    Err(e) => Err(AgcError::Ffi(e.clone())),
+                },
+            )
+            .collect();
+
+        let mut entries = Vec::new();
+        for sample in per_sample {
+            entries.extend(sample?);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_par_inventory() {
+        for prefetching in [true, false] {
+            let agc = AgcFile::open(TEST_FILE, prefetching).expect("Failed to open file");
+            assert_eq!(agc.par_inventory().unwrap(), agc.inventory().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_par_inventory_keeps_options() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = plain.reference_sample().unwrap();
+        let contigs = plain.list_ctg(Some(&sample)).unwrap();
+        // Workers must size the second contig through the alias to the first
+        let agc = AgcFile::open(TEST_FILE, true)
+            .unwrap()
+            .with_contig_aliases(HashMap::from([(contigs[1].clone(), contigs[0].clone())]));
+        let aliased = agc.inventory().unwrap();
+        assert_ne!(aliased, plain.inventory().unwrap());
+        assert_eq!(agc.par_inventory().unwrap(), aliased);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::AgcError;
+
+/// How a bulk operation handles an error on one of its entries
+///
+/// Taken by the `*_with` variants of bulk methods:
+/// `AgcFile::inventory_with`, `AgcFile::extract_samples_to_dir_with`,
+/// `AgcFile::write_all_fasta_with` and
+/// `AgcFile::extract_sample_verified_with`. Errors that prevent the
+/// operation from starting at all, such as failing to list the samples,
+/// or that leave its output unusable, such as failing to write it, are
+/// returned under either policy. Iterators such as `AgcFile::contigs` and
+/// `AgcFile::contig_records` need no policy: each item is its own
+/// `Result`, so the caller decides whether to stop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop at the first error and return it
+    #[default]
+    FailFast,
+    /// Record the error, skip the entry and carry on
+    Collect,
+}
+
+/// Results of a bulk operation run with `ErrorPolicy::Collect`
+///
+/// Under `ErrorPolicy::FailFast`, `errors` is always empty.
+#[derive(Debug, Default)]
+pub struct Collected<T> {
+    /// Entries that succeeded, in order
+    pub items: Vec<T>,
+    /// Errors of the entries that failed, in order
+    pub errors: Vec<AgcError>,
+}
+
+impl<T> Collected<T> {
+    /// Whether every entry succeeded
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Applies an `ErrorPolicy` entry by entry while building a `Collected`
+pub(crate) struct Collector<T> {
+    policy: ErrorPolicy,
+    collected: Collected<T>,
+}
+
+impl<T> Collector<T> {
+    pub(crate) fn new(policy: ErrorPolicy, capacity: usize) -> Self {
+        Collector {
+            policy,
+            collected: Collected {
+                items: Vec::with_capacity(capacity),
+                errors: Vec::new(),
+            },
+        }
+    }
+
+    /// Record one entry's result
+    ///
+    /// # Returns
+    /// The error itself under `FailFast`, for the caller to propagate
+    pub(crate) fn push(&mut self, result: Result<T, AgcError>) -> Result<(), AgcError> {
+        match result {
+            Ok(item) => self.collected.items.push(item),
+            Err(e) if self.policy == ErrorPolicy::Collect => self.collected.errors.push(e),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Collected<T> {
+        self.collected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries where the second fails
+    fn entries() -> Vec<Result<i32, AgcError>> {
+        vec![Ok(1), Err(AgcError::SampleNotFound("b".to_string())), Ok(3)]
+    }
+
+    fn run(policy: ErrorPolicy) -> Result<Collected<i32>, AgcError> {
+        let mut collector = Collector::new(policy, 3);
+        for entry in entries() {
+            collector.push(entry)?;
+        }
+        Ok(collector.finish())
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        assert!(matches!(
+            run(ErrorPolicy::FailFast),
+            Err(AgcError::SampleNotFound(name)) if name == "b"
+        ));
+    }
+
+    #[test]
+    fn test_collect() {
+        let collected = run(ErrorPolicy::Collect).unwrap();
+        assert_eq!(collected.items, [1, 3]);
+        assert_eq!(collected.errors.len(), 1);
+        assert!(!collected.is_complete());
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use crate::casefold::NameFolding;
+use crate::{AgcError, AgcFile, PrefetchState};
+
+/// A pool of handles to one archive for concurrent readers
+///
+/// Each concurrent reader gets its own `AgcFile`, so no handle is ever
+/// shared between threads mid-query. Handles are opened lazily on
+/// checkout, up to `max_size`; once that many are checked out, `checkout`
+/// blocks until one is returned.
+///
+/// Dropping a guard only returns its handle; the handles are closed when
+/// the pool itself is dropped, which the guards' borrows delay until every
+/// one of them is gone.
+pub struct AgcPool {
+    filename: String,
+    prefetching: bool,
+    max_size: usize,
+    /// Options given to every handle the pool opens, when taken from an
+    /// existing handle
+    options: Option<HandleOptions>,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+/// The query options of a handle, set by the builder and the `with_`
+/// methods, for handles opened later to the same archive
+///
+/// The file lock is left out: the handle the options came from keeps
+/// holding it.
+#[derive(Clone)]
+pub(crate) struct HandleOptions {
+    aliases: HashMap<String, String>,
+    display_names: HashMap<String, String>,
+    max_list_len: usize,
+    strict_bounds: bool,
+    max_fetch_len: Option<usize>,
+    name_folding: Option<NameFolding>,
+    #[cfg(feature = "lru")]
+    contig_cache_capacity: usize,
+    #[cfg(feature = "log")]
+    slow_query_threshold: Option<std::time::Duration>,
+}
+
+impl HandleOptions {
+    /// Give a freshly opened handle these options
+    pub(crate) fn apply(&self, mut agc: AgcFile) -> AgcFile {
+        agc.aliases = self.aliases.clone();
+        agc.display_names = self.display_names.clone();
+        agc.max_list_len = self.max_list_len;
+        agc.strict_bounds = self.strict_bounds;
+        agc.max_fetch_len = self.max_fetch_len;
+        agc.name_folding = self.name_folding.clone();
+        #[cfg(feature = "lru")]
+        {
+            agc = agc.with_contig_cache(self.contig_cache_capacity);
+        }
+        #[cfg(feature = "log")]
+        {
+            agc.slow_query_threshold = self.slow_query_threshold;
+        }
+        agc
+    }
+}
+
+struct PoolState {
+    /// Handles waiting to be checked out
+    idle: Vec<AgcFile>,
+    /// Handles opened, or being opened, by this pool
+    created: usize,
+}
+
+impl AgcPool {
+    /// Create a pool; no handle is opened until the first checkout
+    ///
+    /// # Arguments
+    /// * `filename` - path to the AGC file
+    /// * `prefetching` - whether each handle preloads the file into memory
+    /// * `max_size` - maximum number of open handles, at least 1
+    pub fn new(filename: &str, prefetching: bool, max_size: usize) -> Result<Self, AgcError> {
+        if max_size == 0 {
+            return Err(AgcError::InvalidArgument(
+                "pool size must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(AgcPool {
+            filename: filename.to_string(),
+            prefetching,
+            max_size,
+            options: None,
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                created: 0,
+            }),
+            returned: Condvar::new(),
+        })
+    }
+
+    /// Check out a handle, opening one if none is idle and the pool has room
+    ///
+    /// Blocks while all `max_size` handles are checked out. The handle goes
+    /// back to the pool when the guard is dropped, and the guard borrows the
+    /// pool so it cannot outlive it.
+    pub fn checkout(&self) -> Result<PooledAgcFile<'_>, AgcError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(agc) = state.idle.pop() {
+                return Ok(PooledAgcFile {
+                    pool: self,
+                    agc: Some(agc),
+                });
+            }
+            if state.created < self.max_size {
+                break;
+            }
+            state = self.returned.wait(state).unwrap();
+        }
+
+        // Reserve the slot, then open without holding the lock
+        state.created += 1;
+        drop(state);
+
+        match AgcFile::open(&self.filename, self.prefetching) {
+            Ok(agc) => Ok(PooledAgcFile {
+                pool: self,
+                agc: Some(match &self.options {
+                    Some(options) => options.apply(agc),
+                    None => agc,
+                }),
+            }),
+            Err(e) => {
+                self.state.lock().unwrap().created -= 1;
+                self.returned.notify_one();
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Maximum number of open handles
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Number of handles opened so far
+    pub fn n_open(&self) -> usize {
+        self.state.lock().unwrap().created
+    }
+
+    /// Number of open handles not currently checked out
+    pub fn n_idle(&self) -> usize {
+        self.state.lock().unwrap().idle.len()
+    }
+}
+
+/// A handle checked out of an `AgcPool`, returned to it on drop
+pub struct PooledAgcFile<'a> {
+    pool: &'a AgcPool,
+    agc: Option<AgcFile>,
+}
+
+impl Deref for PooledAgcFile<'_> {
+    type Target = AgcFile;
+
+    fn deref(&self) -> &AgcFile {
+        self.agc.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledAgcFile<'_> {
+    fn drop(&mut self) {
+        if let Some(agc) = self.agc.take() {
+            self.pool.state.lock().unwrap().idle.push(agc);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+impl AgcFile {
+    /// The filename and prefetch flag to open another handle to this
+    /// archive with
+    pub(crate) fn reopen_args(&self) -> Result<(&str, bool), AgcError> {
+        let filename = self.path.to_str().ok_or_else(|| {
+            AgcError::InvalidArgument(format!("Path is not valid UTF-8: {}", self.path.display()))
+        })?;
+        Ok((filename, self.prefetch != PrefetchState::NotRequested))
+    }
+
+    /// This handle's query options, to open more handles like it with
+    pub(crate) fn handle_options(&self) -> HandleOptions {
+        HandleOptions {
+            aliases: self.aliases.clone(),
+            display_names: self.display_names.clone(),
+            max_list_len: self.max_list_len,
+            strict_bounds: self.strict_bounds,
+            max_fetch_len: self.max_fetch_len,
+            name_folding: self.name_folding.clone(),
+            #[cfg(feature = "lru")]
+            contig_cache_capacity: self
+                .contig_cache
+                .as_ref()
+                .map_or(0, |cache| cache.lock().unwrap().cap().get()),
+            #[cfg(feature = "log")]
+            slow_query_threshold: self.slow_query_threshold,
+        }
+    }
+
+    /// Run a closure over every sample with up to `concurrency` workers
+    ///
+    /// Each worker queries through its own handle to this archive, taken
+    /// from an `AgcPool` of at most `concurrency` handles that is closed
+    /// when all samples are done; the closure gets that handle and the
+    /// sample name. The worker handles get this handle's options, such as
+    /// aliases, name folding, strict bounds and `max_fetch_len`, but not
+    /// its file lock, which this handle keeps holding throughout. An error
+    /// from one sample does not stop the others. Samples are handed out in
+    /// archive order, but may finish in any.
+    ///
+    /// # Arguments
+    /// * `concurrency` - maximum number of workers, at least 1
+    /// * `f` - closure run once per sample
+    ///
+    /// # Returns
+    /// Result containing the samples whose closure, or handle, failed, with
+    /// their errors in archive order
+    pub fn for_each_sample<F>(
+        &self,
+        concurrency: usize,
+        f: F,
+    ) -> Result<Vec<(String, AgcError)>, AgcError>
+    where
+        F: Fn(&AgcFile, &str) -> Result<(), AgcError> + Sync,
+    {
+        if concurrency == 0 {
+            return Err(AgcError::InvalidArgument(
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+        let samples = self.list_sample()?;
+        let (filename, prefetching) = self.reopen_args()?;
+        let workers = concurrency.min(samples.len());
+        let mut pool = AgcPool::new(filename, prefetching, workers.max(1))?;
+        pool.options = Some(self.handle_options());
+
+        let next = AtomicUsize::new(0);
+        let failed = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= samples.len() {
+                        break;
+                    }
+                    if let Err(e) = pool.checkout().and_then(|agc| f(&agc, &samples[i])) {
+                        failed.lock().unwrap().push((i, e));
+                    }
+                });
+            }
+        });
+
+        let mut failed = failed.into_inner().unwrap();
+        failed.sort_by_key(|(i, _)| *i);
+        Ok(failed
+            .into_iter()
+            .map(|(i, e)| (samples[i].clone(), e))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_pool_reuses_handles() {
+        let pool = AgcPool::new(TEST_FILE, true, 2).expect("Failed to create pool");
+        assert_eq!(pool.n_open(), 0);
+
+        let first = pool.checkout().expect("Failed to check out");
+        assert!(first.n_sample() > 0);
+        drop(first);
+        assert_eq!(pool.n_idle(), 1);
+
+        let _again = pool.checkout().expect("Failed to check out");
+        assert_eq!(pool.n_open(), 1, "An idle handle should be reused");
+
+        assert!(AgcPool::new(TEST_FILE, true, 0).is_err());
+    }
+
+    #[test]
+    fn test_pool_concurrent_checkouts() {
+        let pool = AgcPool::new(TEST_FILE, true, 3).expect("Failed to create pool");
+        let in_use = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..5 {
+                        let agc = pool.checkout().expect("Failed to check out");
+                        let now = in_use.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+
+                        let sample = agc.reference_sample().unwrap();
+                        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+                        assert!(agc.get_ctg_len(Some(&sample), &contig).unwrap() > 0);
+
+                        in_use.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= pool.max_size());
+        assert!(pool.n_open() <= pool.max_size());
+        assert_eq!(pool.n_idle(), pool.n_open());
+    }
+
+    #[test]
+    fn test_for_each_sample() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let mut samples = agc.list_sample().unwrap();
+        let seen = Mutex::new(Vec::new());
+
+        let failed = agc
+            .for_each_sample(2, |handle, sample| {
+                handle.n_ctg(sample)?;
+                seen.lock().unwrap().push(sample.to_string());
+                if *sample == samples[0] {
+                    return Err(AgcError::InvalidArgument("first".to_string()));
+                }
+                Ok(())
+            })
+            .expect("Failed to run over samples");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, samples[0]);
+        assert!(matches!(&failed[0].1, AgcError::InvalidArgument(m) if m == "first"));
+
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort();
+        samples.sort();
+        assert_eq!(seen, samples);
+
+        assert!(agc.for_each_sample(0, |_, _| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_for_each_sample_keeps_options() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = plain.reference_sample().unwrap();
+        let contig = plain.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = plain.get_ctg_len(Some(&sample), &contig).unwrap();
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .strict_bounds(true)
+            .case_insensitive_names(true)
+            .max_fetch_len(len as usize - 1)
+            .open()
+            .expect("Failed to open file")
+            .with_contig_aliases(HashMap::from([("alias".to_string(), contig.clone())]));
+
+        let failed = agc
+            .for_each_sample(2, |handle, current| {
+                if current != sample {
+                    return Ok(());
+                }
+                let folded = contig.to_uppercase();
+                assert_eq!(handle.ctg_len(Some(&sample.to_uppercase()), &folded)?, len);
+                assert_eq!(handle.ctg_len(Some(&sample), "alias")?, len);
+                assert!(matches!(
+                    handle.ctg_seq_bytes(Some(&sample), &contig, 0, len + 1),
+                    Err(AgcError::InvalidRange { .. })
+                ));
+                assert!(matches!(
+                    handle.ctg_seq_bytes(Some(&sample), &contig, 0, len),
+                    Err(AgcError::FetchTooLarge { .. })
+                ));
+                Ok(())
+            })
+            .expect("Failed to run over samples");
+        assert!(failed.is_empty(), "{:?}", failed);
+    }
+
+    #[test]
+    fn test_drops_during_queries() {
+        let shared = Arc::new(AgcFile::open(TEST_FILE, true).expect("Failed to open file"));
+        let sample = shared.reference_sample().unwrap();
+        let contig = shared.list_ctg(Some(&sample)).unwrap().remove(0);
+        let expected = shared.get_ctg_seq(Some(&sample), &contig, 0, 10).unwrap();
+        let pool = AgcPool::new(TEST_FILE, true, 2).expect("Failed to create pool");
+
+        thread::scope(|scope| {
+            for i in 0..8 {
+                let shared = Arc::clone(&shared);
+                let (pool, sample, contig, expected) = (&pool, &sample, &contig, &expected);
+                scope.spawn(move || {
+                    // Threads let go of their clone and guards at different
+                    // points while the others keep querying
+                    let mut shared = Some(shared);
+                    for round in 0..20 {
+                        if round == i * 2 {
+                            shared = None;
+                        }
+                        if let Some(agc) = &shared {
+                            let seq = agc.get_ctg_seq(Some(sample), contig, 0, 10).unwrap();
+                            assert_eq!(&seq, expected);
+                        }
+                        let pooled = pool.checkout().expect("Failed to check out");
+                        let seq = pooled.get_ctg_seq(Some(sample), contig, 0, 10).unwrap();
+                        assert_eq!(&seq, expected);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(Arc::strong_count(&shared), 1);
+        assert_eq!(pool.n_idle(), pool.n_open());
+        drop(pool);
+        assert_eq!(
+            shared.get_ctg_seq(Some(&sample), &contig, 0, 10).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Best-effort assessment of whether prefetching took effect
+//!
+//! The AGC C API accepts a prefetch flag but does not report whether the
+//! archive was actually loaded into memory, so the state is estimated at
+//! open time by comparing the archive size against the memory available.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{AgcError, AgcFile};
+
+/// Rough bytes a length cache entry takes besides its names: the key's
+/// string and option headers, the length and the hash table slot
+const LEN_CACHE_ENTRY_OVERHEAD: u64 = 80;
+
+/// Estimated outcome of a prefetch request, taken when the archive was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefetchState {
+    /// The archive was opened without prefetching
+    NotRequested,
+    /// Prefetching was requested and the archive fitted in available memory
+    Likely {
+        /// Archive size in bytes
+        file_size: u64,
+        /// Available memory in bytes at open time
+        available: u64,
+    },
+    /// Prefetching was requested but the archive exceeded available memory,
+    /// so AGC may have paged or fallen back to reading on demand
+    Unlikely {
+        /// Archive size in bytes
+        file_size: u64,
+        /// Available memory in bytes at open time
+        available: u64,
+    },
+    /// Prefetching was requested but available memory could not be
+    /// determined on this platform
+    Unknown,
+}
+
+/// Estimate the prefetch state of an archive about to be opened
+pub(crate) fn assess(path: &Path, requested: bool) -> PrefetchState {
+    if !requested {
+        return PrefetchState::NotRequested;
+    }
+
+    let file_size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return PrefetchState::Unknown,
+    };
+    match available_memory() {
+        Some(available) if file_size <= available => PrefetchState::Likely {
+            file_size,
+            available,
+        },
+        Some(available) => PrefetchState::Unlikely {
+            file_size,
+            available,
+        },
+        None => PrefetchState::Unknown,
+    }
+}
+
+impl AgcFile {
+    /// Estimate the memory this handle holds, in bytes
+    ///
+    /// Counts the archive size if prefetching was requested, since AGC then
+    /// loads the whole file, and nothing for the archive otherwise, plus
+    /// the crate's own caches: contig lengths and, with the `lru` feature,
+    /// cached contigs. AGC's working buffers while decompressing and the
+    /// smaller lookup tables are not counted, so treat the figure as a
+    /// lower bound for budgeting process memory.
+    ///
+    /// # Returns
+    /// Result containing the estimate, or `AgcError::Io` if the archive
+    /// size cannot be read
+    pub fn estimated_memory_footprint(&self) -> Result<u64, AgcError> {
+        let mut bytes = match self.prefetch {
+            PrefetchState::NotRequested => 0,
+            _ => fs::metadata(&self.path)?.len(),
+        };
+        bytes += self
+            .len_cache
+            .lock()
+            .unwrap()
+            .keys()
+            .map(|(sample, name)| {
+                let names = sample.as_ref().map_or(0, String::len) + name.len();
+                LEN_CACHE_ENTRY_OVERHEAD + names as u64
+            })
+            .sum::<u64>();
+        #[cfg(feature = "lru")]
+        if let Some(cache) = &self.contig_cache {
+            bytes += cache
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, seq)| seq.len() as u64)
+                .sum::<u64>();
+        }
+        Ok(bytes)
+    }
+}
+
+/// Available memory in bytes, where the platform reports it
+fn available_memory() -> Option<u64> {
+    parse_mem_available(&fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+/// Extract `MemAvailable` from the contents of `/proc/meminfo`
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_parse_mem_available() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1234567 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(parse_mem_available(meminfo), Some(8_000_000 * 1024));
+        assert_eq!(parse_mem_available("MemTotal: 16318480 kB\n"), None);
+    }
+
+    #[test]
+    fn test_estimated_memory_footprint() {
+        let file_size = fs::metadata(TEST_FILE).unwrap().len();
+
+        let streaming = AgcFile::open(TEST_FILE, false).expect("Failed to open file");
+        assert_eq!(streaming.estimated_memory_footprint().unwrap(), 0);
+
+        let prefetched = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        assert_eq!(prefetched.estimated_memory_footprint().unwrap(), file_size);
+
+        // Cached lengths add to the estimate
+        let sample = streaming.reference_sample().unwrap();
+        let contig = streaming.list_ctg(Some(&sample)).unwrap().remove(0);
+        streaming.get_ctg_len(Some(&sample), &contig).unwrap();
+        let cached = streaming.estimated_memory_footprint().unwrap();
+        assert!(cached > 0 && cached < 1024);
+    }
+
+    #[test]
+    fn test_prefetch_state() {
+        let agc = AgcFile::open(TEST_FILE, false).expect("Failed to open file");
+        assert_eq!(agc.prefetch_state(), PrefetchState::NotRequested);
+
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        match agc.prefetch_state() {
+            PrefetchState::Likely {
+                file_size,
+                available,
+            } => {
+                assert_eq!(file_size, fs::metadata(TEST_FILE).unwrap().len());
+                assert!(file_size <= available);
+            }
+            PrefetchState::Unknown => assert!(available_memory().is_none()),
+            state => panic!("Unexpected prefetch state for the fixture: {:?}", state),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::sketch::mix64;
+use crate::{AgcError, AgcFile};
+
+/// SplitMix64, a small seeded generator; good enough for picking read
+/// positions and identical on every platform
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        mix64(self.0)
+    }
+
+    /// A value in `[0, bound)`, for `bound > 0`
+    fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+impl AgcFile {
+    /// Draw reads at uniformly random positions across a sample
+    ///
+    /// Every start position from which a whole read fits inside a contig
+    /// is equally likely, so contigs are picked in proportion to their
+    /// length and no read runs off a contig end; contigs shorter than
+    /// `read_len` are never picked. The same seed gives the same reads.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `read_len` - bases per read, at least 1
+    /// * `n` - number of reads
+    /// * `seed` - seed of the random generator
+    ///
+    /// # Returns
+    /// Result containing `(contig, start, bases)` for each read, with a
+    /// 0-based start; `AgcError::InvalidArgument` if no contig is long
+    /// enough
+    pub fn uniform_sample_reads(
+        &self,
+        sample: &str,
+        read_len: usize,
+        n: usize,
+        seed: u64,
+    ) -> Result<Vec<(String, usize, Vec<u8>)>, AgcError> {
+        if read_len == 0 || read_len > i32::MAX as usize {
+            return Err(AgcError::InvalidArgument(format!(
+                "read length {} must be between 1 and {}",
+                read_len,
+                i32::MAX
+            )));
+        }
+
+        // Contigs a read fits in, with the first start position of each
+        // on one axis of all valid start positions
+        let mut contigs = Vec::new();
+        let mut total = 0u64;
+        for info in self.contigs(Some(sample))? {
+            let info = info?;
+            let len = info.length as usize;
+            if len >= read_len {
+                contigs.push((total, info.name));
+                total += (len - read_len + 1) as u64;
+            }
+        }
+        if contigs.is_empty() && n > 0 {
+            return Err(AgcError::InvalidArgument(format!(
+                "no contig of sample {} holds a read of {} bases",
+                sample, read_len
+            )));
+        }
+
+        let mut rng = SplitMix64(seed);
+        (0..n)
+            .map(|_| {
+                let pick = rng.below(total);
+                let index = contigs.partition_point(|(first, _)| *first <= pick) - 1;
+                let (first, name) = &contigs[index];
+                let start = (pick - first) as usize;
+                let seq = self.ctg_seq_bytes(
+                    Some(sample),
+                    name,
+                    start as i32,
+                    (start + read_len) as i32,
+                )?;
+                Ok((name.clone(), start, seq))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_split_mix_below() {
+        let mut rng = SplitMix64(7);
+        let draws: Vec<u64> = (0..1000).map(|_| rng.below(10)).collect();
+        assert!(draws.iter().all(|&d| d < 10));
+        assert!((0..10).all(|v| draws.contains(&v)));
+    }
+
+    #[test]
+    fn test_uniform_sample_reads() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let reads = agc.uniform_sample_reads(&sample, 12, 50, 42).unwrap();
+
+        assert_eq!(reads.len(), 50);
+        assert_eq!(
+            reads,
+            agc.uniform_sample_reads(&sample, 12, 50, 42).unwrap()
+        );
+        assert_ne!(
+            reads,
+            agc.uniform_sample_reads(&sample, 12, 50, 43).unwrap()
+        );
+        for (contig, start, seq) in &reads {
+            assert_eq!(seq.len(), 12);
+            let len = agc.get_ctg_len(Some(&sample), contig).unwrap() as usize;
+            assert!(start + 12 <= len);
+            assert_eq!(
+                seq,
+                &agc.ctg_seq_bytes(Some(&sample), contig, *start as i32, *start as i32 + 12)
+                    .unwrap()
+            );
+        }
+
+        assert!(agc.uniform_sample_reads(&sample, 0, 1, 42).is_err());
+        assert!(agc
+            .uniform_sample_reads(&sample, i32::MAX as usize, 1, 42)
+            .is_err());
+    }
+}
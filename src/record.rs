@@ -0,0 +1,155 @@
+use std::vec;
+
+use crate::{AgcError, AgcFile};
+
+/// A named sequence, whatever it was read from
+///
+/// Lets analysis code be written once against `impl SeqRecord` and run
+/// over AGC contigs, FASTA records from another crate, or test data alike.
+pub trait SeqRecord {
+    /// Name of the sequence
+    fn id(&self) -> &str;
+
+    /// The bases, as stored
+    fn seq(&self) -> &[u8];
+
+    /// Number of bases
+    fn len(&self) -> usize {
+        self.seq().len()
+    }
+
+    /// Whether the sequence has no bases
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A whole contig fetched from an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgcSeqRecord {
+    /// Sample the contig was fetched from, None if the query named none
+    pub sample: Option<String>,
+    /// Contig name
+    pub name: String,
+    /// Contig sequence
+    pub seq: Vec<u8>,
+}
+
+impl SeqRecord for AgcSeqRecord {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn seq(&self) -> &[u8] {
+        &self.seq
+    }
+}
+
+/// Iterator over the contigs of a sample as sequence records
+///
+/// Like `Contigs`, the names are listed up front and each contig is only
+/// fetched when its item is reached, so at most one contig is held at a
+/// time unless the caller keeps them. Created by `AgcFile::contig_records`.
+pub struct ContigRecords<'a> {
+    agc: &'a AgcFile,
+    sample: Option<String>,
+    names: vec::IntoIter<String>,
+}
+
+impl Iterator for ContigRecords<'_> {
+    type Item = Result<AgcSeqRecord, AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.names.next()?;
+        Some(
+            self.agc
+                .ctg_full_bytes(self.sample.as_deref(), &name)
+                .map(|seq| AgcSeqRecord {
+                    sample: self.sample.clone(),
+                    name,
+                    seq,
+                }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.names.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ContigRecords<'_> {}
+
+impl AgcFile {
+    /// Iterate over the contigs of a sample as `SeqRecord`s
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    ///
+    /// # Returns
+    /// Result containing a lazy iterator borrowing this handle
+    pub fn contig_records(&self, sample: Option<&str>) -> Result<ContigRecords<'_>, AgcError> {
+        Ok(ContigRecords {
+            agc: self,
+            sample: sample.map(str::to_string),
+            names: self.list_ctg(sample)?.into_iter(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// A record from somewhere other than an archive
+    struct Plain(&'static str, &'static [u8]);
+
+    impl SeqRecord for Plain {
+        fn id(&self) -> &str {
+            self.0
+        }
+
+        fn seq(&self) -> &[u8] {
+            self.1
+        }
+    }
+
+    /// A format-agnostic analysis: the id and G+C count of a record
+    fn gc_count(record: &impl SeqRecord) -> (String, usize) {
+        let gc = record
+            .seq()
+            .iter()
+            .filter(|b| matches!(b.to_ascii_uppercase(), b'G' | b'C'))
+            .count();
+        (record.id().to_string(), gc)
+    }
+
+    #[test]
+    fn test_seq_record_generic() {
+        let plain = Plain("seq1", b"ACGGTc");
+        assert_eq!(gc_count(&plain), ("seq1".to_string(), 4));
+        assert_eq!(plain.len(), 6);
+        assert!(!plain.is_empty());
+        assert!(Plain("empty", b"").is_empty());
+    }
+
+    #[test]
+    fn test_contig_records() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+
+        let records = agc.contig_records(Some(&sample)).unwrap();
+        assert_eq!(records.len(), contigs.len());
+        for (record, contig) in records.zip(&contigs) {
+            let record = record.unwrap();
+            let seq = agc.ctg_full_bytes(Some(&sample), contig).unwrap();
+            assert_eq!(record.id(), contig);
+            assert_eq!(record.sample.as_deref(), Some(sample.as_str()));
+            assert_eq!(record.len(), seq.len());
+            let gc = seq.iter().filter(|b| b"GCgc".contains(b)).count();
+            assert_eq!(gc_count(&record), (contig.clone(), gc));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use crate::AgcError;
+
+/// A samtools-style region such as `chr1`, `chr1:1000` or `chr1:1,000-2,000`
+///
+/// Coordinates in the region string are 1-based and inclusive, as in
+/// samtools. They are stored 0-based and half-open, as used by the rest of
+/// the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Contig name
+    pub name: String,
+    /// 0-based start offset
+    pub start: i32,
+    /// 0-based exclusive end offset; None means the end of the contig
+    pub end: Option<i32>,
+}
+
+impl Region {
+    /// Parse a region string
+    ///
+    /// A suffix after the last `:` is only treated as coordinates if it
+    /// parses as such, so contig names containing `:` can still be given
+    /// without coordinates.
+    ///
+    /// # Arguments
+    /// * `region` - region string, e.g. `chr1:100-200`
+    ///
+    /// # Returns
+    /// Result containing the parsed region or `AgcError::InvalidRegion`
+    pub fn parse(region: &str) -> Result<Self, AgcError> {
+        let invalid = || AgcError::InvalidRegion(region.to_string());
+
+        if region.is_empty() {
+            return Err(invalid());
+        }
+
+        let (name, coords) = match region.rsplit_once(':') {
+            Some((name, coords)) if !name.is_empty() && looks_like_coords(coords) => (name, coords),
+            _ => {
+                return Ok(Region {
+                    name: region.to_string(),
+                    start: 0,
+                    end: None,
+                })
+            }
+        };
+
+        let (begin, end) = match coords.split_once('-') {
+            Some((begin, end)) => (parse_coord(begin).ok_or_else(invalid)?, Some(end)),
+            None => (parse_coord(coords).ok_or_else(invalid)?, None),
+        };
+        if begin < 1 {
+            return Err(invalid());
+        }
+
+        let end = match end {
+            Some(end) => {
+                let end = parse_coord(end).ok_or_else(invalid)?;
+                if end < begin {
+                    return Err(invalid());
+                }
+                Some(end)
+            }
+            None => None,
+        };
+
+        Ok(Region {
+            name: name.to_string(),
+            start: begin - 1,
+            end,
+        })
+    }
+}
+
+impl FromStr for Region {
+    type Err = AgcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Region::parse(s)
+    }
+}
+
+/// Separator between sample and contig in a qualified contig name
+///
+/// AGC's command line tools print contigs as `sample#contig`, e.g.
+/// `HG002#chr1`.
+pub const SAMPLE_SEPARATOR: char = '#';
+
+/// Split a qualified `sample#contig` name into its sample and contig
+///
+/// The split is made at the last `#`, so PanSN-style sample names such as
+/// `HG002#1` in `HG002#1#chr1` are kept whole. Names without a `#`, or with
+/// an empty sample or contig part, are returned unchanged with no sample.
+///
+/// # Arguments
+/// * `name` - contig name, qualified or plain
+///
+/// # Returns
+/// The sample, if present, and the contig name
+pub fn split_qualified_name(name: &str) -> (Option<&str>, &str) {
+    match name.rsplit_once(SAMPLE_SEPARATOR) {
+        Some((sample, contig)) if !sample.is_empty() && !contig.is_empty() => {
+            (Some(sample), contig)
+        }
+        _ => (None, name),
+    }
+}
+
+/// Whether a suffix is made of coordinate characters only
+fn looks_like_coords(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '-')
+}
+
+/// Parse a coordinate, allowing thousands separators
+fn parse_coord(s: &str) -> Option<i32> {
+    let digits: String = s.chars().filter(|&c| c != ',').collect();
+    if digits.is_empty() {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_only() {
+        let region = Region::parse("chr1").unwrap();
+        assert_eq!(region.name, "chr1");
+        assert_eq!(region.start, 0);
+        assert_eq!(region.end, None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let region = Region::parse("chr1:1,001-2,000").unwrap();
+        assert_eq!(region.name, "chr1");
+        assert_eq!(region.start, 1000);
+        assert_eq!(region.end, Some(2000));
+
+        let region = Region::parse("chr1:11").unwrap();
+        assert_eq!(region.start, 10);
+        assert_eq!(region.end, None);
+    }
+
+    #[test]
+    fn test_parse_name_with_colon() {
+        let region = Region::parse("HLA:A*01").unwrap();
+        assert_eq!(region.name, "HLA:A*01");
+        assert_eq!(region.start, 0);
+    }
+
+    #[test]
+    fn test_split_qualified_name() {
+        assert_eq!(split_qualified_name("HG002#chr1"), (Some("HG002"), "chr1"));
+        assert_eq!(
+            split_qualified_name("HG002#1#chr1"),
+            (Some("HG002#1"), "chr1")
+        );
+        assert_eq!(split_qualified_name("chr1"), (None, "chr1"));
+        assert_eq!(split_qualified_name("#chr1"), (None, "#chr1"));
+        assert_eq!(split_qualified_name("chr1#"), (None, "chr1#"));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert!(Region::parse("").is_err());
+        assert!(Region::parse("chr1:0-10").is_err());
+        assert!(Region::parse("chr1:20-10").is_err());
+        assert!(Region::parse("chr1:10-").is_err());
+        assert!(Region::parse("chr1:1-2-3").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Read-through cache of fetched regions, backed by a pluggable store
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{AgcError, AgcFile};
+
+/// The region a cached sequence was fetched for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegionKey {
+    /// Sample name, None if the fetch named none
+    pub sample: Option<String>,
+    /// Contig name
+    pub contig: String,
+    /// Start of the fetched range
+    pub start: i32,
+    /// Exclusive end of the fetched range
+    pub end: i32,
+}
+
+/// Storage for fetched regions, consulted by `CachedReader`
+///
+/// Implement this to keep hot regions in an external store such as Redis
+/// or a disk cache shared between processes. Methods take `&self`, so an
+/// implementation handles its own locking. A store that cannot be reached
+/// should answer `get` with None and drop `put`s: the reader then falls
+/// back to fetching from the archive.
+pub trait RegionCache {
+    /// Look up a region's sequence
+    fn get(&self, key: &RegionKey) -> Option<Vec<u8>>;
+
+    /// Store a region's sequence after it was fetched
+    fn put(&self, key: RegionKey, seq: Vec<u8>);
+}
+
+/// In-memory `RegionCache` without eviction
+///
+/// Holds every region put into it, so it suits a bounded set of hot
+/// regions; for whole contigs see `AgcFile::with_contig_cache`.
+#[derive(Debug, Default)]
+pub struct MemoryRegionCache {
+    regions: Mutex<HashMap<RegionKey, Vec<u8>>>,
+}
+
+impl MemoryRegionCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached regions
+    pub fn len(&self) -> usize {
+        self.regions.lock().unwrap().len()
+    }
+
+    /// Whether no region is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl RegionCache for MemoryRegionCache {
+    fn get(&self, key: &RegionKey) -> Option<Vec<u8>> {
+        self.regions.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: RegionKey, seq: Vec<u8>) {
+        self.regions.lock().unwrap().insert(key, seq);
+    }
+}
+
+/// Region fetches through a `RegionCache`
+///
+/// Each fetch is looked up in the cache first; on a miss it is fetched
+/// from the archive and put into the cache. Regions are cached under the
+/// names and coordinates as requested, so differently written requests
+/// for the same bases are cached separately.
+pub struct CachedReader<'a, C> {
+    agc: &'a AgcFile,
+    cache: C,
+}
+
+impl<'a, C: RegionCache> CachedReader<'a, C> {
+    /// Read from an archive through a cache
+    ///
+    /// # Arguments
+    /// * `agc` - archive to fetch misses from
+    /// * `cache` - store of fetched regions
+    pub fn new(agc: &'a AgcFile, cache: C) -> Self {
+        CachedReader { agc, cache }
+    }
+
+    /// Get `[start, end)` of a contig, from the cache if it has it
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    ///
+    /// # Returns
+    /// Result containing the bases; failed fetches are not cached
+    pub fn get_ctg_seq(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<Vec<u8>, AgcError> {
+        let key = RegionKey {
+            sample: sample.map(str::to_string),
+            contig: name.to_string(),
+            start,
+            end,
+        };
+        if let Some(seq) = self.cache.get(&key) {
+            return Ok(seq);
+        }
+        let seq = self.agc.ctg_seq_bytes(sample, name, start, end)?;
+        self.cache.put(key, seq.clone());
+        Ok(seq)
+    }
+
+    /// The cache fetches go through
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// The archive misses are fetched from
+    pub fn agc(&self) -> &'a AgcFile {
+        self.agc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_cached_reader() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let reader = CachedReader::new(&agc, MemoryRegionCache::new());
+
+        let calls = agc.ffi_seq_calls.load(Ordering::Relaxed);
+        let first = reader.get_ctg_seq(Some(&sample), &contig, 0, 10).unwrap();
+        assert_eq!(agc.ffi_seq_calls.load(Ordering::Relaxed), calls + 1);
+        assert_eq!(reader.cache().len(), 1, "A miss should populate the cache");
+
+        let second = reader.get_ctg_seq(Some(&sample), &contig, 0, 10).unwrap();
+        assert_eq!(
+            agc.ffi_seq_calls.load(Ordering::Relaxed),
+            calls + 1,
+            "A hit should not call into AGC"
+        );
+        assert_eq!(second, first);
+        assert_eq!(
+            first,
+            agc.get_ctg_seq(Some(&sample), &contig, 0, 10)
+                .unwrap()
+                .into_bytes()
+        );
+
+        assert!(reader
+            .get_ctg_seq(Some(&sample), "no_such_contig", 0, 10)
+            .is_err());
+        assert_eq!(
+            reader.cache().len(),
+            1,
+            "Failed fetches should not be cached"
+        );
+    }
+}
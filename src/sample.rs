@@ -0,0 +1,140 @@
+use crate::{AgcError, AgcFile, ContigInfo, Contigs};
+
+/// A sample of an archive, for repeated queries against the same sample
+///
+/// Created by `AgcFile::sample`, which checks that the sample exists, or
+/// `AgcFile::reference_handle`.
+#[derive(Clone)]
+pub struct SampleHandle<'a> {
+    agc: &'a AgcFile,
+    name: String,
+}
+
+impl<'a> SampleHandle<'a> {
+    /// Sample name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Archive the sample belongs to
+    pub fn archive(&self) -> &'a AgcFile {
+        self.agc
+    }
+
+    /// Get the number of contigs in the sample
+    pub fn n_ctg(&self) -> Result<i32, AgcError> {
+        Ok(self.agc.n_ctg(&self.name)?)
+    }
+
+    /// List the contigs of the sample
+    pub fn list_ctg(&self) -> Result<Vec<String>, AgcError> {
+        Ok(self.agc.list_ctg(Some(&self.name))?)
+    }
+
+    /// Iterate over the contigs of the sample with their lengths
+    pub fn contigs(&self) -> Result<Contigs<'a>, AgcError> {
+        self.agc.contigs(Some(&self.name))
+    }
+
+    /// Get the length of a contig
+    ///
+    /// # Arguments
+    /// * `contig` - contig name
+    pub fn get_ctg_len(&self, contig: &str) -> Result<i32, AgcError> {
+        self.agc.ctg_len(Some(&self.name), contig)
+    }
+
+    /// Get the bytes of `[start, end)` of a contig
+    ///
+    /// # Arguments
+    /// * `contig` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    pub fn get_ctg_seq(&self, contig: &str, start: i32, end: i32) -> Result<Vec<u8>, AgcError> {
+        self.agc.ctg_seq_bytes(Some(&self.name), contig, start, end)
+    }
+
+    /// Get the longest contig of the sample
+    pub fn longest_contig(&self) -> Result<ContigInfo, AgcError> {
+        self.agc.longest_contig(&self.name)
+    }
+}
+
+impl AgcFile {
+    /// Get a handle to a sample
+    ///
+    /// The handle borrows `self`; iterators obtained from it borrow the
+    /// archive for the same lifetime.
+    ///
+    /// # Arguments
+    /// * `name` - sample name
+    ///
+    /// # Returns
+    /// Result containing the handle, or `AgcError::SampleNotFound`
+    pub fn sample(&self, name: &str) -> Result<SampleHandle<'_>, AgcError> {
+        if !self.has_sample(name)? {
+            return Err(AgcError::SampleNotFound(name.to_string()));
+        }
+
+        Ok(SampleHandle {
+            agc: self,
+            name: name.to_string(),
+        })
+    }
+
+    /// Get a handle to the reference sample
+    ///
+    /// The reference sample name is looked up once per `AgcFile` and
+    /// cached, so repeated calls do not go back to AGC. The handle borrows
+    /// `self` as for [`AgcFile::sample`].
+    pub fn reference_handle(&self) -> Result<SampleHandle<'_>, AgcError> {
+        Ok(SampleHandle {
+            agc: self,
+            name: self.reference_sample()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_reference_handle() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let handle = agc
+            .reference_handle()
+            .expect("Failed to get reference handle");
+        assert_eq!(handle.name(), reference);
+
+        let contigs = agc.list_ctg(Some(&reference)).unwrap();
+        assert_eq!(handle.list_ctg().unwrap(), contigs);
+        assert_eq!(handle.n_ctg().unwrap(), agc.n_ctg(&reference).unwrap());
+
+        for contig in &contigs {
+            let len = agc.get_ctg_len(Some(&reference), contig).unwrap();
+            assert_eq!(handle.get_ctg_len(contig).unwrap(), len);
+            assert_eq!(
+                String::from_utf8(handle.get_ctg_seq(contig, 0, len).unwrap()).unwrap(),
+                agc.get_ctg_seq(Some(&reference), contig, 0, len).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_handle_lookup() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for name in agc.list_sample().unwrap() {
+            assert_eq!(agc.sample(&name).unwrap().name(), name);
+        }
+        assert!(matches!(
+            agc.sample("no_such_sample"),
+            Err(AgcError::SampleNotFound(_))
+        ));
+    }
+}
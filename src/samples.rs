@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::rc::Rc;
+
+use crate::{agc_list_destroy, agc_list_sample, list_count, AgcError, AgcFile};
+
+/// The haplotype samples of one individual
+///
+/// Returned by `AgcFile::infer_haplotype_groups`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HaplotypeGroup {
+    /// Individual the samples belong to
+    pub individual: String,
+    /// `(sample, haplotype)` of each member, in archive order; the
+    /// haplotype is empty for a sample named after the individual alone
+    pub members: Vec<(String, String)>,
+    /// Number of distinct haplotypes among the members
+    pub ploidy: usize,
+}
+
+/// The sample names of an archive, as listed by AGC
+///
+/// Created by `AgcFile::samples`. `len()` is known without converting any
+/// name, and iterating by value converts one name per step. AGC's list is
+/// freed when the view, or the iterator made from it, is dropped.
+pub struct SampleList<'a> {
+    samples: Samples<'a>,
+}
+
+impl SampleList<'_> {
+    /// Number of sample names
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether the archive lists no samples
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> IntoIterator for SampleList<'a> {
+    type Item = String;
+    type IntoIter = Samples<'a>;
+
+    fn into_iter(self) -> Samples<'a> {
+        self.samples
+    }
+}
+
+/// Iterator over the sample names of an archive
+///
+/// Holds the list returned by AGC and converts one name per step; the list
+/// is freed when the iterator is dropped, whether or not it was exhausted.
+/// Created from a `SampleList`.
+pub struct Samples<'a> {
+    list: *mut *mut c_char,
+    len: usize,
+    next: usize,
+    /// Names not yet yielded; AGC's null entries are skipped, so this can
+    /// be less than `len - next`
+    remaining: usize,
+    _agc: PhantomData<&'a AgcFile>,
+}
+
+impl Iterator for Samples<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while self.next < self.len {
+            let ptr = unsafe { *self.list.add(self.next) };
+            self.next += 1;
+            if !ptr.is_null() {
+                self.remaining -= 1;
+                return Some(
+                    unsafe { CStr::from_ptr(ptr) }
+                        .to_string_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Samples<'_> {}
+
+impl Drop for Samples<'_> {
+    fn drop(&mut self) {
+        if !self.list.is_null() {
+            unsafe {
+                agc_list_destroy(self.list);
+            }
+        }
+    }
+}
+
+impl AgcFile {
+    /// Iterate over sample names without collecting them first
+    ///
+    /// The iterator owns AGC's name list but borrows `self`, so the archive
+    /// cannot be closed while it is alive.
+    ///
+    /// The count is checked against `with_max_list_len` as for
+    /// `list_sample`, and AGC's null entries are left out of `len()`.
+    ///
+    /// # Returns
+    /// Result containing a view whose `len()` is the number of samples
+    pub fn samples(&self) -> Result<SampleList<'_>, AgcError> {
+        let mut n_sample: c_int = 0;
+        let list = unsafe { agc_list_sample(self.handle, &mut n_sample) };
+        if list.is_null() {
+            return Err(AgcError::Ffi("Failed to list samples".to_string()));
+        }
+        let len = match list_count(n_sample, self.max_list_len, "samples") {
+            Ok(len) => len,
+            Err(e) => {
+                unsafe { agc_list_destroy(list) };
+                return Err(e);
+            }
+        };
+
+        let remaining = (0..len)
+            .filter(|&i| !unsafe { *list.add(i) }.is_null())
+            .count();
+        Ok(SampleList {
+            samples: Samples {
+                list,
+                len,
+                next: 0,
+                remaining,
+                _agc: PhantomData,
+            },
+        })
+    }
+
+    /// Whether the archive has a sample of this name
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    pub fn has_sample(&self, sample: &str) -> Result<bool, AgcError> {
+        let sample = self.fold_sample(sample);
+        Ok(self.samples()?.into_iter().any(|s| s == sample))
+    }
+
+    /// List the contigs of a sample that must exist
+    ///
+    /// AGC lists no contigs for an unknown sample, so `list_ctg` cannot
+    /// tell a missing sample from one without contigs; this checks with
+    /// [`AgcFile::has_sample`] first. An existing sample without contigs
+    /// still gives an empty list.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the contig names, or `AgcError::SampleNotFound`
+    pub fn list_ctg_checked(&self, sample: &str) -> Result<Vec<String>, AgcError> {
+        if !self.has_sample(sample)? {
+            return Err(AgcError::SampleNotFound(sample.to_string()));
+        }
+        Ok(self.list_ctg(Some(sample))?)
+    }
+
+    /// Group sample names by the part before the first `delimiter`
+    ///
+    /// Pangenome sample names often encode individual and haplotype, so
+    /// with `'.'` the samples `HG002.hap1` and `HG002.hap2` share the key
+    /// `HG002`. A name without the delimiter is a group of its own.
+    ///
+    /// # Arguments
+    /// * `delimiter` - character ending the group prefix
+    ///
+    /// # Returns
+    /// Result containing each group's members, in archive order
+    pub fn group_samples_by_prefix(
+        &self,
+        delimiter: char,
+    ) -> Result<HashMap<String, Vec<String>>, AgcError> {
+        Ok(group_by_prefix(self.samples()?, delimiter))
+    }
+
+    /// Group samples into individuals and their haplotypes
+    ///
+    /// A sample name is split at the first `delimiter` into individual and
+    /// haplotype, so with `'.'` the samples `HG002.hap1` and `HG002.hap2`
+    /// form one diploid individual `HG002`. A name without the delimiter,
+    /// such as a reference, is an individual of ploidy 1. Use
+    /// [`AgcFile::infer_haplotype_groups_with`] for other naming schemes.
+    ///
+    /// # Arguments
+    /// * `delimiter` - character between individual and haplotype
+    ///
+    /// # Returns
+    /// Result containing one group per individual, in order of first
+    /// appearance in the archive
+    pub fn infer_haplotype_groups(&self, delimiter: char) -> Result<Vec<HaplotypeGroup>, AgcError> {
+        self.infer_haplotype_groups_with(|name| {
+            name.split_once(delimiter)
+                .map(|(individual, haplotype)| (individual.to_string(), haplotype.to_string()))
+        })
+    }
+
+    /// Group samples into individuals and haplotypes with a custom parser
+    ///
+    /// # Arguments
+    /// * `parse` - maps a sample name to `(individual, haplotype)`, or None
+    ///   to make the sample an individual of its own with ploidy 1
+    ///
+    /// # Returns
+    /// Result containing one group per individual, in order of first
+    /// appearance in the archive
+    pub fn infer_haplotype_groups_with<F>(&self, parse: F) -> Result<Vec<HaplotypeGroup>, AgcError>
+    where
+        F: Fn(&str) -> Option<(String, String)>,
+    {
+        Ok(haplotype_groups(self.samples()?, parse))
+    }
+
+    /// Every unordered pair of distinct samples, for all-vs-all comparisons
+    ///
+    /// Each pair is yielded once, with its first sample earlier in archive
+    /// order than its second, so `n` samples give `n * (n - 1) / 2` pairs.
+    ///
+    /// # Returns
+    /// Result containing an iterator over `(sample, sample)` pairs
+    pub fn sample_pairs(&self) -> Result<impl Iterator<Item = (String, String)>, AgcError> {
+        Ok(unordered_pairs(self.samples()?.into_iter().collect()))
+    }
+}
+
+/// Pairs `(names[i], names[j])` for every `i < j`
+fn unordered_pairs(names: Vec<String>) -> impl Iterator<Item = (String, String)> {
+    let n = names.len();
+    let names = Rc::new(names);
+    (0..n).flat_map(move |i| {
+        let names = Rc::clone(&names);
+        (i + 1..n).map(move |j| (names[i].clone(), names[j].clone()))
+    })
+}
+
+/// Group names into individuals and haplotypes, see
+/// `AgcFile::infer_haplotype_groups_with`
+fn haplotype_groups<I, F>(names: I, parse: F) -> Vec<HaplotypeGroup>
+where
+    I: IntoIterator<Item = String>,
+    F: Fn(&str) -> Option<(String, String)>,
+{
+    let mut groups: Vec<HaplotypeGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for name in names {
+        let (individual, haplotype) = parse(&name).unwrap_or_else(|| (name.clone(), String::new()));
+        let i = *index.entry(individual.clone()).or_insert_with(|| {
+            groups.push(HaplotypeGroup {
+                individual,
+                members: Vec::new(),
+                ploidy: 0,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[i];
+        if group.members.iter().all(|(_, h)| *h != haplotype) {
+            group.ploidy += 1;
+        }
+        group.members.push((name, haplotype));
+    }
+    groups
+}
+
+/// Group names by the part before the first `delimiter`, keeping order
+/// within each group; a name without the delimiter is its own key
+fn group_by_prefix<I>(names: I, delimiter: char) -> HashMap<String, Vec<String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for name in names {
+        let key = name.split(delimiter).next().unwrap_or_default().to_string();
+        groups.entry(key).or_default().push(name);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_samples_len() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let n_sample = agc.n_sample() as usize;
+
+        let samples = agc.samples().unwrap();
+        assert_eq!(samples.len(), n_sample);
+        let samples = samples.into_iter();
+        assert_eq!(samples.len(), n_sample);
+        assert_eq!(samples.count(), n_sample);
+
+        let mut names = Vec::new();
+        for name in agc.samples().unwrap() {
+            names.push(name);
+        }
+        assert_eq!(names, agc.list_sample().unwrap());
+    }
+
+    #[test]
+    fn test_list_ctg_checked() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            assert!(agc.has_sample(&sample).unwrap());
+            assert_eq!(
+                agc.list_ctg_checked(&sample).unwrap(),
+                agc.list_ctg(Some(&sample)).unwrap()
+            );
+        }
+
+        assert!(!agc.has_sample("no_such_sample").unwrap());
+        assert!(matches!(
+            agc.list_ctg_checked("no_such_sample"),
+            Err(AgcError::SampleNotFound(name)) if name == "no_such_sample"
+        ));
+    }
+
+    #[test]
+    fn test_group_by_prefix() {
+        let names = ["HG002.hap1", "HG003.hap1", "HG002.hap2", "ref"];
+        let groups = group_by_prefix(names.iter().map(|s| s.to_string()), '.');
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["HG002"], ["HG002.hap1", "HG002.hap2"]);
+        assert_eq!(groups["HG003"], ["HG003.hap1"]);
+        assert_eq!(groups["ref"], ["ref"]);
+    }
+
+    #[test]
+    fn test_haplotype_groups() {
+        let names = [
+            "ref",
+            "HG002.hap1",
+            "HG003.hap1",
+            "HG002.hap2",
+            "HG003.hap2",
+        ];
+        let names = || names.iter().map(|s| s.to_string());
+        let parse = |name: &str| {
+            name.split_once('.')
+                .map(|(i, h)| (i.to_string(), h.to_string()))
+        };
+        let groups = haplotype_groups(names(), parse);
+
+        let individuals: Vec<&str> = groups.iter().map(|g| g.individual.as_str()).collect();
+        assert_eq!(individuals, ["ref", "HG002", "HG003"]);
+        assert_eq!(groups[0].ploidy, 1);
+        assert_eq!(groups[0].members, [("ref".to_string(), String::new())]);
+        assert_eq!(groups[1].ploidy, 2);
+        assert_eq!(
+            groups[1].members,
+            [
+                ("HG002.hap1".to_string(), "hap1".to_string()),
+                ("HG002.hap2".to_string(), "hap2".to_string())
+            ]
+        );
+
+        // A parser mapping every sample to one haplotype
+        let haploid = haplotype_groups(names(), |name| {
+            Some((
+                name[..name.find('.').unwrap_or(name.len())].to_string(),
+                "h".to_string(),
+            ))
+        });
+        assert!(haploid.iter().all(|g| g.ploidy == 1));
+        assert_eq!(haploid[1].members.len(), 2);
+    }
+
+    #[test]
+    fn test_infer_haplotype_groups() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let groups = agc.infer_haplotype_groups('.').unwrap();
+        let members: usize = groups.iter().map(|g| g.members.len()).sum();
+        assert_eq!(members, agc.list_sample().unwrap().len());
+        assert!(groups
+            .iter()
+            .all(|g| g.ploidy >= 1 && g.ploidy <= g.members.len()));
+    }
+
+    #[test]
+    fn test_group_samples_by_prefix() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let groups = agc.group_samples_by_prefix('#').unwrap();
+        let mut members: Vec<String> = groups.into_values().flatten().collect();
+        let mut samples = agc.list_sample().unwrap();
+        members.sort();
+        samples.sort();
+        assert_eq!(members, samples);
+    }
+
+    #[test]
+    fn test_unordered_pairs() {
+        let names = ["a", "b", "c"].map(String::from).to_vec();
+        let pairs: Vec<(String, String)> = unordered_pairs(names).collect();
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(
+            pairs,
+            [("a", "b"), ("a", "c"), ("b", "c")].map(|(x, y)| (x.to_string(), y.to_string()))
+        );
+        assert_eq!(unordered_pairs(vec!["a".to_string()]).count(), 0);
+    }
+
+    #[test]
+    fn test_sample_pairs() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let n = agc.list_sample().unwrap().len();
+        let pairs: Vec<(String, String)> = agc.sample_pairs().unwrap().collect();
+        assert_eq!(pairs.len(), n * (n - 1) / 2);
+        assert!(pairs.iter().all(|(a, b)| a != b));
+    }
+
+    #[test]
+    fn test_samples_partial_iteration() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let mut samples = agc.samples().expect("Failed to list samples").into_iter();
+        let total = samples.len();
+
+        assert!(samples.next().is_some());
+        assert_eq!(samples.len(), total - 1);
+        // Dropping part-way through must still free the list
+        drop(samples);
+    }
+
+    #[test]
+    fn test_samples_len_skips_null_entries() {
+        let (a, b) = (CString::new("a").unwrap(), CString::new("b").unwrap());
+        let mut list = vec![
+            a.as_ptr() as *mut c_char,
+            std::ptr::null_mut(),
+            b.as_ptr() as *mut c_char,
+        ];
+        let mut samples = Samples {
+            list: list.as_mut_ptr(),
+            len: list.len(),
+            next: 0,
+            remaining: 2,
+            _agc: PhantomData,
+        };
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples.next().as_deref(), Some("a"));
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples.next().as_deref(), Some("b"));
+        assert_eq!(samples.len(), 0);
+        assert_eq!(samples.next(), None);
+        // The list belongs to the test, not to AGC
+        std::mem::forget(samples);
+    }
+
+    #[test]
+    fn test_samples_max_list_len() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let n_sample = agc.n_sample() as usize;
+        let agc = agc.with_max_list_len(n_sample - 1);
+        assert!(matches!(
+            agc.samples(),
+            Err(AgcError::Ffi(msg)) if msg.contains("limit")
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{AgcError, AgcFile};
+
+/// Small archive used by `AgcFile::self_test`, the crate's test fixture
+const SELF_TEST_ARCHIVE: &[u8] = include_bytes!("../test/data/input/test.agc");
+
+/// Turn a failed check into a self-test error
+fn failed(what: &str) -> AgcError {
+    AgcError::Ffi(format!("libagc self-test failed: {}", what))
+}
+
+impl AgcFile {
+    /// Check that the linked libagc works
+    ///
+    /// Opens a small archive embedded in the crate and runs a few queries
+    /// whose answers must agree with each other: the sample count against
+    /// the sample list, the reference against the list, and a contig's
+    /// length against the sequence fetched for it. A broken or ABI-mismatched
+    /// library then fails here with a clear message rather than on first
+    /// real use. Suitable for CI and application startup.
+    ///
+    /// # Returns
+    /// Ok if every check passed, or an error naming the first that failed
+    pub fn self_test() -> Result<(), AgcError> {
+        let agc = AgcFile::open_reader(SELF_TEST_ARCHIVE, false)?;
+
+        let samples = agc.list_sample()?;
+        if samples.is_empty() || agc.n_sample() as usize != samples.len() {
+            return Err(failed(&format!(
+                "n_sample() is {} but {} samples are listed",
+                agc.n_sample(),
+                samples.len()
+            )));
+        }
+
+        let reference = agc.reference_sample()?;
+        if !samples.contains(&reference) {
+            return Err(failed(&format!(
+                "reference sample {} is not listed",
+                reference
+            )));
+        }
+
+        let contigs = agc.list_ctg(Some(&reference))?;
+        if agc.n_ctg(&reference)? as usize != contigs.len() {
+            return Err(failed("n_ctg() disagrees with list_ctg()"));
+        }
+        let contig = contigs
+            .first()
+            .ok_or_else(|| failed("reference sample has no contigs"))?;
+
+        let len = agc.ctg_len(Some(&reference), contig)?;
+        let seq = agc.ctg_seq_bytes(Some(&reference), contig, 0, len)?;
+        if len <= 0 || seq.len() != len as usize {
+            return Err(failed(&format!(
+                "contig {} has length {} but {} bases were returned",
+                contig,
+                len,
+                seq.len()
+            )));
+        }
+        if !seq.iter().all(u8::is_ascii_alphabetic) {
+            return Err(failed("sequence contains non-letter bytes"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test() {
+        AgcFile::self_test().expect("Self-test should pass with a working libagc");
+    }
+}
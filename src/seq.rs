@@ -0,0 +1,1387 @@
+//! Sequence analysis on fetched bytes
+
+use std::collections::HashMap;
+
+use crate::{check_range, AgcError, AgcFile};
+
+/// Strand of a feature relative to the contig
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    /// Forward strand, read as stored
+    Plus,
+    /// Reverse strand, read as the reverse complement
+    Minus,
+}
+
+/// What `AgcFile::get_ctg_seq_clean` does with bases other than A/C/G/T
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonAcgt {
+    /// Leave them out; positions after the first one dropped no longer
+    /// match contig coordinates
+    Drop,
+    /// Write this byte in their place, keeping coordinates
+    Replace(u8),
+}
+
+/// A region on both strands, from one fetch
+///
+/// Returned by `AgcFile::get_region_both_strands`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BothStrands {
+    /// Bases as stored
+    pub forward: Vec<u8>,
+    /// Reverse complement of `forward`
+    pub reverse: Vec<u8>,
+}
+
+/// Count each length-`k` substring of `seq`, skipping windows containing `N`
+pub(crate) fn count_kmers(seq: &[u8], k: usize) -> HashMap<Vec<u8>, u64> {
+    let mut counts = HashMap::new();
+    if k == 0 {
+        return counts;
+    }
+
+    for window in seq.windows(k) {
+        if window.iter().any(|&b| b == b'N' || b == b'n') {
+            continue;
+        }
+        *counts.entry(window.to_vec()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Shannon entropy in bits of a k-mer count distribution; 0 when empty
+fn kmer_entropy(counts: &HashMap<Vec<u8>, u64>) -> f64 {
+    let total: u64 = counts.values().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .values()
+        .map(|&c| {
+            let p = c as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Predominant alphabet of a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Nucleotides with T
+    Dna,
+    /// Nucleotides with U instead of T
+    Rna,
+    /// Amino acids
+    Protein,
+    /// Empty, or not recognisably any of the above
+    Unknown,
+}
+
+/// Bytes sampled from the start of a contig by `AgcFile::alphabet`
+const ALPHABET_SAMPLE_LEN: i32 = 4096;
+
+/// Classify a sequence by its byte distribution
+///
+/// It is nucleotide if every byte is an IUPAC nucleotide code or gap and at
+/// least 90% are A, C, G, T, U or N; RNA if it then has U but no T. It is
+/// protein if every byte is an amino acid code, stop or gap.
+fn classify_alphabet(seq: &[u8]) -> Alphabet {
+    if seq.is_empty() {
+        return Alphabet::Unknown;
+    }
+
+    let mut core = 0;
+    let (mut has_t, mut has_u) = (false, false);
+    let mut nucleotide = true;
+    let mut protein = true;
+    for &b in seq {
+        let b = b.to_ascii_uppercase();
+        match b {
+            b'A' | b'C' | b'G' | b'N' => core += 1,
+            b'T' => {
+                core += 1;
+                has_t = true;
+            }
+            b'U' => {
+                core += 1;
+                has_u = true;
+            }
+            b'R' | b'Y' | b'K' | b'M' | b'S' | b'W' | b'B' | b'D' | b'H' | b'V' | b'-' => {}
+            _ => nucleotide = false,
+        }
+        if !(b.is_ascii_uppercase() || b == b'*' || b == b'-') {
+            protein = false;
+        }
+    }
+
+    if nucleotide && core * 10 >= seq.len() * 9 {
+        if has_u && !has_t {
+            Alphabet::Rna
+        } else {
+            Alphabet::Dna
+        }
+    } else if protein {
+        Alphabet::Protein
+    } else {
+        Alphabet::Unknown
+    }
+}
+
+/// Base composition counts, accumulated over one or more chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct BaseCounts {
+    /// All bytes seen
+    pub(crate) length: u64,
+    /// G, C, g or c
+    pub(crate) gc: u64,
+    /// G or g, the part of `gc` on the G side
+    pub(crate) g: u64,
+    /// A, T, a or t
+    pub(crate) at: u64,
+    /// N or n
+    pub(crate) n: u64,
+    /// Lowercase (soft-masked) bases
+    pub(crate) masked: u64,
+}
+
+impl BaseCounts {
+    /// Add the bases of a chunk
+    pub(crate) fn add(&mut self, seq: &[u8]) {
+        self.length += seq.len() as u64;
+        for &b in seq {
+            match b.to_ascii_uppercase() {
+                b'G' => {
+                    self.gc += 1;
+                    self.g += 1;
+                }
+                b'C' => self.gc += 1,
+                b'A' | b'T' => self.at += 1,
+                b'N' => self.n += 1,
+                _ => {}
+            }
+            if b.is_ascii_lowercase() {
+                self.masked += 1;
+            }
+        }
+    }
+
+    /// Fraction of G+C among A/C/G/T bases, or None if there are none
+    pub(crate) fn gc_content(&self) -> Option<f64> {
+        let acgt = self.gc + self.at;
+        (acgt > 0).then(|| self.gc as f64 / acgt as f64)
+    }
+
+    /// GC skew `(G - C) / (G + C)`, or None if there is no G or C
+    pub(crate) fn gc_skew(&self) -> Option<f64> {
+        let c = self.gc - self.g;
+        (self.gc > 0).then(|| (self.g as f64 - c as f64) / self.gc as f64)
+    }
+
+    /// Fraction of lowercase bases, or None for an empty sequence
+    pub(crate) fn masked_fraction(&self) -> Option<f64> {
+        (self.length > 0).then(|| self.masked as f64 / self.length as f64)
+    }
+}
+
+/// Bases fetched per chunk when streaming a whole contig
+pub(crate) const STREAM_CHUNK_LEN: usize = 1 << 20;
+
+/// Record bytes of `seq` outside A/C/G/T/N, in either case, as
+/// `(offset + index, byte)` until `found` holds `max` entries
+///
+/// # Returns
+/// Whether a byte was left out because `found` was full
+fn collect_nonstandard(seq: &[u8], offset: i32, found: &mut Vec<(i32, u8)>, max: usize) -> bool {
+    for (i, &b) in seq.iter().enumerate() {
+        if !matches!(b.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'N') {
+            if found.len() == max {
+                return true;
+            }
+            found.push((offset + i as i32, b));
+        }
+    }
+    false
+}
+
+/// Longest run of one A/C/G/T base, in either case, fed chunk by chunk
+///
+/// The run in progress is carried from one chunk to the next, so runs
+/// spanning chunk boundaries are measured whole.
+#[derive(Debug, Default)]
+struct HomopolymerRun {
+    /// Offset of the next base
+    pos: i32,
+    /// `(base, start, length)` of the run ending at `pos`
+    current: Option<(u8, i32, i32)>,
+    /// `(base, start, length)` of the first longest run so far
+    longest: Option<(u8, i32, i32)>,
+}
+
+impl HomopolymerRun {
+    fn add(&mut self, seq: &[u8]) {
+        for &b in seq {
+            let base = b.to_ascii_uppercase();
+            self.current = match (self.current, base) {
+                (Some((run_base, start, len)), _) if run_base == base => {
+                    Some((base, start, len + 1))
+                }
+                (_, b'A' | b'C' | b'G' | b'T') => Some((base, self.pos, 1)),
+                _ => None,
+            };
+            if let Some(run) = self.current {
+                if self.longest.is_none_or(|(_, _, len)| run.2 > len) {
+                    self.longest = Some(run);
+                }
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// Count possibly overlapping occurrences of a non-empty `pattern` in `seq`
+fn count_occurrences(seq: &[u8], pattern: &[u8]) -> u64 {
+    seq.windows(pattern.len()).filter(|w| *w == pattern).count() as u64
+}
+
+/// Complement of a base, preserving case and IUPAC ambiguity codes
+const fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        b'U' => b'A',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        b'a' => b't',
+        b'c' => b'g',
+        b'g' => b'c',
+        b't' => b'a',
+        b'u' => b'a',
+        b'r' => b'y',
+        b'y' => b'r',
+        b'k' => b'm',
+        b'm' => b'k',
+        b'b' => b'v',
+        b'v' => b'b',
+        b'd' => b'h',
+        b'h' => b'd',
+        other => other,
+    }
+}
+
+/// Complement table for DNA, indexed by byte
+///
+/// A/C/G/T and the IUPAC ambiguity codes are complemented in either case,
+/// `U` is complemented to `A` as in DNA, and every other byte, such as
+/// `N` and `-`, is its own complement.
+pub const DNA_COMPLEMENT: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = complement(i as u8);
+        i += 1;
+    }
+    table
+};
+
+/// Reverse-complement a sequence in place with the DNA table
+pub(crate) fn reverse_complement(seq: &mut [u8]) {
+    reverse_complement_with(seq, &DNA_COMPLEMENT);
+}
+
+/// Reverse-complement a sequence in place with a custom complement table
+///
+/// `table[b]` is the complement of byte `b`, e.g. [`DNA_COMPLEMENT`] with
+/// `A` mapped to `U` for RNA, or with extra codes for modified bases.
+///
+/// # Arguments
+/// * `seq` - bases to reverse-complement
+/// * `table` - complement of every byte
+pub fn reverse_complement_with(seq: &mut [u8], table: &[u8; 256]) {
+    seq.reverse();
+    for base in seq.iter_mut() {
+        *base = table[*base as usize];
+    }
+}
+
+/// Check that a complement table undoes itself on an alphabet
+///
+/// Complementing twice must give back every base of `alphabet`, which
+/// catches a table with a one-way mapping such as `U` to `A` but `A` to
+/// `T`. Bytes outside `alphabet` are not checked.
+///
+/// # Arguments
+/// * `table` - complement of every byte
+/// * `alphabet` - bases the table is meant for
+///
+/// # Returns
+/// `AgcError::InvalidArgument` naming the first base that does not map
+/// back to itself
+pub fn check_complement_table(table: &[u8; 256], alphabet: &[u8]) -> Result<(), AgcError> {
+    match alphabet
+        .iter()
+        .find(|&&b| table[table[b as usize] as usize] != b)
+    {
+        Some(&b) => Err(AgcError::InvalidArgument(format!(
+            "complement table maps {:?} to {:?}, which maps back to {:?}",
+            b as char, table[b as usize] as char, table[table[b as usize] as usize] as char
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Uppercase `seq` and drop or replace every byte that is not then A/C/G/T
+fn clean_acgt(seq: &mut Vec<u8>, mode: NonAcgt) {
+    seq.make_ascii_uppercase();
+    let is_acgt = |b: &u8| matches!(b, b'A' | b'C' | b'G' | b'T');
+    match mode {
+        NonAcgt::Drop => seq.retain(is_acgt),
+        NonAcgt::Replace(with) => {
+            for base in seq.iter_mut().filter(|b| !is_acgt(b)) {
+                *base = with;
+            }
+        }
+    }
+}
+
+/// Transcribe DNA to RNA in place, replacing `T`/`t` with `U`/`u`
+fn transcribe(seq: &mut [u8]) {
+    for base in seq.iter_mut() {
+        match *base {
+            b'T' => *base = b'U',
+            b't' => *base = b'u',
+            _ => {}
+        }
+    }
+}
+
+/// Overwrite the parts of `seq` covered by `intervals` with `mask_char`
+///
+/// `seq` holds the contig from `offset` on; intervals are in contig
+/// coordinates and are clipped to the span `seq` covers.
+fn apply_mask(seq: &mut [u8], offset: i32, intervals: &[(i32, i32)], mask_char: u8) {
+    let span_end = offset as i64 + seq.len() as i64;
+    for &(start, end) in intervals {
+        let from = (start as i64).clamp(offset as i64, span_end) - offset as i64;
+        let to = (end as i64).clamp(offset as i64, span_end) - offset as i64;
+        if from < to {
+            seq[from as usize..to as usize].fill(mask_char);
+        }
+    }
+}
+
+/// Join exon sequences, given in contig order, into a transcript on
+/// `strand`, complementing the minus strand with `table`
+fn splice(exons: &[Vec<u8>], strand: Strand, table: &[u8; 256]) -> Vec<u8> {
+    let mut spliced = exons.concat();
+    if strand == Strand::Minus {
+        reverse_complement_with(&mut spliced, table);
+    }
+    spliced
+}
+
+impl AgcFile {
+    /// Bases per chunk when streaming a whole contig: `STREAM_CHUNK_LEN`,
+    /// lowered to `max_fetch_len` when set so every chunk can be fetched
+    pub(crate) fn stream_chunk_len(&self) -> usize {
+        self.max_fetch_len
+            .map_or(STREAM_CHUNK_LEN, |max| STREAM_CHUNK_LEN.min(max.max(1)))
+    }
+
+    /// Count k-mers over a region
+    ///
+    /// Windows containing `N` are skipped. K-mers are counted as stored, so
+    /// soft-masked (lowercase) bases are distinct from uppercase ones.
+    ///
+    /// The map holds up to `min(4^k, end - start)` entries, so memory grows
+    /// quickly with `k` on long regions.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `k` - k-mer length, at least 1
+    pub fn kmer_counts(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        k: usize,
+    ) -> Result<HashMap<Vec<u8>, u64>, AgcError> {
+        if k == 0 {
+            return Err(AgcError::InvalidArgument(
+                "k must be at least 1".to_string(),
+            ));
+        }
+
+        let seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        Ok(count_kmers(&seq, k))
+    }
+
+    /// Get the Shannon entropy of a region's k-mer distribution
+    ///
+    /// The entropy is in bits, from 0 for a homopolymer up to `2k` when
+    /// all `4^k` k-mers are equally frequent, so low values flag simple
+    /// repeats. Bases are uppercased first, so soft-masking does not add
+    /// entropy, and windows containing `N` are skipped; a region without
+    /// any k-mer gives 0.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `k` - k-mer length, at least 1
+    pub fn sequence_complexity(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        k: usize,
+    ) -> Result<f64, AgcError> {
+        if k == 0 {
+            return Err(AgcError::InvalidArgument(
+                "k must be at least 1".to_string(),
+            ));
+        }
+
+        let mut seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        seq.make_ascii_uppercase();
+        Ok(kmer_entropy(&count_kmers(&seq, k)))
+    }
+
+    /// Detect the predominant alphabet of a contig
+    ///
+    /// Only the first few kilobases are sampled, so a contig whose content
+    /// changes further in is classified by its start.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    pub fn alphabet(&self, sample: Option<&str>, name: &str) -> Result<Alphabet, AgcError> {
+        let len = self.ctg_len(sample, name)?;
+        let seq = self.ctg_seq_bytes(sample, name, 0, len.min(ALPHABET_SAMPLE_LEN))?;
+        Ok(classify_alphabet(&seq))
+    }
+
+    /// Count occurrences of a pattern in a contig
+    ///
+    /// Occurrences may overlap (`ATA` occurs twice in `ATATA`). Bases are
+    /// compared as stored, so soft-masked (lowercase) bases do not match an
+    /// uppercase pattern. The contig is streamed in overlapping chunks, so
+    /// memory use does not grow with the contig length.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `pattern` - bases to search for, at least one
+    pub fn count_pattern(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        pattern: &[u8],
+    ) -> Result<u64, AgcError> {
+        self.count_pattern_chunked(sample, name, pattern, self.stream_chunk_len())
+    }
+
+    fn count_pattern_chunked(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        pattern: &[u8],
+        chunk_len: usize,
+    ) -> Result<u64, AgcError> {
+        if pattern.is_empty() {
+            return Err(AgcError::InvalidArgument(
+                "pattern must not be empty".to_string(),
+            ));
+        }
+
+        // With an overlap of one base less than the pattern, every
+        // occurrence lies wholly within exactly one chunk
+        let chunk_len = chunk_len.max(pattern.len());
+        let mut count = 0;
+        for chunk in self.seq_chunks_overlapping(sample, name, chunk_len, pattern.len() - 1)? {
+            count += count_occurrences(&chunk?, pattern);
+        }
+        Ok(count)
+    }
+
+    /// Find the longest homopolymer run in a contig
+    ///
+    /// Runs are of one A/C/G/T base in either case, so soft-masking does
+    /// not break a run, while N and other codes end it. Of equally long
+    /// runs the first is reported. The contig is streamed in one pass,
+    /// with the current run carried across chunk boundaries.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the uppercase base, start and length of the run,
+    /// or `(b'N', 0, 0)` for a contig without A/C/G/T bases
+    pub fn max_homopolymer(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+    ) -> Result<(u8, i32, i32), AgcError> {
+        self.max_homopolymer_chunked(sample, name, self.stream_chunk_len())
+    }
+
+    fn max_homopolymer_chunked(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        chunk_len: usize,
+    ) -> Result<(u8, i32, i32), AgcError> {
+        let mut run = HomopolymerRun::default();
+        for chunk in self.seq_chunks(sample, name, chunk_len)? {
+            run.add(&chunk?);
+        }
+        Ok(run.longest.unwrap_or((b'N', 0, 0)))
+    }
+
+    /// Get the spliced sequence of a transcript
+    ///
+    /// Exons are given in contig order, as in GFF/GTF. For `Strand::Minus`
+    /// the joined exons are reverse-complemented, which also reverses their
+    /// order, so the result always reads 5' to 3' along the transcript.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `exons` - 0-based, half-open `(start, end)` exon intervals
+    /// * `strand` - strand of the transcript
+    ///
+    /// # Returns
+    /// Result containing the transcript bases, or `AgcError::InvalidRange`
+    /// if an exon is empty or lies outside the contig
+    pub fn spliced_seq(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        exons: &[(i32, i32)],
+        strand: Strand,
+    ) -> Result<Vec<u8>, AgcError> {
+        self.spliced_seq_with(sample, name, exons, strand, &DNA_COMPLEMENT)
+    }
+
+    /// Get the spliced sequence of a transcript with a custom complement
+    ///
+    /// Like [`AgcFile::spliced_seq`], but a minus-strand transcript is
+    /// complemented with `table`, e.g. for an archive of RNA.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `exons` - 0-based, half-open `(start, end)` exon intervals
+    /// * `strand` - strand of the transcript
+    /// * `table` - complement of every byte
+    pub fn spliced_seq_with(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        exons: &[(i32, i32)],
+        strand: Strand,
+        table: &[u8; 256],
+    ) -> Result<Vec<u8>, AgcError> {
+        let len = self.ctg_len(sample, name)?;
+        for &(start, end) in exons {
+            check_range(name, start as i64, end as i64, len)?;
+        }
+
+        let parts = exons
+            .iter()
+            .map(|&(start, end)| self.ctg_seq_bytes(sample, name, start, end))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(splice(&parts, strand, table))
+    }
+
+    /// Get a contig region with some intervals masked
+    ///
+    /// Bases inside any of `mask_intervals` are replaced with `mask_char`,
+    /// e.g. `b'N'` to hide repeats or low-quality regions. Intervals are in
+    /// contig coordinates, may overlap and are clipped to `[start, end)`.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `mask_intervals` - 0-based, half-open `(start, end)` intervals to mask
+    /// * `mask_char` - byte to write over masked bases
+    ///
+    /// # Returns
+    /// Result containing the masked bases, or `AgcError::InvalidRange` if a
+    /// mask interval is reversed
+    pub fn get_ctg_seq_masked(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        mask_intervals: &[(i32, i32)],
+        mask_char: u8,
+    ) -> Result<Vec<u8>, AgcError> {
+        if let Some(&(mask_start, mask_end)) = mask_intervals.iter().find(|(s, e)| e < s) {
+            return Err(AgcError::InvalidRange {
+                name: name.to_string(),
+                start: mask_start as i64,
+                end: mask_end as i64,
+                len: self.ctg_len(sample, name)?,
+            });
+        }
+
+        let mut seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        apply_mask(&mut seq, start, mask_intervals, mask_char);
+        Ok(seq)
+    }
+
+    /// Get a contig region in the RNA alphabet
+    ///
+    /// `T` becomes `U` and `t` becomes `u`; all other bases, including soft
+    /// masking, are kept as stored.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    pub fn get_ctg_seq_rna(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<Vec<u8>, AgcError> {
+        let mut seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        transcribe(&mut seq);
+        Ok(seq)
+    }
+
+    /// Get a contig region as uppercase A/C/G/T only
+    ///
+    /// Soft-masked bases are uppercased, and every other byte, such as `N`
+    /// or an IUPAC code, is dropped or replaced per `mode`, as exact-match
+    /// indexers often require. With `NonAcgt::Drop` the result may be
+    /// shorter than the region and its offsets no longer map to contig
+    /// coordinates; `NonAcgt::Replace` keeps them.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `mode` - what to do with non-ACGT bases
+    pub fn get_ctg_seq_clean(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        mode: NonAcgt,
+    ) -> Result<Vec<u8>, AgcError> {
+        let mut seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        clean_acgt(&mut seq, mode);
+        Ok(seq)
+    }
+
+    /// Get a contig region together with its reverse complement
+    ///
+    /// The region is fetched and decompressed once; the reverse strand is
+    /// computed from that buffer. Case is kept and IUPAC codes are
+    /// complemented.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    pub fn get_region_both_strands(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<BothStrands, AgcError> {
+        self.get_region_both_strands_with(sample, name, start, end, &DNA_COMPLEMENT)
+    }
+
+    /// Get a contig region and its reverse complement by a custom table
+    ///
+    /// Like [`AgcFile::get_region_both_strands`], with the reverse strand
+    /// complemented by `table` instead of [`DNA_COMPLEMENT`].
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    /// * `table` - complement of every byte
+    pub fn get_region_both_strands_with(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+        table: &[u8; 256],
+    ) -> Result<BothStrands, AgcError> {
+        let forward = self.ctg_seq_bytes(sample, name, start, end)?;
+        let mut reverse = forward.clone();
+        reverse_complement_with(&mut reverse, table);
+        Ok(BothStrands { forward, reverse })
+    }
+
+    /// Find every base of a contig other than A, C, G, T or N
+    ///
+    /// Case is ignored, so soft-masked bases are standard; IUPAC ambiguity
+    /// codes such as `R` and `Y` and any stray characters are reported. The
+    /// contig is streamed in chunks. See `nonstandard_bases_capped` to
+    /// bound the result on contigs with many ambiguity codes.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the 0-based position and byte of each, in order
+    pub fn nonstandard_bases(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+    ) -> Result<Vec<(i32, u8)>, AgcError> {
+        Ok(self.nonstandard_bases_capped(sample, name, usize::MAX)?.0)
+    }
+
+    /// Find bases other than A, C, G, T or N, keeping at most `max`
+    ///
+    /// Streaming stops as soon as one more is found than fits.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `max` - most positions to return
+    ///
+    /// # Returns
+    /// Result containing the first `max` positions and bytes, and whether
+    /// more were left out
+    pub fn nonstandard_bases_capped(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        max: usize,
+    ) -> Result<(Vec<(i32, u8)>, bool), AgcError> {
+        let mut found = Vec::new();
+        let mut offset = 0;
+        for chunk in self.seq_chunks(sample, name, self.stream_chunk_len())? {
+            let chunk = chunk?;
+            if collect_nonstandard(&chunk, offset, &mut found, max) {
+                return Ok((found, true));
+            }
+            offset += chunk.len() as i32;
+        }
+        Ok((found, false))
+    }
+
+    /// Compute the GC fraction of consecutive windows across a contig
+    ///
+    /// Window `i` covers `[i * window, (i + 1) * window)`; the last one is
+    /// cut short at the contig end, so there are `ceil(len / window)`
+    /// values. Each is the fraction of G+C among A/C/G/T bases in either
+    /// case, or NaN for a window with none, such as one inside a gap. The
+    /// contig is streamed in chunks holding a whole number of windows.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `window` - window length, at least 1
+    pub fn gc_profile(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        window: i32,
+    ) -> Result<Vec<f64>, AgcError> {
+        self.window_profile(sample, name, window, BaseCounts::gc_content)
+    }
+
+    /// Compute the GC skew of consecutive windows across a contig
+    ///
+    /// Each value is `(G - C) / (G + C)` over the window, in either case,
+    /// from -1 (all C) to 1 (all G); its sign flips at the replication
+    /// origin and terminus of bacterial genomes. A window without G or C,
+    /// such as one inside a gap, is NaN, as in [`AgcFile::gc_profile`],
+    /// whose window layout this shares.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `window` - window length, at least 1
+    pub fn gc_skew(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        window: i32,
+    ) -> Result<Vec<f64>, AgcError> {
+        self.window_profile(sample, name, window, BaseCounts::gc_skew)
+    }
+
+    /// Apply `stat` to the base counts of consecutive windows, NaN where
+    /// it is undefined
+    fn window_profile<F>(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        window: i32,
+        stat: F,
+    ) -> Result<Vec<f64>, AgcError>
+    where
+        F: Fn(&BaseCounts) -> Option<f64>,
+    {
+        if window < 1 {
+            return Err(AgcError::InvalidArgument(format!(
+                "window {} must be at least 1",
+                window
+            )));
+        }
+
+        let window = window as usize;
+        let chunk_len = window * (self.stream_chunk_len() / window).max(1);
+        let mut profile = Vec::new();
+        for chunk in self.seq_chunks(sample, name, chunk_len)? {
+            profile.extend(chunk?.chunks(window).map(|w| {
+                let mut counts = BaseCounts::default();
+                counts.add(w);
+                stat(&counts).unwrap_or(f64::NAN)
+            }));
+        }
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_count_kmers_dimers() {
+        let counts = count_kmers(b"ACGCGNCG", 2);
+        assert_eq!(counts.get(b"AC".as_slice()), Some(&1));
+        assert_eq!(counts.get(b"CG".as_slice()), Some(&3));
+        assert_eq!(counts.get(b"GC".as_slice()), Some(&1));
+        assert_eq!(counts.len(), 3, "Windows with N should be skipped");
+    }
+
+    #[test]
+    fn test_kmer_entropy() {
+        let homopolymer = count_kmers(&[b'A'; 200], 3);
+        assert_eq!(kmer_entropy(&homopolymer), 0.0);
+        assert_eq!(kmer_entropy(&HashMap::new()), 0.0);
+
+        // All 16 dimers once each, cyclically: exactly 4 bits
+        let de_bruijn = b"AACAGATCCGCTGGTTA";
+        assert!((kmer_entropy(&count_kmers(de_bruijn, 2)) - 4.0).abs() < 1e-9);
+
+        let mut state = 12345u32;
+        let random: Vec<u8> = (0..20000)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                b"ACGT"[(state >> 16) as usize % 4]
+            })
+            .collect();
+        let entropy = kmer_entropy(&count_kmers(&random, 2));
+        assert!(entropy > 3.95 && entropy <= 4.0, "{}", entropy);
+    }
+
+    #[test]
+    fn test_classify_alphabet() {
+        assert_eq!(classify_alphabet(b"ACGTACGTNNacgtacgtRY"), Alphabet::Dna);
+        assert_eq!(classify_alphabet(b"ACGUUGCA"), Alphabet::Rna);
+        assert_eq!(classify_alphabet(b"MKVLAAGIWLLQE*"), Alphabet::Protein);
+        // Only ambiguity codes is not convincingly nucleotide
+        assert_eq!(classify_alphabet(b"RYKMSWRYKM"), Alphabet::Protein);
+        assert_eq!(classify_alphabet(b"ACGT 1234"), Alphabet::Unknown);
+        assert_eq!(classify_alphabet(b""), Alphabet::Unknown);
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            assert_eq!(agc.alphabet(Some(&sample), &contig).unwrap(), Alphabet::Dna);
+        }
+    }
+
+    #[test]
+    fn test_base_counts() {
+        let mut counts = BaseCounts::default();
+        counts.add(b"ACGTNN");
+        counts.add(b"gcat");
+        assert_eq!(counts.length, 10);
+        assert_eq!(
+            (counts.gc, counts.at, counts.n, counts.masked),
+            (4, 4, 2, 4)
+        );
+        assert_eq!(counts.gc_content(), Some(0.5));
+        assert_eq!(counts.masked_fraction(), Some(0.4));
+
+        assert_eq!(BaseCounts::default().gc_content(), None);
+        assert_eq!(BaseCounts::default().masked_fraction(), None);
+    }
+
+    #[test]
+    fn test_gc_profile() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let len = agc.ctg_len(Some(&sample), &contig).unwrap();
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            for window in [1, 7, len, len + 5] {
+                let profile = agc.gc_profile(Some(&sample), &contig, window).unwrap();
+                assert_eq!(profile.len(), (len as usize).div_ceil(window as usize));
+
+                for (value, w) in profile.iter().zip(seq.chunks(window as usize)) {
+                    let mut counts = BaseCounts::default();
+                    counts.add(w);
+                    match counts.gc_content() {
+                        Some(gc) => assert_eq!(*value, gc),
+                        None => assert!(value.is_nan()),
+                    }
+                }
+            }
+        }
+        assert!(matches!(
+            agc.gc_profile(Some(&sample), "chr1", 0),
+            Err(AgcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_gc_skew_counts() {
+        let skew = |seq: &[u8]| {
+            let mut counts = BaseCounts::default();
+            counts.add(seq);
+            counts.gc_skew()
+        };
+        assert_eq!(skew(b"GGGC"), Some(0.5));
+        assert_eq!(skew(b"ggAT"), Some(1.0));
+        assert_eq!(skew(b"CcAC"), Some(-1.0));
+        assert_eq!(skew(b"GCgc"), Some(0.0));
+        assert_eq!(skew(b"ATNN"), None);
+    }
+
+    #[test]
+    fn test_gc_skew() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let len = agc.ctg_len(Some(&sample), &contig).unwrap();
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            for window in [1, 8, len + 1] {
+                let skew = agc.gc_skew(Some(&sample), &contig, window).unwrap();
+                assert_eq!(skew.len(), (len as usize).div_ceil(window as usize));
+
+                for (value, w) in skew.iter().zip(seq.chunks(window as usize)) {
+                    let g = w.iter().filter(|b| b.eq_ignore_ascii_case(&b'G')).count();
+                    let c = w.iter().filter(|b| b.eq_ignore_ascii_case(&b'C')).count();
+                    if g + c == 0 {
+                        assert!(value.is_nan());
+                    } else {
+                        let expected = (g as f64 - c as f64) / (g + c) as f64;
+                        assert!((value - expected).abs() < 1e-12);
+                    }
+                }
+            }
+        }
+        assert!(matches!(
+            agc.gc_skew(Some(&sample), "chr1", 0),
+            Err(AgcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_collect_nonstandard() {
+        let seq = b"ACGTRacgtnNyT";
+        let mut found = Vec::new();
+        assert!(!collect_nonstandard(seq, 100, &mut found, usize::MAX));
+        assert_eq!(found, [(104, b'R'), (111, b'y')]);
+
+        let mut capped = Vec::new();
+        assert!(collect_nonstandard(seq, 0, &mut capped, 1));
+        assert_eq!(capped, [(4, b'R')]);
+
+        let mut exact = Vec::new();
+        assert!(!collect_nonstandard(seq, 0, &mut exact, 2));
+        assert_eq!(exact.len(), 2);
+    }
+
+    #[test]
+    fn test_nonstandard_bases() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            let expected: Vec<(i32, u8)> = seq
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| !b"ACGTNacgtn".contains(b))
+                .map(|(i, &b)| (i as i32, b))
+                .collect();
+            assert_eq!(
+                agc.nonstandard_bases(Some(&sample), &contig).unwrap(),
+                expected
+            );
+
+            let (capped, truncated) = agc
+                .nonstandard_bases_capped(Some(&sample), &contig, 0)
+                .unwrap();
+            assert!(capped.is_empty());
+            assert_eq!(truncated, !expected.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_count_occurrences() {
+        assert_eq!(count_occurrences(b"ATATAT", b"ATA"), 2);
+        assert_eq!(count_occurrences(b"GAATTCGAATTC", b"GAATTC"), 2);
+        assert_eq!(count_occurrences(b"ACG", b"ACGT"), 0);
+    }
+
+    #[test]
+    fn test_count_pattern() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        let full = agc.get_ctg_seq(Some(&sample), &contig, 0, len).unwrap();
+        let motif = &full.as_bytes()[3..6];
+        let expected = count_occurrences(full.as_bytes(), motif);
+        assert!(expected >= 1);
+
+        assert_eq!(
+            agc.count_pattern(Some(&sample), &contig, motif).unwrap(),
+            expected
+        );
+        // Small chunks force occurrences across chunk boundaries
+        for chunk_len in [3, 4, 7] {
+            assert_eq!(
+                agc.count_pattern_chunked(Some(&sample), &contig, motif, chunk_len)
+                    .unwrap(),
+                expected
+            );
+        }
+        assert!(agc.count_pattern(Some(&sample), &contig, b"").is_err());
+    }
+
+    #[test]
+    fn test_homopolymer_run() {
+        let longest = |chunks: &[&[u8]]| {
+            let mut run = HomopolymerRun::default();
+            for chunk in chunks {
+                run.add(chunk);
+            }
+            run.longest
+        };
+
+        let seq: &[u8] = b"ACCGGGtTttNAAAcA";
+        assert_eq!(longest(&[seq]), Some((b'T', 6, 4)));
+        // A run split across chunks is measured whole
+        assert_eq!(longest(&[&seq[..8], &seq[8..]]), Some((b'T', 6, 4)));
+        assert_eq!(longest(&[b"GG", b"G", b"GGA"]), Some((b'G', 0, 5)));
+        // N ends a run and is never one itself
+        assert_eq!(longest(&[b"NNNNNAANA"]), Some((b'A', 5, 2)));
+        assert_eq!(longest(&[b"NNN"]), None);
+        // Ties go to the first run
+        assert_eq!(longest(&[b"AACC"]), Some((b'A', 0, 2)));
+    }
+
+    #[test]
+    fn test_max_homopolymer() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            let (base, start, len) = agc.max_homopolymer(Some(&sample), &contig).unwrap();
+            if len > 0 {
+                let run = &seq[start as usize..(start + len) as usize];
+                assert!(run.iter().all(|b| b.to_ascii_uppercase() == base));
+            }
+            for chunk_len in [1, 3, 7] {
+                assert_eq!(
+                    agc.max_homopolymer_chunked(Some(&sample), &contig, chunk_len)
+                        .unwrap(),
+                    (base, start, len)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        let mut seq = b"ACGTNacgtRY".to_vec();
+        reverse_complement(&mut seq);
+        assert_eq!(seq, b"RYacgtNACGT");
+    }
+
+    #[test]
+    fn test_apply_mask() {
+        let mut seq = b"ACGTACGTAC".to_vec();
+        // seq covers contig positions 100..110
+        apply_mask(
+            &mut seq,
+            100,
+            &[(95, 102), (104, 106), (105, 107), (109, 200), (50, 60)],
+            b'N',
+        );
+        assert_eq!(seq, b"NNGTNNNTAN");
+    }
+
+    #[test]
+    fn test_get_ctg_seq_masked() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        let plain = agc.get_ctg_seq(Some(&sample), &contig, 10, 40).unwrap();
+        let masked = agc
+            .get_ctg_seq_masked(Some(&sample), &contig, 10, 40, &[(0, 12), (20, 25)], b'#')
+            .unwrap();
+        for (i, (&m, p)) in masked.iter().zip(plain.bytes()).enumerate() {
+            let pos = 10 + i as i32;
+            if pos < 12 || (20..25).contains(&pos) {
+                assert_eq!(m, b'#', "Position {} should be masked", pos);
+            } else {
+                assert_eq!(m, p, "Position {} should be unchanged", pos);
+            }
+        }
+
+        assert!(matches!(
+            agc.get_ctg_seq_masked(Some(&sample), &contig, 10, 40, &[(30, 20)], b'N'),
+            Err(AgcError::InvalidRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_transcribe() {
+        let mut seq = b"ACGTtacgNn".to_vec();
+        transcribe(&mut seq);
+        assert_eq!(seq, b"ACGUuacgNn");
+    }
+
+    #[test]
+    fn test_get_ctg_seq_rna() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        let dna = agc.get_ctg_seq(Some(&sample), &contig, 0, 40).unwrap();
+        let rna = agc.get_ctg_seq_rna(Some(&sample), &contig, 0, 40).unwrap();
+        assert_eq!(rna.len(), dna.len());
+        assert!(!rna.contains(&b'T') && !rna.contains(&b't'));
+        assert_eq!(
+            String::from_utf8(rna).unwrap(),
+            dna.replace('T', "U").replace('t', "u")
+        );
+    }
+
+    #[test]
+    fn test_clean_acgt() {
+        let mut dropped = b"acGTNnRtgA".to_vec();
+        clean_acgt(&mut dropped, NonAcgt::Drop);
+        assert_eq!(dropped, b"ACGTTGA");
+
+        let mut replaced = b"acGTNnRtgA".to_vec();
+        clean_acgt(&mut replaced, NonAcgt::Replace(b'A'));
+        assert_eq!(replaced, b"ACGTAAATGA");
+    }
+
+    #[test]
+    fn test_get_ctg_seq_clean() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for (sample, contig, len) in agc.inventory().unwrap() {
+            let seq = agc.ctg_seq_bytes(Some(&sample), &contig, 0, len).unwrap();
+            let replaced = agc
+                .get_ctg_seq_clean(Some(&sample), &contig, 0, len, NonAcgt::Replace(b'A'))
+                .unwrap();
+            assert_eq!(replaced.len(), seq.len());
+            let dropped = agc
+                .get_ctg_seq_clean(Some(&sample), &contig, 0, len, NonAcgt::Drop)
+                .unwrap();
+            let acgt = seq.iter().filter(|b| b"ACGTacgt".contains(b)).count();
+            assert_eq!(dropped.len(), acgt);
+            assert!(dropped.iter().all(|b| b"ACGT".contains(b)));
+        }
+    }
+
+    #[test]
+    fn test_get_region_both_strands() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        let both = agc
+            .get_region_both_strands(Some(&sample), &contig, 0, 40)
+            .unwrap();
+        assert_eq!(
+            both.forward,
+            agc.ctg_seq_bytes(Some(&sample), &contig, 0, 40).unwrap()
+        );
+        assert_eq!(both.reverse.len(), both.forward.len());
+        for (f, r) in both.forward.iter().zip(both.reverse.iter().rev()) {
+            assert_eq!(complement(*f), *r);
+        }
+    }
+
+    #[test]
+    fn test_splice_minus_strand() {
+        let exons = vec![b"ACGT".to_vec(), b"GGA".to_vec()];
+        assert_eq!(splice(&exons, Strand::Plus, &DNA_COMPLEMENT), b"ACGTGGA");
+        // Reverse complement of ACGT|GGA: TCC|ACGT
+        assert_eq!(splice(&exons, Strand::Minus, &DNA_COMPLEMENT), b"TCCACGT");
+    }
+
+    /// DNA table with A and U complementing each other, in either case
+    fn rna_table() -> [u8; 256] {
+        let mut table = DNA_COMPLEMENT;
+        for (a, u) in [(b'A', b'U'), (b'a', b'u')] {
+            table[a as usize] = u;
+            table[u as usize] = a;
+        }
+        table
+    }
+
+    #[test]
+    fn test_reverse_complement_with() {
+        let mut seq = b"AUGGcuN".to_vec();
+        reverse_complement_with(&mut seq, &rna_table());
+        assert_eq!(seq, b"NagCCAU");
+
+        let mut dna = b"ACGTNacgtRY".to_vec();
+        reverse_complement_with(&mut dna, &DNA_COMPLEMENT);
+        assert_eq!(dna, b"RYacgtNACGT");
+
+        assert_eq!(
+            splice(&[b"AUG".to_vec()], Strand::Minus, &rna_table()),
+            b"CAU"
+        );
+    }
+
+    #[test]
+    fn test_check_complement_table() {
+        let dna = b"ACGTRYKMBVDHNacgtn";
+        assert!(check_complement_table(&DNA_COMPLEMENT, dna).is_ok());
+        assert!(check_complement_table(&rna_table(), b"ACGUacgu").is_ok());
+        // DNA maps U to A but A back to T
+        assert!(matches!(
+            check_complement_table(&DNA_COMPLEMENT, b"ACGU"),
+            Err(AgcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_region_both_strands_with() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+
+        let table = rna_table();
+        let both = agc
+            .get_region_both_strands_with(Some(&sample), &contig, 0, 40, &table)
+            .unwrap();
+        for (f, r) in both.forward.iter().zip(both.reverse.iter().rev()) {
+            assert_eq!(table[*f as usize], *r);
+        }
+        let spliced = agc
+            .spliced_seq_with(Some(&sample), &contig, &[(0, 40)], Strand::Minus, &table)
+            .unwrap();
+        assert_eq!(spliced, both.reverse);
+    }
+
+    #[test]
+    fn test_spliced_seq() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        let exons = [(0, 4), (6, 9)];
+
+        let mut expected = agc.get_ctg_seq(Some(&sample), &contig, 0, 4).unwrap();
+        expected += &agc.get_ctg_seq(Some(&sample), &contig, 6, 9).unwrap();
+        let mut expected = expected.into_bytes();
+        reverse_complement(&mut expected);
+
+        let spliced = agc
+            .spliced_seq(Some(&sample), &contig, &exons, Strand::Minus)
+            .expect("Failed to splice exons");
+        assert_eq!(spliced, expected);
+
+        assert!(matches!(
+            agc.spliced_seq(
+                Some(&sample),
+                &contig,
+                &[(0, 4), (len - 2, len + 1)],
+                Strand::Plus
+            ),
+            Err(AgcError::InvalidRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sequence_complexity() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+
+        let entropy = agc
+            .sequence_complexity(Some(&sample), &contig, 0, len, 2)
+            .expect("Failed to compute complexity");
+        let mut seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+        seq.make_ascii_uppercase();
+        assert!((entropy - kmer_entropy(&count_kmers(&seq, 2))).abs() < 1e-9);
+        assert!((0.0..=4.0).contains(&entropy));
+        assert!(agc
+            .sequence_complexity(Some(&sample), &contig, 0, len, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_kmer_counts() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contigs = agc.list_ctg(Some(&sample)).expect("Failed to list contigs");
+        let len = agc.get_ctg_len(Some(&sample), &contigs[0]).unwrap();
+
+        let counts = agc
+            .kmer_counts(Some(&sample), &contigs[0], 0, len, 2)
+            .expect("Failed to count k-mers");
+        let total: u64 = counts.values().sum();
+        assert!(total <= (len - 1) as u64);
+
+        assert!(agc
+            .kmer_counts(Some(&sample), &contigs[0], 0, len, 0)
+            .is_err());
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::VecDeque;
+
+use crate::chunks::SeqChunks;
+use crate::{AgcError, AgcFile};
+
+/// Longest k-mer that fits the 2-bit encoding in a `u64`
+const MAX_SKETCH_K: usize = 32;
+
+/// SplitMix64 finalizer: an invertible mix of all 64 bits
+pub(crate) fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// 2-bit code of a nucleotide, in either case
+fn base_code(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// ntHash seeds of A, C, G and T, indexed by 2-bit code
+const NT_SEEDS: [u64; 4] = [
+    0x3c8bfbb395c60474,
+    0x3193c18562a02b4c,
+    0x20323ed082572324,
+    0x295549f54be24456,
+];
+
+/// Rolls the canonical ntHash of the k-mer ending at each base of a
+/// sequence fed to it one base at a time
+struct NtHasher {
+    k: u32,
+    /// Forward-strand and reverse-complement hashes of the current k-mer
+    fwd: u64,
+    rev: u64,
+    /// 2-bit codes of the current k-mer's bases, oldest first
+    codes: VecDeque<usize>,
+    /// Position of the next base
+    pos: i32,
+}
+
+impl NtHasher {
+    /// `k` must be at least 1 before any base is pushed
+    fn new(k: usize) -> Self {
+        NtHasher {
+            k: k as u32,
+            fwd: 0,
+            rev: 0,
+            codes: VecDeque::new(),
+            pos: 0,
+        }
+    }
+
+    /// Add the next base, returning `(start, hash)` of the k-mer it
+    /// completes, if that k-mer is all A, C, G or T
+    fn push(&mut self, base: u8) -> Option<(i32, u64)> {
+        self.pos += 1;
+        let code = match base_code(base) {
+            Some(code) => code as usize,
+            None => {
+                self.codes.clear();
+                self.fwd = 0;
+                self.rev = 0;
+                return None;
+            }
+        };
+
+        if self.codes.len() == self.k as usize {
+            let out = self.codes.pop_front().unwrap();
+            self.fwd = self.fwd.rotate_left(1) ^ NT_SEEDS[out].rotate_left(self.k) ^ NT_SEEDS[code];
+            self.rev = self.rev.rotate_right(1)
+                ^ NT_SEEDS[3 - out].rotate_right(1)
+                ^ NT_SEEDS[3 - code].rotate_left(self.k - 1);
+        } else {
+            self.fwd = self.fwd.rotate_left(1) ^ NT_SEEDS[code];
+            self.rev ^= NT_SEEDS[3 - code].rotate_left(self.codes.len() as u32);
+        }
+        self.codes.push_back(code);
+
+        (self.codes.len() == self.k as usize)
+            .then(|| (self.pos - self.k as i32, self.fwd.min(self.rev)))
+    }
+}
+
+/// Lazy iterator over the rolling k-mer hashes of a contig
+///
+/// Created by `AgcFile::rolling_hashes`.
+pub struct RollingHashes<'a> {
+    /// Chunks still to hash, None once they run out or fail
+    chunks: Option<SeqChunks<'a>>,
+    chunk: Vec<u8>,
+    /// Next base of `chunk` to hash
+    offset: usize,
+    hasher: NtHasher,
+    /// Error to report before anything else, from setting up the iterator
+    error: Option<AgcError>,
+}
+
+impl Iterator for RollingHashes<'_> {
+    type Item = Result<(i32, u64), AgcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        loop {
+            while let Some(&base) = self.chunk.get(self.offset) {
+                self.offset += 1;
+                if let Some(hit) = self.hasher.push(base) {
+                    return Some(Ok(hit));
+                }
+            }
+
+            match self.chunks.as_mut()?.next() {
+                Some(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.offset = 0;
+                }
+                Some(Err(e)) => {
+                    self.chunks = None;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.chunks = None;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Computes `(w, k)` minimizers of a sequence fed to it in pieces
+///
+/// Keeps the current k-mer on both strands and a monotone queue of the
+/// k-mer hashes in the current window, so each base costs amortized O(1)
+/// and a piece boundary changes nothing.
+struct MinimizerSketcher {
+    k: usize,
+    w: usize,
+    mask: u64,
+    /// Forward and reverse-complement encodings of the current k-mer
+    fwd: u64,
+    rev: u64,
+    /// Bases since the sequence start or the last non-ACGT base
+    run: usize,
+    /// Position of the next base
+    pos: u64,
+    /// `(k-mer start, hash)` candidates of the current window, increasing
+    /// in hash
+    window: VecDeque<(u64, u64)>,
+    /// Start of the k-mer last added to the sketch
+    last: Option<u64>,
+    sketch: Vec<u64>,
+}
+
+impl MinimizerSketcher {
+    /// `k` must be in `1..=32` and `w` at least 1
+    fn new(k: usize, w: usize) -> Self {
+        MinimizerSketcher {
+            k,
+            w,
+            mask: if k == MAX_SKETCH_K {
+                u64::MAX
+            } else {
+                (1 << (2 * k)) - 1
+            },
+            fwd: 0,
+            rev: 0,
+            run: 0,
+            pos: 0,
+            window: VecDeque::new(),
+            last: None,
+            sketch: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, seq: &[u8]) {
+        for &base in seq {
+            self.pos += 1;
+            let code = match base_code(base) {
+                Some(code) => code,
+                None => {
+                    self.run = 0;
+                    self.window.clear();
+                    continue;
+                }
+            };
+            self.fwd = ((self.fwd << 2) | code) & self.mask;
+            self.rev = (self.rev >> 2) | ((3 - code) << (2 * (self.k - 1)));
+            self.run += 1;
+            if self.run < self.k {
+                continue;
+            }
+
+            let start = self.pos - self.k as u64;
+            let hash = mix64(self.fwd.min(self.rev));
+            while self.window.back().is_some_and(|&(_, h)| h > hash) {
+                self.window.pop_back();
+            }
+            self.window.push_back((start, hash));
+            while self
+                .window
+                .front()
+                .is_some_and(|&(s, _)| s + self.w as u64 <= start)
+            {
+                self.window.pop_front();
+            }
+
+            if self.run - self.k + 1 >= self.w {
+                let (min_start, min_hash) = self.window[0];
+                if self.last != Some(min_start) {
+                    self.last = Some(min_start);
+                    self.sketch.push(min_hash);
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Vec<u64> {
+        self.sketch
+    }
+}
+
+impl AgcFile {
+    /// Compute the minimizer sketch of a contig
+    ///
+    /// Each k-mer is encoded 2 bits per base (A=0, C=1, G=2, T=3, either
+    /// case) and its canonical form, the smaller of the forward and
+    /// reverse-complement codes, hashed with the SplitMix64 finalizer. In
+    /// every window of `w` consecutive k-mers the smallest hash, the
+    /// leftmost on a tie, is a minimizer; each minimizer is listed once,
+    /// in order. Windows containing a base other than A, C, G or T, such
+    /// as `N`, are skipped. The contig is streamed in chunks, so the
+    /// sketch does not depend on how it is fetched.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `k` - k-mer length, from 1 to 32
+    /// * `w` - k-mers per window, at least 1
+    ///
+    /// # Returns
+    /// Result containing the minimizer hashes in contig order
+    pub fn minimizer_sketch(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        k: usize,
+        w: usize,
+    ) -> Result<Vec<u64>, AgcError> {
+        if k == 0 || k > MAX_SKETCH_K || w == 0 {
+            return Err(AgcError::InvalidArgument(format!(
+                "k must be between 1 and {} and w at least 1, got k={} w={}",
+                MAX_SKETCH_K, k, w
+            )));
+        }
+
+        let mut sketcher = MinimizerSketcher::new(k, w);
+        for chunk in self.seq_chunks(sample, name, self.stream_chunk_len())? {
+            sketcher.feed(&chunk?);
+        }
+        Ok(sketcher.finish())
+    }
+
+    /// Stream the rolling hash of every k-mer of a contig
+    ///
+    /// The hash is canonical ntHash (Mohamadi et al., 2016): each base
+    /// has a fixed 64-bit seed, the forward hash of a k-mer is the XOR of
+    /// its seeds rotated left by their distance from the k-mer end, the
+    /// reverse hash is the same over the reverse complement, and the
+    /// smaller of the two is yielded, so a k-mer and its reverse
+    /// complement hash alike. Sliding one base costs a few rotations and
+    /// XORs. Bases may be in either case, and k-mers containing a base
+    /// other than A, C, G or T, such as `N`, are skipped. The contig is
+    /// streamed in chunks and read once.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `k` - k-mer length, at least 1 and at most `i32::MAX`
+    ///
+    /// # Returns
+    /// An iterator of `(start, hash)` in contig order; setup errors, such
+    /// as an unknown contig or `k == 0`, are its first item
+    pub fn rolling_hashes(&self, sample: Option<&str>, name: &str, k: usize) -> RollingHashes<'_> {
+        self.rolling_hashes_chunked(sample, name, k, self.stream_chunk_len())
+    }
+
+    /// `rolling_hashes` fetching `chunk_len` bases at a time
+    fn rolling_hashes_chunked(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        k: usize,
+        chunk_len: usize,
+    ) -> RollingHashes<'_> {
+        let chunks = if k == 0 || k > i32::MAX as usize {
+            Err(AgcError::InvalidArgument(format!(
+                "k must be between 1 and {}, got {}",
+                i32::MAX,
+                k
+            )))
+        } else {
+            self.seq_chunks(sample, name, chunk_len)
+        };
+        let (chunks, error) = match chunks {
+            Ok(chunks) => (Some(chunks), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        RollingHashes {
+            chunks,
+            chunk: Vec::new(),
+            offset: 0,
+            hasher: NtHasher::new(k),
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    fn sketch(seq: &[u8], k: usize, w: usize) -> Vec<u64> {
+        let mut sketcher = MinimizerSketcher::new(k, w);
+        sketcher.feed(seq);
+        sketcher.finish()
+    }
+
+    /// Scan every window directly
+    fn naive_sketch(seq: &[u8], k: usize, w: usize) -> Vec<u64> {
+        let canonical = |kmer: &[u8]| {
+            let code = |b: u8| base_code(b).unwrap();
+            let fwd = kmer.iter().fold(0u64, |acc, &b| (acc << 2) | code(b));
+            let rev = kmer
+                .iter()
+                .rev()
+                .fold(0u64, |acc, &b| (acc << 2) | (3 - code(b)));
+            mix64(fwd.min(rev))
+        };
+        let mut sketch = Vec::new();
+        let mut last = None;
+        for (start, window) in seq.windows(k + w - 1).enumerate() {
+            if window.iter().any(|&b| base_code(b).is_none()) {
+                continue;
+            }
+            let hashes: Vec<u64> = window.windows(k).map(canonical).collect();
+            let min = *hashes.iter().min().unwrap();
+            let at = start + hashes.iter().position(|&h| h == min).unwrap();
+            if last != Some(at) {
+                last = Some(at);
+                sketch.push(min);
+            }
+        }
+        sketch
+    }
+
+    /// Canonical ntHash of a k-mer, computed from scratch
+    fn nt_hash(kmer: &[u8]) -> u64 {
+        let k = kmer.len() as u32;
+        let mut fwd = 0;
+        let mut rev = 0;
+        for (i, &b) in kmer.iter().enumerate() {
+            let code = base_code(b).unwrap() as usize;
+            fwd ^= NT_SEEDS[code].rotate_left(k - 1 - i as u32);
+            rev ^= NT_SEEDS[3 - code].rotate_left(i as u32);
+        }
+        fwd.min(rev)
+    }
+
+    /// Every ACGT-only k-mer of `seq` with its recomputed hash
+    fn naive_hashes(seq: &[u8], k: usize) -> Vec<(i32, u64)> {
+        seq.windows(k)
+            .enumerate()
+            .filter(|(_, kmer)| kmer.iter().all(|&b| base_code(b).is_some()))
+            .map(|(start, kmer)| (start as i32, nt_hash(kmer)))
+            .collect()
+    }
+
+    #[test]
+    fn test_nt_hasher() {
+        let seq = b"ACGTTGCAAGGCTTAACGNNACGTACGGTTACCAGTtgcaACGTAGGACTNA";
+        for k in [1, 2, 5, 17, 64, 70] {
+            let mut hasher = NtHasher::new(k);
+            let hashes: Vec<(i32, u64)> = seq.iter().filter_map(|&b| hasher.push(b)).collect();
+            assert_eq!(hashes, naive_hashes(seq, k), "k={}", k);
+        }
+
+        // A k-mer and its reverse complement hash the same, in either case
+        assert_eq!(nt_hash(b"AACGTG"), nt_hash(b"CACGTT"));
+        assert_eq!(nt_hash(b"AACGTG"), nt_hash(b"aacgtg"));
+        assert_ne!(nt_hash(b"AACGTG"), nt_hash(b"AACGTC"));
+    }
+
+    #[test]
+    fn test_rolling_hashes() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+
+        for k in [1, 4, 11] {
+            let expected = naive_hashes(&seq, k);
+            let hashes: Vec<(i32, u64)> = agc
+                .rolling_hashes(Some(&sample), &contig, k)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(hashes, expected, "k={}", k);
+            // Chunk boundaries change nothing
+            for chunk_len in [1, 3, 7] {
+                let chunked: Vec<(i32, u64)> = agc
+                    .rolling_hashes_chunked(Some(&sample), &contig, k, chunk_len)
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+                assert_eq!(chunked, expected, "k={} chunk_len={}", k, chunk_len);
+            }
+        }
+
+        let mut bad = agc.rolling_hashes(Some(&sample), &contig, 0);
+        assert!(matches!(
+            bad.next(),
+            Some(Err(AgcError::InvalidArgument(_)))
+        ));
+        assert!(bad.next().is_none());
+        let mut missing = agc.rolling_hashes(Some(&sample), "no_such_contig", 5);
+        assert!(matches!(missing.next(), Some(Err(_))));
+        assert!(missing.next().is_none());
+    }
+
+    #[test]
+    fn test_minimizer_sketcher() {
+        let seq = b"ACGTTGCAAGGCTTAACGNNACGTACGGTTACCAGTtgcaACGTAGGACT";
+        for (k, w) in [(1, 1), (3, 2), (5, 4), (7, 10)] {
+            assert_eq!(
+                sketch(seq, k, w),
+                naive_sketch(seq, k, w),
+                "k={} w={}",
+                k,
+                w
+            );
+
+            let mut pieces = MinimizerSketcher::new(k, w);
+            for piece in seq.chunks(3) {
+                pieces.feed(piece);
+            }
+            assert_eq!(pieces.finish(), sketch(seq, k, w));
+        }
+
+        assert_eq!(sketch(b"ACGTACGTAC", 4, 2), sketch(b"acgtacgtac", 4, 2));
+        assert!(sketch(b"NNNNNNNN", 3, 2).is_empty());
+        // A k-mer and its reverse complement hash the same
+        assert_eq!(sketch(b"AACGT", 5, 1), sketch(b"ACGTT", 5, 1));
+        assert_eq!(sketch(&[b'A'; 40], 32, 1).len(), 9);
+    }
+
+    #[test]
+    fn test_minimizer_sketch() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let seq = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+
+        let first = agc.minimizer_sketch(Some(&sample), &contig, 5, 3).unwrap();
+        let second = AgcFile::open(TEST_FILE, true)
+            .unwrap()
+            .minimizer_sketch(Some(&sample), &contig, 5, 3)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first, sketch(&seq, 5, 3));
+
+        for (k, w) in [(0, 3), (33, 3), (5, 0)] {
+            assert!(agc.minimizer_sketch(Some(&sample), &contig, k, w).is_err());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Warnings for FFI calls slower than `AgcFileBuilder::slow_query_threshold`
+
+use std::time::Duration;
+
+/// Log target of slow-query warnings
+pub(crate) const TARGET: &str = "libagc_sys::slow_query";
+
+/// Describe an FFI call for a slow-query warning
+pub(crate) fn describe(
+    call: &str,
+    sample: Option<&str>,
+    name: &str,
+    range: Option<(i32, i32)>,
+) -> String {
+    let mut query = format!("{} sample={} contig={}", call, sample.unwrap_or("-"), name);
+    if let Some((start, end)) = range {
+        query.push_str(&format!(" range={}-{}", start, end));
+    }
+    query
+}
+
+/// Warn if a call took at least `threshold`
+///
+/// `query` is only called, and the description only built, when warning.
+///
+/// # Returns
+/// Whether a warning was emitted
+pub(crate) fn check(
+    threshold: Option<Duration>,
+    elapsed: Duration,
+    query: impl FnOnce() -> String,
+) -> bool {
+    match threshold {
+        Some(threshold) if elapsed >= threshold => {
+            log::warn!(
+                target: TARGET,
+                "slow query: {} took {:?} (threshold {:?})",
+                query(),
+                elapsed,
+                threshold
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AgcFile;
+    use std::sync::{Mutex, Once};
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Keeps the slow-query warnings logged by the tests
+    struct Capture(Mutex<Vec<String>>);
+
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == TARGET
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    /// Install the capturing logger once and return the messages that
+    /// mention `needle`
+    fn captured(needle: &str) -> Vec<String> {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&CAPTURE).unwrap();
+            log::set_max_level(log::LevelFilter::Warn);
+        });
+        CAPTURE
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|m| m.contains(needle))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_check_threshold() {
+        captured("");
+        let threshold = Some(Duration::from_millis(10));
+        let query = || describe("get_ctg_seq", Some("slow_sample"), "chr9", Some((5, 50)));
+
+        assert!(!check(threshold, Duration::from_millis(2), query));
+        assert!(!check(None, Duration::from_secs(60), query));
+        assert!(check(threshold, Duration::from_millis(250), query));
+
+        let warnings = captured("slow_sample");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("get_ctg_seq sample=slow_sample contig=chr9 range=5-50"));
+        assert!(warnings[0].contains("250ms"));
+    }
+
+    #[test]
+    fn test_slow_query_threshold() {
+        captured("");
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .slow_query_threshold(Duration::ZERO)
+            .open()
+            .expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        agc.get_ctg_seq(Some(&sample), &contig, 0, 5).unwrap();
+
+        let expected_len = describe("get_ctg_len", Some(&sample), &contig, None);
+        let expected_seq = describe("get_ctg_seq", Some(&sample), &contig, Some((0, 5)));
+        let warnings = captured(&format!("contig={}", contig));
+        assert!(warnings.iter().any(|m| m.contains(&expected_len)));
+        assert!(warnings.iter().any(|m| m.contains(&expected_seq)));
+    }
+}
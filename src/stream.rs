@@ -0,0 +1,228 @@
+use std::future::Future;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use futures::stream::{self, Stream};
+
+use crate::{AgcError, AgcFile};
+
+/// What the stream does when next polled
+enum Next {
+    /// Fetch the chunk starting here
+    Fetch(i32),
+    /// Report a setup error, then end
+    Fail(AgcError),
+    Done,
+}
+
+/// Fetch the chunk of `[start, start + chunk_len)` clipped to the contig,
+/// with the end of that chunk, or None past the contig end
+fn fetch_chunk(
+    agc: &AgcFile,
+    sample: Option<&str>,
+    name: &str,
+    start: i32,
+    chunk_len: i32,
+) -> Result<Option<(Vec<u8>, i32)>, AgcError> {
+    let len = agc.ctg_len(sample, name)?;
+    if start >= len {
+        return Ok(None);
+    }
+    let end = start.saturating_add(chunk_len).min(len);
+    let seq = agc.ctg_seq_bytes(sample, name, start, end)?;
+    Ok(Some((seq, end)))
+}
+
+impl AgcFile {
+    /// Stream a contig as consecutive chunks
+    ///
+    /// *Requires the `tokio` feature.* Each chunk is fetched with
+    /// `tokio::task::spawn_blocking`, so decompression does not stall the
+    /// async runtime, and only once the stream is polled for it, so a slow
+    /// consumer holds back the fetching. Chunks are laid out as by
+    /// [`AgcFile::seq_chunks`]. Blocking tasks must own what they use,
+    /// hence the `Arc` receiver.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `chunk_len` - bases per chunk, at least 1
+    ///
+    /// # Returns
+    /// A stream of chunks; setup errors, such as an unknown contig or a
+    /// zero chunk length, are its only item
+    pub fn seq_stream(
+        self: &Arc<Self>,
+        sample: Option<&str>,
+        name: &str,
+        chunk_len: usize,
+    ) -> impl Stream<Item = Result<Vec<u8>, AgcError>> + Send + 'static {
+        let first = if chunk_len == 0 {
+            Next::Fail(AgcError::InvalidArgument(
+                "chunk length must be at least 1".to_string(),
+            ))
+        } else {
+            Next::Fetch(0)
+        };
+        let query = Arc::new((
+            Arc::clone(self),
+            sample.map(str::to_string),
+            name.to_string(),
+            chunk_len.min(i32::MAX as usize) as i32,
+        ));
+
+        stream::unfold(first, move |next| {
+            let query = Arc::clone(&query);
+            async move {
+                let start = match next {
+                    Next::Fetch(start) => start,
+                    Next::Fail(e) => return Some((Err(e), Next::Done)),
+                    Next::Done => return None,
+                };
+                let fetched = tokio::task::spawn_blocking(move || {
+                    let (agc, sample, name, chunk_len) = &*query;
+                    fetch_chunk(agc, sample.as_deref(), name, start, *chunk_len)
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    Err(AgcError::Ffi(format!("Blocking fetch task failed: {}", e)))
+                });
+
+                match fetched {
+                    Ok(Some((seq, end))) => Some((Ok(seq), Next::Fetch(end))),
+                    Ok(None) => None,
+                    Err(e) => Some((Err(e), Next::Done)),
+                }
+            }
+        })
+    }
+
+    /// Fill the length cache with every contig of every sample
+    ///
+    /// *Requires the `tokio` feature.* Runs one blocking task per sample,
+    /// so a server can start up, await this in the background and then
+    /// answer length queries without calling into AGC. Only lengths asked
+    /// for with an explicit sample are cached; the sample-less lookups of
+    /// `get_ctg_len(None, ..)` still go to AGC on first use.
+    ///
+    /// # Arguments
+    /// * `progress` - if given, receives `(samples_done, samples_total)`
+    ///   after each sample; a dropped receiver is ignored
+    ///
+    /// # Returns
+    /// A future resolving once every length is cached, or to the first error
+    pub fn prime_length_cache(
+        self: &Arc<Self>,
+        progress: Option<Sender<(usize, usize)>>,
+    ) -> impl Future<Output = Result<(), AgcError>> + Send + 'static {
+        let agc = Arc::clone(self);
+        async move {
+            let samples = agc.list_sample()?;
+            let total = samples.len();
+            for (done, sample) in samples.into_iter().enumerate() {
+                let agc = Arc::clone(&agc);
+                tokio::task::spawn_blocking(move || {
+                    for contig in agc.list_ctg(Some(&sample))? {
+                        agc.ctg_len(Some(&sample), &contig)?;
+                    }
+                    Ok::<_, AgcError>(())
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    Err(AgcError::Ffi(format!("Blocking length task failed: {}", e)))
+                })?;
+                if let Some(progress) = &progress {
+                    let _ = progress.send((done + 1, total));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Drain a stream on a fresh runtime
+    fn collect<S>(stream: S) -> Vec<Result<Vec<u8>, AgcError>>
+    where
+        S: Stream<Item = Result<Vec<u8>, AgcError>>,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut stream = std::pin::pin!(stream);
+            let mut items = Vec::new();
+            while let Some(item) = stream.next().await {
+                items.push(item);
+            }
+            items
+        })
+    }
+
+    #[test]
+    fn test_seq_stream() {
+        let agc = Arc::new(AgcFile::open(TEST_FILE, true).expect("Failed to open file"));
+        let sample = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let full = agc.ctg_full_bytes(Some(&sample), &contig).unwrap();
+            for chunk_len in [1, 7, full.len() + 1] {
+                let chunks: Vec<Vec<u8>> =
+                    collect(agc.seq_stream(Some(&sample), &contig, chunk_len))
+                        .into_iter()
+                        .collect::<Result<_, _>>()
+                        .unwrap();
+                assert_eq!(chunks.len(), full.len().div_ceil(chunk_len));
+                assert_eq!(chunks.concat(), full);
+            }
+        }
+    }
+
+    #[test]
+    fn test_prime_length_cache() {
+        let agc = Arc::new(AgcFile::open(TEST_FILE, true).expect("Failed to open file"));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(agc.prime_length_cache(Some(sender)))
+            .expect("Failed to prime length cache");
+
+        let samples = agc.list_sample().unwrap();
+        let progress: Vec<(usize, usize)> = receiver.iter().collect();
+        assert_eq!(progress.len(), samples.len());
+        assert_eq!(progress.last(), Some(&(samples.len(), samples.len())));
+
+        let cached = agc.len_cache.lock().unwrap().len();
+        let mut n_contigs = 0;
+        for sample in &samples {
+            for contig in agc.list_ctg(Some(sample)).unwrap() {
+                assert!(agc.get_ctg_len(Some(sample), &contig).unwrap() >= 0);
+                n_contigs += 1;
+            }
+        }
+        assert_eq!(cached, n_contigs);
+        // Every lookup was a hit, so nothing new was cached
+        assert_eq!(agc.len_cache.lock().unwrap().len(), cached);
+    }
+
+    #[test]
+    fn test_seq_stream_errors() {
+        let agc = Arc::new(AgcFile::open(TEST_FILE, true).expect("Failed to open file"));
+        let sample = agc.reference_sample().unwrap();
+
+        let items = collect(agc.seq_stream(Some(&sample), "no_such_contig", 10));
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], Err(AgcError::ContigNotFound { .. })));
+
+        let items = collect(agc.seq_stream(Some(&sample), "no_such_contig", 0));
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], Err(AgcError::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,853 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Write;
+
+use crate::md5::Md5;
+use crate::policy::Collector;
+use crate::seq::BaseCounts;
+use crate::{AgcError, AgcFile, Collected, ContigInfo, ErrorPolicy, PrefetchState};
+
+/// Column names of `AgcFile::write_contig_report`
+pub const CONTIG_REPORT_HEADER: &str = "contig\tlength\tgc_content\tn_count\tmasked_fraction";
+
+/// Storage figures of one sample, from `AgcFile::sample_storage_estimate`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleStorage {
+    /// Total bases of the sample's contigs, uncompressed
+    pub uncompressed_bases: u64,
+    /// Bytes of the archive attributable to the sample, if known
+    pub compressed_bytes: Option<u64>,
+    /// `uncompressed_bases / compressed_bytes`, if known
+    pub ratio: Option<f64>,
+}
+
+/// Assembly contiguity of one sample, from `AgcFile::contiguity_metrics`
+///
+/// Nx is the length of the contig at which the contigs, longest first,
+/// reach x% of the total length, and Lx is how many contigs that takes.
+/// auN is the area under the Nx curve, `sum(len^2) / total`, which unlike
+/// N50 accounts for every contig.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContiguityMetrics {
+    /// Number of contigs
+    pub n_contigs: usize,
+    /// Total length of all contigs
+    pub total_length: u64,
+    /// Length of the longest contig
+    pub largest: i32,
+    /// N50 contig length
+    pub n50: i32,
+    /// Number of contigs reaching half the total length
+    pub l50: usize,
+    /// N90 contig length
+    pub n90: i32,
+    /// Number of contigs reaching 90% of the total length
+    pub l90: usize,
+    /// Area under the Nx curve
+    pub aun: f64,
+}
+
+impl fmt::Display for ContiguityMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "contigs={} total={} largest={} N50={} L50={} N90={} L90={} auN={:.1}",
+            self.n_contigs,
+            self.total_length,
+            self.largest,
+            self.n50,
+            self.l50,
+            self.n90,
+            self.l90,
+            self.aun
+        )
+    }
+}
+
+/// Count `lengths` into the buckets delimited by strictly increasing,
+/// non-negative `edges`: `[0, e0)`, `[e0, e1)`, ..., `[ek, i32::MAX]`
+fn length_buckets(lengths: &[i32], edges: &[i32]) -> Vec<(i32, i32, u64)> {
+    let mut buckets: Vec<(i32, i32, u64)> = std::iter::once(0)
+        .chain(edges.iter().copied())
+        .zip(edges.iter().copied().chain(std::iter::once(i32::MAX)))
+        .map(|(lo, hi)| (lo, hi, 0))
+        .collect();
+    for &len in lengths {
+        buckets[edges.partition_point(|&edge| edge <= len)].2 += 1;
+    }
+    buckets
+}
+
+/// Contiguity of a non-empty set of non-negative contig lengths
+fn contiguity(mut lengths: Vec<i32>) -> ContiguityMetrics {
+    lengths.sort_unstable_by(|a, b| b.cmp(a));
+    let total: u64 = lengths.iter().map(|&len| len as u64).sum();
+    let mut metrics = ContiguityMetrics {
+        n_contigs: lengths.len(),
+        total_length: total,
+        largest: lengths[0],
+        n50: 0,
+        l50: 0,
+        n90: 0,
+        l90: 0,
+        aun: 0.0,
+    };
+
+    let mut cumulative = 0u64;
+    let mut squares = 0f64;
+    for (i, &len) in lengths.iter().enumerate() {
+        cumulative += len as u64;
+        squares += len as f64 * len as f64;
+        if metrics.l50 == 0 && cumulative * 2 >= total {
+            (metrics.n50, metrics.l50) = (len, i + 1);
+        }
+        if metrics.l90 == 0 && cumulative * 10 >= total * 9 {
+            (metrics.n90, metrics.l90) = (len, i + 1);
+        }
+    }
+    if total > 0 {
+        metrics.aun = squares / total as f64;
+    }
+    metrics
+}
+
+/// Format a fraction to four decimals, or `NA` when undefined
+fn fraction(value: Option<f64>) -> String {
+    value.map_or_else(|| "NA".to_string(), |v| format!("{:.4}", v))
+}
+
+/// MD5 over a reference name and an inventory, in sorted order
+///
+/// The reference and each `sample\tcontig\tlength` entry are hashed as
+/// one line each, so the digest does not depend on listing order.
+fn inventory_digest(reference: &str, mut inventory: Vec<(String, String, i32)>) -> String {
+    inventory.sort();
+    let mut md5 = Md5::new();
+    md5.update(reference.as_bytes());
+    md5.update(b"\n");
+    for (sample, contig, len) in &inventory {
+        md5.update(format!("{}\t{}\t{}\n", sample, contig, len).as_bytes());
+    }
+    md5.hex_digest()
+}
+
+/// Sum contig lengths as `u64`, rejecting any that wrapped negative
+fn sum_lengths<I>(contigs: I) -> Result<u64, AgcError>
+where
+    I: IntoIterator<Item = Result<ContigInfo, AgcError>>,
+{
+    let mut total = 0u64;
+    for info in contigs {
+        let info = info?;
+        if info.length < 0 {
+            return Err(AgcError::CoordinateOverflow {
+                name: info.name,
+                value: info.length as i64,
+            });
+        }
+        total += info.length as u64;
+    }
+    Ok(total)
+}
+
+/// Lay contigs end to end as `(name, global_start, global_end)`
+///
+/// A length that wrapped negative, or an end past `u64::MAX`, is rejected
+/// rather than producing offsets that run backwards.
+fn lay_out<I>(contigs: I) -> Result<Vec<(String, u64, u64)>, AgcError>
+where
+    I: IntoIterator<Item = Result<ContigInfo, AgcError>>,
+{
+    let mut offset = 0u64;
+    contigs
+        .into_iter()
+        .map(|info| {
+            let info = info?;
+            let end = u64::try_from(info.length)
+                .ok()
+                .and_then(|length| offset.checked_add(length));
+            let Some(end) = end else {
+                return Err(AgcError::CoordinateOverflow {
+                    name: info.name,
+                    value: info.length as i64,
+                });
+            };
+            let start = std::mem::replace(&mut offset, end);
+            Ok((info.name, start, end))
+        })
+        .collect()
+}
+
+/// `sum_lengths` for a single sample, which must have a contig
+///
+/// A total of 0 would read as a sample made only of empty contigs.
+fn sample_total<I>(sample: &str, contigs: I) -> Result<u64, AgcError>
+where
+    I: IntoIterator<Item = Result<ContigInfo, AgcError>>,
+{
+    let mut contigs = contigs.into_iter().peekable();
+    if contigs.peek().is_none() {
+        return Err(AgcError::EmptySample(sample.to_string()));
+    }
+    sum_lengths(contigs)
+}
+
+/// Per-sample summary, one row of an archive overview table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleRow {
+    /// Sample name
+    pub name: String,
+    /// Number of contigs in the sample
+    pub n_contigs: usize,
+    /// Sum of the contig lengths in bases
+    pub total_length: u64,
+    /// Whether this is the archive's reference sample
+    pub is_reference: bool,
+}
+
+impl SampleRow {
+    /// Column names matching `to_tsv_line`
+    pub const TSV_HEADER: &'static str = "name\tn_contigs\ttotal_length\tis_reference";
+
+    /// Format the row as a tab-separated line without a trailing newline
+    pub fn to_tsv_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.name, self.n_contigs, self.total_length, self.is_reference
+        )
+    }
+}
+
+impl AgcFile {
+    /// Summarise every sample in the archive
+    ///
+    /// Samples are visited once, in archive order, listing and sizing the
+    /// contigs of each. A sample without contigs gets a row of zeros.
+    ///
+    /// # Returns
+    /// Result containing one row per sample or an error
+    pub fn sample_rows(&self) -> Result<Vec<SampleRow>, AgcError> {
+        let reference = self.reference_sample()?;
+        let samples = self.list_sample()?;
+        let mut rows = Vec::with_capacity(samples.len());
+
+        for name in samples {
+            let contigs = self.contigs(Some(&name))?;
+            let n_contigs = contigs.len();
+            let total_length = sum_lengths(contigs)?;
+
+            rows.push(SampleRow {
+                is_reference: name == reference,
+                n_contigs,
+                total_length,
+                name,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// Get the number of contigs summed over all samples
+    ///
+    /// # Returns
+    /// Result containing the total, or an error if AGC reports a negative
+    /// count for a sample
+    pub fn total_n_ctg(&self) -> Result<usize, AgcError> {
+        let mut total = 0;
+        for sample in self.list_sample()? {
+            let n = self.n_ctg(&sample)?;
+            if n < 0 {
+                return Err(AgcError::Ffi(format!(
+                    "Failed to count contigs of sample: {}",
+                    sample
+                )));
+            }
+            total += n as usize;
+        }
+        Ok(total)
+    }
+
+    /// List every contig of every sample with its length
+    ///
+    /// Entries are `(sample, contig, length)`, samples in archive order and
+    /// contigs in listing order within each, for writing a catalog.
+    pub fn inventory(&self) -> Result<Vec<(String, String, i32)>, AgcError> {
+        Ok(self.inventory_with(ErrorPolicy::FailFast)?.items)
+    }
+
+    /// List every contig of every sample with its length, handling errors
+    /// per `policy`
+    ///
+    /// Under `ErrorPolicy::Collect` a contig whose length cannot be read,
+    /// or a sample whose contigs cannot be listed, is skipped and its error
+    /// kept.
+    ///
+    /// # Arguments
+    /// * `policy` - whether to stop at or collect per-entry errors
+    pub fn inventory_with(
+        &self,
+        policy: ErrorPolicy,
+    ) -> Result<Collected<(String, String, i32)>, AgcError> {
+        let mut entries = Collector::new(policy, self.total_n_ctg()?);
+        for sample in self.list_sample()? {
+            let contigs = match self.list_ctg(Some(&sample)) {
+                Ok(contigs) => contigs,
+                Err(e) => {
+                    entries.push(Err(e.into()))?;
+                    continue;
+                }
+            };
+            for contig in contigs {
+                let len = self.ctg_len(Some(&sample), &contig);
+                entries.push(len.map(|len| (sample.clone(), contig, len)))?;
+            }
+        }
+        Ok(entries.finish())
+    }
+
+    /// Get the total length of all contigs of a sample
+    ///
+    /// Lengths are summed as `u64`, so a sample of many contigs near the
+    /// `i32` limit still totals correctly.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the total, `AgcError::EmptySample` if the sample
+    /// has no contigs, or `AgcError::CoordinateOverflow` if AGC reports a
+    /// contig length that has wrapped negative
+    pub fn sample_total_length(&self, sample: &str) -> Result<u64, AgcError> {
+        sample_total(sample, self.contigs(Some(sample))?)
+    }
+
+    /// Count a sample's contigs by length, in buckets between bin edges
+    ///
+    /// `bins` are the edges between buckets, so `[1000, 10000]` gives the
+    /// buckets `[0, 1000)`, `[1000, 10000)` and `[10000, i32::MAX]`: the
+    /// first takes every contig shorter than the first edge and the last
+    /// every contig from the last edge up. No edges give one bucket of all
+    /// contigs.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `bins` - strictly increasing, non-negative bin edges
+    ///
+    /// # Returns
+    /// Result containing `(lo, hi, count)` of each bucket, `[lo, hi)` and
+    /// in increasing order, or `AgcError::InvalidArgument` for bad edges
+    pub fn length_histogram(
+        &self,
+        sample: &str,
+        bins: &[i32],
+    ) -> Result<Vec<(i32, i32, u64)>, AgcError> {
+        if bins.first().is_some_and(|&edge| edge < 0) || bins.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(AgcError::InvalidArgument(format!(
+                "bin edges {:?} must be non-negative and strictly increasing",
+                bins
+            )));
+        }
+
+        let lengths = self
+            .contigs(Some(sample))?
+            .map(|info| info.map(|info| info.length))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(length_buckets(&lengths, bins))
+    }
+
+    /// Compute N50, N90, L50, L90, auN and totals over a sample's contigs
+    ///
+    /// The lengths are sorted once and every metric is taken in a single
+    /// pass over them. Lengths are summed as `u64`.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the metrics, `AgcError::EmptySample` if the sample
+    /// has no contigs, or `AgcError::CoordinateOverflow` for a contig
+    /// length that has wrapped negative
+    pub fn contiguity_metrics(&self, sample: &str) -> Result<ContiguityMetrics, AgcError> {
+        let mut lengths = Vec::new();
+        for info in self.contigs(Some(sample))? {
+            let info = info?;
+            if info.length < 0 {
+                return Err(AgcError::CoordinateOverflow {
+                    name: info.name,
+                    value: info.length as i64,
+                });
+            }
+            lengths.push(info.length);
+        }
+        if lengths.is_empty() {
+            return Err(AgcError::EmptySample(sample.to_string()));
+        }
+        Ok(contiguity(lengths))
+    }
+
+    /// Estimate how much of the archive a sample accounts for
+    ///
+    /// AGC deduplicates segments across samples and stores no per-sample
+    /// compressed size, so a sample's compressed footprint cannot be told
+    /// apart from the others'; `compressed_bytes` and `ratio` are always
+    /// None for now and only the uncompressed total is filled in.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing the sample's storage figures, or
+    /// `AgcError::EmptySample` if the sample has no contigs
+    pub fn sample_storage_estimate(&self, sample: &str) -> Result<SampleStorage, AgcError> {
+        Ok(SampleStorage {
+            uncompressed_bases: self.sample_total_length(sample)?,
+            compressed_bytes: None,
+            ratio: None,
+        })
+    }
+
+    /// Fraction of the reference's contigs that a sample also has
+    ///
+    /// A quick completeness check for pangenome members: contigs are
+    /// matched by name only, so a sample naming its contigs differently
+    /// scores low even if its sequence is complete.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing a value in `[0, 1]`, or `AgcError::EmptySample` if
+    /// the reference has no contigs
+    pub fn reference_coverage(&self, sample: &str) -> Result<f64, AgcError> {
+        let reference = self.reference_sample()?;
+        let reference_contigs: HashSet<String> =
+            self.list_ctg(Some(&reference))?.into_iter().collect();
+        if reference_contigs.is_empty() {
+            return Err(AgcError::EmptySample(reference));
+        }
+        let sample_contigs: HashSet<String> = self.list_ctg(Some(sample))?.into_iter().collect();
+        let shared = reference_contigs.intersection(&sample_contigs).count();
+        Ok(shared as f64 / reference_contigs.len() as f64)
+    }
+
+    /// Place a sample's contigs end to end on one coordinate axis
+    ///
+    /// Contigs are laid out in listing order, each starting where the
+    /// previous one ends, as whole-genome plots and indexes do. Offsets are
+    /// `u64`, so genomes longer than `i32::MAX` fit.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    ///
+    /// # Returns
+    /// Result containing each contig with its `[global_start, global_end)`;
+    /// a sample without contigs lays out nothing, so the list is empty.
+    /// A contig length that has wrapped negative gives
+    /// `AgcError::CoordinateOverflow`.
+    pub fn contig_offsets(&self, sample: &str) -> Result<Vec<(String, u64, u64)>, AgcError> {
+        lay_out(self.contigs(Some(sample))?)
+    }
+
+    /// One-line summary of the archive for status bars and logs
+    ///
+    /// Reads like `test.agc: 3 samples, 42 contigs, ref=GRCh38, prefetched`.
+    /// Only counts come from AGC, and the reference name is cached, so no
+    /// contig is listed or sized. A count or name AGC cannot report is
+    /// shown as `?`.
+    pub fn status_line(&self) -> String {
+        let file = self
+            .path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy();
+        let contigs = self
+            .total_n_ctg()
+            .map_or_else(|_| "?".to_string(), |n| n.to_string());
+        let reference = self.reference_sample().unwrap_or_else(|_| "?".to_string());
+        let mode = match self.prefetch {
+            PrefetchState::NotRequested => "streaming",
+            _ => "prefetched",
+        };
+        format!(
+            "{}: {} samples, {} contigs, ref={}, {}",
+            file,
+            self.n_sample(),
+            contigs,
+            reference,
+            mode
+        )
+    }
+
+    /// Get an identifier for the archive's contents, independent of its path
+    ///
+    /// AGC stores no archive UUID, so the id is derived from the contents:
+    /// it is the MD5, in lowercase hex, of the reference sample name
+    /// followed by every `(sample, contig, length)` of `inventory`, sorted.
+    /// Copies and renames of an archive keep its id, and adding a sample
+    /// changes it. Sequences are not hashed, so two archives with the same
+    /// names and lengths but different bases share an id. The id is
+    /// computed on the first call and kept for the handle's lifetime.
+    ///
+    /// # Returns
+    /// Result containing a 32-character hex id
+    pub fn archive_id(&self) -> Result<String, AgcError> {
+        if let Some(id) = self.archive_id.get() {
+            return Ok(id.clone());
+        }
+        let reference = self.reference_sample()?;
+        let id = inventory_digest(&reference, self.inventory()?);
+        Ok(self.archive_id.get_or_init(|| id).clone())
+    }
+
+    /// Write per-contig composition statistics of a sample as TSV
+    ///
+    /// Columns are `CONTIG_REPORT_HEADER`: contig name, length, G+C fraction
+    /// of the A/C/G/T bases, number of Ns, and the fraction of lowercase
+    /// (soft-masked) bases. Fractions have four decimals and are `NA` when
+    /// undefined, e.g. for a contig of only Ns. Each contig is streamed in
+    /// chunks in a single pass, so memory use does not grow with its length.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `out` - output to write to
+    pub fn write_contig_report<W: Write>(&self, sample: &str, out: &mut W) -> Result<(), AgcError> {
+        writeln!(out, "{}", CONTIG_REPORT_HEADER)?;
+
+        for contig in self.list_ctg(Some(sample))? {
+            let mut counts = BaseCounts::default();
+            for chunk in self.seq_chunks(Some(sample), &contig, self.stream_chunk_len())? {
+                counts.add(&chunk?);
+            }
+
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                contig,
+                counts.length,
+                fraction(counts.gc_content()),
+                counts.n,
+                fraction(counts.masked_fraction())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_inventory_digest() {
+        let entry = |s: &str, c: &str, len| (s.to_string(), c.to_string(), len);
+        let inventory = vec![entry("ref", "chr1", 100), entry("a", "chr1", 90)];
+        let digest = inventory_digest("ref", inventory.clone());
+        assert_eq!(digest.len(), 32);
+
+        let reordered = inventory.iter().rev().cloned().collect();
+        assert_eq!(inventory_digest("ref", reordered), digest);
+        assert_ne!(inventory_digest("a", inventory.clone()), digest);
+        let mut longer = inventory;
+        longer[0].2 += 1;
+        assert_ne!(inventory_digest("ref", longer), digest);
+    }
+
+    #[test]
+    fn test_contig_offsets() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let offsets = agc.contig_offsets(&sample).expect("Failed to get offsets");
+
+        assert_eq!(offsets.len(), agc.n_ctg(&sample).unwrap() as usize);
+        assert_eq!(offsets[0].1, 0);
+        assert!(offsets.windows(2).all(|w| w[0].2 == w[1].1));
+        for (name, start, end) in &offsets {
+            let len = agc.get_ctg_len(Some(&sample), name).unwrap() as u64;
+            assert_eq!(end - start, len);
+        }
+        assert_eq!(
+            offsets.last().unwrap().2,
+            agc.sample_total_length(&sample).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_status_line() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let line = agc.status_line();
+        assert!(line.starts_with("test.agc: "), "{}", line);
+        assert!(line.contains(&format!("{} samples", agc.n_sample())));
+        assert!(line.contains(&format!("{} contigs", agc.total_n_ctg().unwrap())));
+        assert!(line.contains(&format!("ref={}", agc.reference_sample().unwrap())));
+        assert!(line.ends_with(", prefetched"));
+
+        let streaming = AgcFile::open(TEST_FILE, false).expect("Failed to open file");
+        assert!(streaming.status_line().ends_with(", streaming"));
+    }
+
+    #[test]
+    fn test_archive_id() {
+        let first = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let id = first.archive_id().expect("Failed to get archive id");
+        assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let copy = dir.path().join("copy.agc");
+        std::fs::copy(TEST_FILE, &copy).unwrap();
+        let second = AgcFile::open(copy.to_str().unwrap(), false).expect("Failed to open copy");
+        assert_eq!(second.archive_id().unwrap(), id);
+    }
+
+    #[test]
+    fn test_to_tsv_line() {
+        let row = SampleRow {
+            name: "ref".to_string(),
+            n_contigs: 3,
+            total_length: 1200,
+            is_reference: true,
+        };
+        assert_eq!(row.to_tsv_line(), "ref\t3\t1200\ttrue");
+        assert_eq!(
+            SampleRow::TSV_HEADER.split('\t').count(),
+            row.to_tsv_line().split('\t').count()
+        );
+    }
+
+    #[test]
+    fn test_write_contig_report() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let mut out = Vec::new();
+        agc.write_contig_report(&sample, &mut out)
+            .expect("Failed to write report");
+
+        let text = String::from_utf8(out).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some(CONTIG_REPORT_HEADER));
+
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), agc.n_ctg(&sample).unwrap() as usize);
+        for row in rows {
+            assert_eq!(row.len(), 5);
+            let length: i32 = row[1].parse().unwrap();
+            assert_eq!(length, agc.get_ctg_len(Some(&sample), row[0]).unwrap());
+            assert!(row[3].parse::<i32>().unwrap() <= length);
+            for value in [row[2], row[4]] {
+                if value != "NA" {
+                    let value: f64 = value.parse().unwrap();
+                    assert!((0.0..=1.0).contains(&value));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sum_lengths_near_overflow() {
+        let info = |name: &str, length| {
+            Ok(ContigInfo {
+                name: name.to_string(),
+                length,
+            })
+        };
+        let total = sum_lengths([info("a", i32::MAX), info("b", i32::MAX), info("c", 1)]);
+        assert_eq!(total.unwrap(), 2 * i32::MAX as u64 + 1);
+
+        let wrapped = (i32::MAX as u32 + 10) as i32;
+        assert!(matches!(
+            sum_lengths([info("a", 5), info("big", wrapped)]),
+            Err(AgcError::CoordinateOverflow { name, .. }) if name == "big"
+        ));
+    }
+
+    #[test]
+    fn test_lay_out_rejects_wrapped_length() {
+        let info = |name: &str, length| {
+            Ok(ContigInfo {
+                name: name.to_string(),
+                length,
+            })
+        };
+        let offsets = lay_out([info("a", i32::MAX), info("b", 3)]).unwrap();
+        assert_eq!(
+            offsets,
+            vec![
+                ("a".to_string(), 0, i32::MAX as u64),
+                ("b".to_string(), i32::MAX as u64, i32::MAX as u64 + 3),
+            ]
+        );
+
+        let wrapped = (i32::MAX as u32 + 10) as i32;
+        assert!(matches!(
+            lay_out([info("a", 5), info("big", wrapped)]),
+            Err(AgcError::CoordinateOverflow { name, value }) if name == "big" && value < 0
+        ));
+    }
+
+    #[test]
+    fn test_sample_total_empty_sample() {
+        assert!(matches!(
+            sample_total("empty", Vec::new()),
+            Err(AgcError::EmptySample(sample)) if sample == "empty"
+        ));
+        let contigs = [Ok(ContigInfo {
+            name: "chr1".to_string(),
+            length: 0,
+        })];
+        assert_eq!(sample_total("s", contigs).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_inventory() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let inventory = agc.inventory().expect("Failed to list inventory");
+        assert_eq!(inventory.len(), agc.total_n_ctg().unwrap());
+        let collected = agc.inventory_with(ErrorPolicy::Collect).unwrap();
+        assert!(collected.is_complete());
+        assert_eq!(collected.items, inventory);
+
+        for (sample, contig, len) in inventory.iter().step_by(2) {
+            assert_eq!(*len, agc.get_ctg_len(Some(sample), contig).unwrap());
+            assert!(agc.list_ctg(Some(sample)).unwrap().contains(contig));
+        }
+    }
+
+    #[test]
+    fn test_sample_total_length() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            let mut expected = 0u64;
+            for contig in agc.list_ctg(Some(&sample)).unwrap() {
+                expected += agc.get_ctg_len(Some(&sample), &contig).unwrap() as u64;
+            }
+            assert_eq!(agc.sample_total_length(&sample).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_reference_coverage() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        assert_eq!(agc.reference_coverage(&reference).unwrap(), 1.0);
+        for sample in agc.list_sample().unwrap() {
+            let coverage = agc.reference_coverage(&sample).unwrap();
+            assert!((0.0..=1.0).contains(&coverage));
+        }
+    }
+
+    #[test]
+    fn test_contiguity() {
+        let metrics = contiguity(vec![4, 10, 2, 8, 6]);
+        assert_eq!(
+            (metrics.n_contigs, metrics.total_length, metrics.largest),
+            (5, 30, 10)
+        );
+        // Cumulative lengths, longest first: 10, 18, 24, 28, 30
+        assert_eq!((metrics.n50, metrics.l50), (8, 2));
+        assert_eq!((metrics.n90, metrics.l90), (4, 4));
+        // (100 + 64 + 36 + 16 + 4) / 30
+        assert!((metrics.aun - 220.0 / 30.0).abs() < 1e-12);
+        assert_eq!(
+            metrics.to_string(),
+            "contigs=5 total=30 largest=10 N50=8 L50=2 N90=4 L90=4 auN=7.3"
+        );
+
+        let single = contiguity(vec![7]);
+        assert_eq!(
+            (single.n50, single.l50, single.n90, single.l90),
+            (7, 1, 7, 1)
+        );
+        assert_eq!(single.aun, 7.0);
+    }
+
+    #[test]
+    fn test_length_buckets() {
+        let lengths = [5, 999, 1000, 1500, 9999, 10000, 250_000];
+        assert_eq!(
+            length_buckets(&lengths, &[1000, 10000]),
+            [(0, 1000, 2), (1000, 10000, 3), (10000, i32::MAX, 2)]
+        );
+        assert_eq!(length_buckets(&lengths, &[]), [(0, i32::MAX, 7)]);
+        assert_eq!(length_buckets(&[], &[10]), [(0, 10, 0), (10, i32::MAX, 0)]);
+    }
+
+    #[test]
+    fn test_length_histogram() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let n = agc.n_ctg(&sample).unwrap() as u64;
+
+        let histogram = agc.length_histogram(&sample, &[10, 100, 1000]).unwrap();
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram.iter().map(|b| b.2).sum::<u64>(), n);
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+            assert!(histogram
+                .iter()
+                .any(|&(lo, hi, count)| lo <= len && (len < hi || hi == i32::MAX) && count > 0));
+        }
+
+        for bad in [&[10, 10][..], &[100, 10], &[-1, 10]] {
+            assert!(matches!(
+                agc.length_histogram(&sample, bad),
+                Err(AgcError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_contiguity_metrics() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            let metrics = agc.contiguity_metrics(&sample).unwrap();
+            assert_eq!(metrics.n_contigs, agc.n_ctg(&sample).unwrap() as usize);
+            assert_eq!(
+                metrics.total_length,
+                agc.sample_total_length(&sample).unwrap()
+            );
+            assert!(metrics.n90 <= metrics.n50 && metrics.n50 <= metrics.largest);
+            assert!(metrics.l50 <= metrics.l90 && metrics.l90 <= metrics.n_contigs);
+            assert!(metrics.aun <= metrics.largest as f64);
+        }
+        assert!(agc.contiguity_metrics("no_such_sample").is_err());
+    }
+
+    #[test]
+    fn test_sample_storage_estimate() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for sample in agc.list_sample().unwrap() {
+            let storage = agc.sample_storage_estimate(&sample).unwrap();
+            assert_eq!(
+                storage.uncompressed_bases,
+                agc.sample_total_length(&sample).unwrap()
+            );
+            assert_eq!(storage.compressed_bytes, None);
+            assert_eq!(storage.ratio, None);
+        }
+    }
+
+    #[test]
+    fn test_sample_rows() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let rows = agc.sample_rows().expect("Failed to summarise samples");
+        let reference = agc.reference_sample().unwrap();
+
+        assert_eq!(rows.len(), agc.n_sample() as usize);
+        assert_eq!(rows.iter().filter(|row| row.is_reference).count(), 1);
+
+        for row in &rows {
+            assert_eq!(row.is_reference, row.name == reference);
+            assert_eq!(row.n_contigs, agc.n_ctg(&row.name).unwrap() as usize);
+
+            let total: u64 = agc
+                .list_ctg(Some(&row.name))
+                .unwrap()
+                .iter()
+                .map(|ctg| agc.get_ctg_len(Some(&row.name), ctg).unwrap() as u64)
+                .sum();
+            assert_eq!(row.total_length, total);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Tar export of per-sample FASTA files, enabled by the `tar` feature
+//!
+//! Only the small part of the ustar format needed to write regular files is
+//! implemented, so the feature has no dependency.
+
+use std::io::{self, Write};
+
+use crate::fasta::sample_file_names;
+use crate::{AgcError, AgcFile, LineEnding};
+
+/// Tar block size; headers and padded file data are whole blocks
+const BLOCK: usize = 512;
+
+/// Largest size the 11 octal digits of a ustar header can hold; larger
+/// sizes use the GNU base-256 encoding
+const MAX_OCTAL_SIZE: u64 = (1 << 33) - 1;
+
+/// Write `value` as zero-padded octal filling `field` but its last byte,
+/// which stays NUL
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let octal = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(octal.as_bytes());
+}
+
+/// Bytes `FastaWriter::write_record` writes for a record, without writing it
+fn record_len(
+    header_len: usize,
+    seq_len: usize,
+    line_width: usize,
+    line_ending: LineEnding,
+) -> u64 {
+    let n_lines = if line_width == 0 {
+        usize::from(seq_len > 0)
+    } else {
+        seq_len.div_ceil(line_width)
+    };
+    let eol = line_ending.as_bytes().len();
+    (1 + header_len + eol + seq_len + n_lines * eol) as u64
+}
+
+/// Header block of a regular file entry
+fn file_header(name: &str, size: u64) -> Result<[u8; BLOCK], AgcError> {
+    if name.len() > 100 {
+        return Err(AgcError::InvalidArgument(format!(
+            "Tar entry name longer than 100 bytes: {}",
+            name
+        )));
+    }
+
+    let mut header = [0u8; BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    if size <= MAX_OCTAL_SIZE {
+        write_octal(&mut header[124..136], size);
+    } else {
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    write_octal(&mut header[136..148], 0);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is taken with its own field as spaces
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|&b| b as u32).sum();
+    write_octal(&mut header[148..155], checksum as u64);
+    Ok(header)
+}
+
+/// Counts the bytes passed through to the inner writer
+struct CountingWriter<'a, W> {
+    inner: &'a mut W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl AgcFile {
+    /// Write every sample as a FASTA file inside a tar archive
+    ///
+    /// *Requires the `tar` feature.* Each sample becomes an entry
+    /// `<sample>.fa`, with any `/` in the name replaced by `_`, holding
+    /// what `write_sample_fasta` writes for it with `\n` line endings. A
+    /// tar header must give the entry size up front, so that is computed
+    /// from the contig lengths first; the FASTA is then streamed one contig
+    /// at a time, so no whole genome is held in memory.
+    ///
+    /// # Arguments
+    /// * `out` - output to write the tar archive to
+    /// * `line_width` - bases per sequence line; 0 for unwrapped
+    ///
+    /// # Returns
+    /// Result indicating success, or `AgcError::InvalidArgument` for a
+    /// sample whose entry name exceeds the 100 bytes of a tar header or for
+    /// two samples, such as `a/b` and `a_b`, that would share an entry
+    pub fn export_tar<W: Write>(&self, out: &mut W, line_width: usize) -> Result<(), AgcError> {
+        let samples = self.list_sample()?;
+        let names = sample_file_names(samples.iter().map(String::as_str))?;
+        for (sample, name) in samples.iter().zip(names) {
+            let mut size = 0;
+            for info in self.contigs(Some(sample))? {
+                let info = info?;
+                let header_len = self.display_contig(&info.name).len();
+                size += record_len(header_len, info.length as usize, line_width, LineEnding::Lf);
+            }
+
+            out.write_all(&file_header(&name, size)?)?;
+            let mut counted = CountingWriter {
+                inner: out,
+                written: 0,
+            };
+            self.write_sample_fasta(sample, &mut counted, line_width, LineEnding::Lf)?;
+            if counted.written != size {
+                return Err(AgcError::FfiContract(format!(
+                    "Sample {} has {} bytes of FASTA, but its contig lengths give {}",
+                    sample, counted.written, size
+                )));
+            }
+
+            let padding = (BLOCK - (size as usize % BLOCK)) % BLOCK;
+            out.write_all(&[0; BLOCK][..padding])?;
+        }
+
+        // The archive ends with two empty blocks
+        out.write_all(&[0; 2 * BLOCK])?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FastaWriter;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    /// Read the regular file entries of a tar archive as (name, data)
+    fn read_tar(mut data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut entries = Vec::new();
+        while data.len() >= BLOCK && data[..BLOCK].iter().any(|&b| b != 0) {
+            let header = &data[..BLOCK];
+            let field = |range: std::ops::Range<usize>| {
+                let bytes = &header[range];
+                let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                String::from_utf8(bytes[..end].to_vec()).unwrap()
+            };
+            let checksum = u32::from_str_radix(field(148..155).trim(), 8).unwrap();
+            let sum: u32 = header
+                .iter()
+                .enumerate()
+                .map(|(i, &b)| {
+                    if (148..156).contains(&i) {
+                        b' ' as u32
+                    } else {
+                        b as u32
+                    }
+                })
+                .sum();
+            assert_eq!(checksum, sum, "Header checksum");
+            assert_eq!(&header[257..263], b"ustar\0");
+
+            let size = usize::from_str_radix(&field(124..136), 8).unwrap();
+            let padded = size.div_ceil(BLOCK) * BLOCK;
+            entries.push((field(0..100), data[BLOCK..BLOCK + size].to_vec()));
+            data = &data[BLOCK + padded..];
+        }
+        assert_eq!(
+            data,
+            [0; 2 * BLOCK],
+            "Archive should end in two empty blocks"
+        );
+        entries
+    }
+
+    #[test]
+    fn test_record_len() {
+        for seq in [&b""[..], b"ACG", b"ACGTACGTAC"] {
+            for line_width in [0, 1, 3, 4, 20] {
+                for line_ending in [LineEnding::Lf, LineEnding::CrLf] {
+                    let mut writer =
+                        FastaWriter::new(Vec::new(), line_width).line_ending(line_ending);
+                    writer.write_record("chr1 x", seq).unwrap();
+                    let written = writer.into_inner().unwrap().len() as u64;
+                    assert_eq!(record_len(6, seq.len(), line_width, line_ending), written);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_file_header() {
+        let header = file_header("ref.fa", 1000).unwrap();
+        assert_eq!(&header[..7], b"ref.fa\0");
+        assert_eq!(&header[124..136], b"00000001750\0");
+        assert_eq!(header[156], b'0');
+
+        let large = file_header("big.fa", 1 << 34).unwrap();
+        assert_eq!(large[124], 0x80);
+        assert_eq!(&large[128..136], &(1u64 << 34).to_be_bytes());
+
+        assert!(matches!(
+            file_header(&"x".repeat(101), 0),
+            Err(AgcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_export_tar() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        for line_width in [0, 7, 60] {
+            let mut tar = Vec::new();
+            agc.export_tar(&mut tar, line_width)
+                .expect("Failed to export tar");
+            assert_eq!(tar.len() % BLOCK, 0);
+
+            let entries = read_tar(&tar);
+            let samples = agc.list_sample().unwrap();
+            assert_eq!(entries.len(), samples.len());
+            for ((name, data), sample) in entries.iter().zip(&samples) {
+                let mut expected = Vec::new();
+                agc.write_sample_fasta(sample, &mut expected, line_width, LineEnding::Lf)
+                    .unwrap();
+                assert_eq!(name, &format!("{}.fa", sample));
+                assert_eq!(data, &expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::seq::reverse_complement;
+use crate::{AgcError, AgcFile};
+
+/// Telomeric repeat unit of vertebrates, read on the forward strand at
+/// the end of a chromosome arm
+pub const VERTEBRATE_TELOMERE: &[u8] = b"TTAGGG";
+
+/// Bases read from each end of a contig when looking for a telomere
+const TELOMERE_SCAN_LEN: i32 = 100_000;
+
+/// Fewest tandem copies of the motif for an end to count as capped
+const TELOMERE_MIN_COPIES: usize = 10;
+
+/// Telomeric repeats found at the ends of one contig
+///
+/// Returned by `AgcFile::telomere_report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelomereHit {
+    /// Contig name
+    pub contig: String,
+    /// Bases of tandem repeat starting at the first base
+    pub head_len: i32,
+    /// Bases of tandem repeat ending at the last base
+    pub tail_len: i32,
+    /// Whether the head repeat holds at least ten copies of the motif
+    pub head_capped: bool,
+    /// Whether the tail repeat holds at least ten copies of the motif
+    pub tail_capped: bool,
+}
+
+/// Length of the longest prefix of `seq` that is a tandem repeat of one
+/// of `units`, in any rotation and either case
+fn tandem_prefix_len(seq: &[u8], units: &[Vec<u8>]) -> usize {
+    let mut longest = 0;
+    for unit in units {
+        for phase in 0..unit.len() {
+            let len = seq
+                .iter()
+                .enumerate()
+                .take_while(|&(i, b)| b.eq_ignore_ascii_case(&unit[(i + phase) % unit.len()]))
+                .count();
+            longest = longest.max(len);
+        }
+    }
+    longest
+}
+
+/// Measure the repeats of `motif` or its reverse complement at the ends
+/// of a contig, given the bases read from its head and tail
+fn telomere_hit(contig: &str, head: &[u8], tail: &[u8], motif: &[u8]) -> TelomereHit {
+    let mut revcomp = motif.to_vec();
+    reverse_complement(&mut revcomp);
+    let units = [motif.to_vec(), revcomp];
+    let head_len = tandem_prefix_len(head, &units);
+
+    // The tail, read backwards, is a prefix of the reversed units
+    let reversed: Vec<Vec<u8>> = units
+        .iter()
+        .map(|u| u.iter().rev().copied().collect())
+        .collect();
+    let tail: Vec<u8> = tail.iter().rev().copied().collect();
+    let tail_len = tandem_prefix_len(&tail, &reversed);
+
+    let min_len = TELOMERE_MIN_COPIES * motif.len();
+    TelomereHit {
+        contig: contig.to_string(),
+        head_len: head_len as i32,
+        tail_len: tail_len as i32,
+        head_capped: head_len >= min_len,
+        tail_capped: tail_len >= min_len,
+    }
+}
+
+impl AgcFile {
+    /// Look for telomeric repeats at both ends of each contig of a sample
+    ///
+    /// An end is scanned for an exact tandem repeat of `motif` or its
+    /// reverse complement, in any rotation and either case, reaching the
+    /// end of the contig; a single mismatch ends the repeat. Only the
+    /// first and last 100 kb of a contig are read, which bounds the
+    /// lengths reported. For T2T-style completeness checks, an end is
+    /// capped when the repeat holds at least ten copies of the motif.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `motif` - telomeric repeat unit, such as `VERTEBRATE_TELOMERE`
+    ///
+    /// # Returns
+    /// Result containing one hit per contig, in listing order
+    pub fn telomere_report(
+        &self,
+        sample: &str,
+        motif: &[u8],
+    ) -> Result<Vec<TelomereHit>, AgcError> {
+        if motif.is_empty() {
+            return Err(AgcError::InvalidArgument(
+                "motif must not be empty".to_string(),
+            ));
+        }
+
+        let mut hits = Vec::new();
+        for info in self.contigs(Some(sample))? {
+            let info = info?;
+            let scan = info.length.min(TELOMERE_SCAN_LEN);
+            // Read in streaming chunks, which respect `max_fetch_len`
+            let read = |start, end| -> Result<Vec<u8>, AgcError> {
+                let chunks = self.map_seq(
+                    Some(sample),
+                    &info.name,
+                    start,
+                    end,
+                    self.stream_chunk_len(),
+                    <[u8]>::to_vec,
+                )?;
+                Ok(chunks.concat())
+            };
+            let head = read(0, scan)?;
+            let tail = read(info.length - scan, info.length)?;
+            hits.push(telomere_hit(&info.name, &head, &tail, motif));
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_tandem_prefix_len() {
+        let units = [b"TTAGGG".to_vec()];
+        assert_eq!(tandem_prefix_len(b"TTAGGGTTAGGGACGT", &units), 12);
+        // Any rotation, either case
+        assert_eq!(tandem_prefix_len(b"gggttaGGGTTAGGA", &units), 14);
+        assert_eq!(tandem_prefix_len(b"ACGT", &units), 1);
+        assert_eq!(tandem_prefix_len(b"", &units), 0);
+    }
+
+    #[test]
+    fn test_telomere_hit() {
+        let telomere = VERTEBRATE_TELOMERE.repeat(12);
+        let mut contig = b"ACGTACGGATCCA".repeat(20);
+        contig.extend_from_slice(&telomere);
+
+        let hit = telomere_hit("chr1", &contig, &contig, VERTEBRATE_TELOMERE);
+        assert!(!hit.head_capped);
+        assert!(hit.tail_capped);
+        assert_eq!(hit.tail_len, telomere.len() as i32);
+        assert!(hit.head_len < 6);
+
+        // The other arm carries the reverse complement, CCCTAA
+        let mut other = b"CCCTAA".repeat(10);
+        other.extend_from_slice(&contig);
+        let hit = telomere_hit("chr2", &other, &other, VERTEBRATE_TELOMERE);
+        assert!(hit.head_capped && hit.tail_capped);
+        assert_eq!(hit.head_len, 60);
+
+        // Too few copies do not cap an end
+        let short = VERTEBRATE_TELOMERE.repeat(3);
+        assert!(!telomere_hit("chr3", &short, &short, VERTEBRATE_TELOMERE).tail_capped);
+    }
+
+    #[test]
+    fn test_telomere_report() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+
+        let hits = agc.telomere_report(&sample, VERTEBRATE_TELOMERE).unwrap();
+        assert_eq!(hits.len(), contigs.len());
+        for (hit, contig) in hits.iter().zip(&contigs) {
+            let len = agc.get_ctg_len(Some(&sample), contig).unwrap();
+            assert_eq!(&hit.contig, contig);
+            assert!(hit.head_len <= len && hit.tail_len <= len);
+        }
+        assert!(agc.telomere_report(&sample, b"").is_err());
+    }
+}
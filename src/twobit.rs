@@ -0,0 +1,122 @@
+//! 2-bit packed nucleotide sequences
+//!
+//! Bases are packed four per byte as A=00, C=01, G=10, T=11, first base in
+//! the most significant bits, as in UCSC `.2bit` files. When the base count
+//! is not a multiple of four, the low bits of the last byte are zero.
+
+use crate::{AgcError, AgcFile};
+
+/// 2-bit code of a base, or None for N and other ambiguity codes
+fn base_code(base: u8) -> Option<u8> {
+    match base {
+        b'A' | b'a' => Some(0b00),
+        b'C' | b'c' => Some(0b01),
+        b'G' | b'g' => Some(0b10),
+        b'T' | b't' => Some(0b11),
+        _ => None,
+    }
+}
+
+/// Pack A/C/G/T bases (either case) 2 bits per base
+///
+/// # Returns
+/// Result containing the packed bytes, or `AgcError::InvalidBase` at the
+/// first base other than A, C, G or T
+pub(crate) fn pack_2bit(seq: &[u8]) -> Result<Vec<u8>, AgcError> {
+    let mut packed = vec![0u8; seq.len().div_ceil(4)];
+    for (position, &base) in seq.iter().enumerate() {
+        let code = base_code(base).ok_or(AgcError::InvalidBase { position, base })?;
+        packed[position / 4] |= code << (6 - 2 * (position % 4));
+    }
+    Ok(packed)
+}
+
+/// Unpack 2-bit packed sequence into uppercase bases
+///
+/// # Arguments
+/// * `packed` - packed bytes
+/// * `n_bases` - number of bases, at most `4 * packed.len()`
+pub fn unpack_2bit(packed: &[u8], n_bases: usize) -> Vec<u8> {
+    (0..n_bases.min(4 * packed.len()))
+        .map(|i| b"ACGT"[((packed[i / 4] >> (6 - 2 * (i % 4))) & 0b11) as usize])
+        .collect()
+}
+
+impl AgcFile {
+    /// Get contig sequence packed 2 bits per base
+    ///
+    /// Soft-masking is lost, since lowercase bases pack like uppercase ones.
+    /// Regions containing N or other ambiguity codes cannot be packed.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `start` - start offset
+    /// * `end` - end offset
+    ///
+    /// # Returns
+    /// Result containing the packed bytes and the number of bases, or
+    /// `AgcError::InvalidBase` if a base is not A, C, G or T
+    pub fn get_ctg_seq_2bit(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<(Vec<u8>, usize), AgcError> {
+        let seq = self.ctg_seq_bytes(sample, name, start, end)?;
+        Ok((pack_2bit(&seq)?, seq.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_pack_2bit_layout() {
+        assert_eq!(pack_2bit(b"ACGT").unwrap(), vec![0b00_01_10_11]);
+        assert_eq!(pack_2bit(b"TGc").unwrap(), vec![0b11_10_01_00]);
+        assert!(matches!(
+            pack_2bit(b"ACNT"),
+            Err(AgcError::InvalidBase {
+                position: 2,
+                base: b'N'
+            })
+        ));
+    }
+
+    #[test]
+    fn test_2bit_round_trip() {
+        let seq = b"GATTACACATTAG";
+        let packed = pack_2bit(seq).unwrap();
+        assert_eq!(packed.len(), 4);
+        assert_eq!(unpack_2bit(&packed, seq.len()), seq);
+    }
+
+    #[test]
+    fn test_get_ctg_seq_2bit() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        let seq = agc
+            .get_ctg_seq(Some(&sample), &contig, 0, len)
+            .unwrap()
+            .to_ascii_uppercase();
+
+        // Take the longest all-ACGT prefix as the region
+        let end = seq.bytes().take_while(|&b| base_code(b).is_some()).count();
+        assert!(end > 0, "Fixture contig starts with an ambiguous base");
+
+        let (packed, n) = agc
+            .get_ctg_seq_2bit(Some(&sample), &contig, 0, end as i32)
+            .expect("Failed to pack sequence");
+        assert_eq!(n, end);
+        assert_eq!(unpack_2bit(&packed, n), seq.as_bytes()[..end]);
+    }
+}
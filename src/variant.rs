@@ -0,0 +1,224 @@
+use crate::{check_range, clamp_range, AgcError, AgcFile};
+
+/// Sequence around a variant: left flank, reference allele and right flank
+///
+/// Coordinates are 0-based and half-open, after clamping to the contig, so
+/// `left` covers `[left_start, ref_start)`, `reference` covers
+/// `[ref_start, ref_end)` and `right` covers `[ref_end, right_end)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantContext {
+    /// Bases before the reference allele
+    pub left: Vec<u8>,
+    /// Reference allele bases
+    pub reference: Vec<u8>,
+    /// Bases after the reference allele
+    pub right: Vec<u8>,
+    /// Start of the left flank
+    pub left_start: i32,
+    /// Start of the reference allele
+    pub ref_start: i32,
+    /// End of the reference allele
+    pub ref_end: i32,
+    /// End of the right flank
+    pub right_end: i32,
+}
+
+/// `(offset + i, reference[i], query[i])` wherever the two differ, ignoring case
+fn base_differences(reference: &[u8], query: &[u8], offset: i32) -> Vec<(i32, u8, u8)> {
+    reference
+        .iter()
+        .zip(query)
+        .enumerate()
+        .filter(|(_, (r, q))| !r.eq_ignore_ascii_case(q))
+        .map(|(i, (&r, &q))| (offset + i as i32, r, q))
+        .collect()
+}
+
+impl AgcFile {
+    /// Find the positions where two samples differ over one span of a contig
+    ///
+    /// A lightweight SNP call at a locus whose coordinates line up between
+    /// the samples: the same `[start, end)` is fetched from both and
+    /// compared base by base. Case is ignored, so soft-masking alone is not
+    /// a difference; bases are reported as stored. Insertions and deletions
+    /// are not detected and shift every base after them.
+    ///
+    /// # Arguments
+    /// * `contig` - contig name
+    /// * `start` - start of the span
+    /// * `end` - exclusive end of the span
+    /// * `ref_sample` - sample to compare against
+    /// * `query_sample` - sample compared
+    ///
+    /// # Returns
+    /// Result containing `(position, ref_base, query_base)` per difference,
+    /// in order, or `AgcError::InvalidRange` if the span is empty or does
+    /// not fit in the contig of either sample
+    pub fn region_variants(
+        &self,
+        contig: &str,
+        start: i32,
+        end: i32,
+        ref_sample: &str,
+        query_sample: &str,
+    ) -> Result<Vec<(i32, u8, u8)>, AgcError> {
+        for sample in [ref_sample, query_sample] {
+            let len = self.ctg_len(Some(sample), contig)?;
+            check_range(contig, start as i64, end as i64, len)?;
+        }
+        let reference = self.ctg_seq_bytes(Some(ref_sample), contig, start, end)?;
+        let query = self.ctg_seq_bytes(Some(query_sample), contig, start, end)?;
+        Ok(base_differences(&reference, &query, start))
+    }
+
+    /// Get the flanking sequence and reference allele around a variant
+    ///
+    /// The flanks and the allele are clamped to the contig, so near a contig
+    /// end a flank may be shorter than `flank`; the coordinates in the result
+    /// give the spans actually used. The whole span is fetched at once.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    /// * `pos` - 0-based start of the reference allele
+    /// * `ref_len` - length of the reference allele
+    /// * `flank` - bases wanted on each side
+    ///
+    /// # Returns
+    /// Result containing the context, or `AgcError::InvalidRange` if `pos`
+    /// lies outside the contig
+    pub fn variant_context(
+        &self,
+        sample: Option<&str>,
+        name: &str,
+        pos: i32,
+        ref_len: i32,
+        flank: i32,
+    ) -> Result<VariantContext, AgcError> {
+        if ref_len < 0 || flank < 0 {
+            return Err(AgcError::InvalidArgument(format!(
+                "reference length {} and flank {} must not be negative",
+                ref_len, flank
+            )));
+        }
+
+        let len = self.ctg_len(sample, name)?;
+        if pos < 0 || pos > len {
+            return Err(AgcError::InvalidRange {
+                name: name.to_string(),
+                start: pos as i64,
+                end: pos as i64 + ref_len as i64,
+                len,
+            });
+        }
+
+        let (ref_start, ref_end) = clamp_range(pos, pos.saturating_add(ref_len), len);
+        let (left_start, _) = clamp_range(pos.saturating_sub(flank), ref_start, len);
+        let (_, right_end) = clamp_range(ref_end, ref_end.saturating_add(flank), len);
+
+        let mut left = self.ctg_seq_bytes(sample, name, left_start, right_end)?;
+        let mut reference = left.split_off((ref_start - left_start) as usize);
+        let right = reference.split_off((ref_end - ref_start) as usize);
+
+        Ok(VariantContext {
+            left,
+            reference,
+            right,
+            left_start,
+            ref_start,
+            ref_end,
+            right_end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_base_differences() {
+        assert_eq!(
+            base_differences(b"ACGTACGT", b"ACGTTCGT", 100),
+            [(104, b'A', b'T')]
+        );
+        assert_eq!(base_differences(b"ACGT", b"acgt", 0), []);
+        assert_eq!(
+            base_differences(b"ACGT", b"TCGA", 0),
+            [(0, b'A', b'T'), (3, b'T', b'A')]
+        );
+    }
+
+    #[test]
+    fn test_region_variants() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let reference = agc.reference_sample().unwrap();
+        for contig in agc.list_ctg(Some(&reference)).unwrap() {
+            let len = agc.get_ctg_len(Some(&reference), &contig).unwrap();
+            assert_eq!(
+                agc.region_variants(&contig, 0, len, &reference, &reference)
+                    .unwrap(),
+                []
+            );
+
+            for sample in agc.list_sample().unwrap() {
+                let end = match agc.common_length(&contig, &[&reference, &sample]) {
+                    Ok(end) if end > 1 => end,
+                    _ => continue,
+                };
+                let variants = agc
+                    .region_variants(&contig, 1, end, &reference, &sample)
+                    .unwrap();
+                let ref_seq = agc
+                    .ctg_seq_bytes(Some(&reference), &contig, 1, end)
+                    .unwrap();
+                let query = agc.ctg_seq_bytes(Some(&sample), &contig, 1, end).unwrap();
+                assert_eq!(variants, base_differences(&ref_seq, &query, 1));
+
+                assert!(matches!(
+                    agc.region_variants(&contig, 0, end.max(len) + 1, &reference, &sample),
+                    Err(AgcError::InvalidRange { .. })
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_variant_context_truncated_left_flank() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc
+            .reference_sample()
+            .expect("Failed to get reference sample");
+        let contig = agc.list_ctg(Some(&sample)).unwrap().remove(0);
+        let len = agc.get_ctg_len(Some(&sample), &contig).unwrap();
+        let seq = |start, end| {
+            agc.get_ctg_seq(Some(&sample), &contig, start, end)
+                .unwrap()
+                .into_bytes()
+        };
+
+        let ctx = agc
+            .variant_context(Some(&sample), &contig, 2, 1, 5)
+            .expect("Failed to get variant context");
+        assert_eq!(
+            (ctx.left_start, ctx.ref_start, ctx.ref_end, ctx.right_end),
+            (0, 2, 3, 8)
+        );
+        assert_eq!(ctx.left, seq(0, 2));
+        assert_eq!(ctx.reference, seq(2, 3));
+        assert_eq!(ctx.right, seq(3, 8));
+
+        let ctx = agc
+            .variant_context(Some(&sample), &contig, len - 1, 1, 5)
+            .unwrap();
+        assert_eq!(ctx.right_end, len);
+        assert!(ctx.right.is_empty());
+        assert_eq!(ctx.left.len(), 5);
+
+        assert!(agc
+            .variant_context(Some(&sample), &contig, len + 1, 1, 5)
+            .is_err());
+    }
+}
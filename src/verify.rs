@@ -0,0 +1,776 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::Path;
+
+use crate::fasta::FastaWriter;
+use crate::md5::{md5_hex, Md5};
+use crate::policy::Collector;
+use crate::{AgcError, AgcFile, ErrorPolicy};
+
+/// Bases per line in FASTA written by `extract_sample_verified`
+const VERIFIED_LINE_WIDTH: usize = 60;
+
+/// Header line of a sequence dictionary written by `write_sequence_dict`
+const SEQUENCE_DICT_HEADER: &str = "@HD\tVN:1.6";
+
+/// Feed bases to an MD5 the way CRAM and SAM `M5` tags require
+///
+/// Only printable, non-space ASCII counts, uppercased, so soft-masking and
+/// stray whitespace do not change the checksum.
+fn update_normalized(md5: &mut Md5, seq: &[u8]) {
+    let normalized: Vec<u8> = seq
+        .iter()
+        .filter(|b| b.is_ascii_graphic())
+        .map(u8::to_ascii_uppercase)
+        .collect();
+    md5.update(&normalized);
+}
+
+/// A contig whose checksum differs from the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// Contig name
+    pub contig: String,
+    /// MD5 from the manifest
+    pub expected: String,
+    /// MD5 of the extracted sequence
+    pub actual: String,
+}
+
+/// Outcome of a verified extraction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Contigs whose checksum matched the manifest
+    pub matched: Vec<String>,
+    /// Contigs whose checksum did not match
+    pub mismatches: Vec<ChecksumMismatch>,
+    /// Contigs written without a manifest entry to check against
+    pub unlisted: Vec<String>,
+    /// Manifest entries naming contigs the sample does not have
+    pub missing: Vec<String>,
+}
+
+impl VerifyReport {
+    /// Whether every contig matched and every manifest entry was found
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// How a FASTA record compares with the archive's contig of that name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContigMatch {
+    /// The bases are identical
+    Match,
+    /// The lengths differ, so the bases were not compared
+    LengthDiffers {
+        /// Bases in the FASTA record
+        fasta: usize,
+        /// Bases in the archive
+        archive: i32,
+    },
+    /// Same length, but the bases differ
+    SequenceDiffers {
+        /// 0-based position of the first differing base
+        first_mismatch: usize,
+    },
+    /// The sample has no contig of this name
+    NotInArchive,
+}
+
+/// Outcome of comparing one FASTA record with the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContigComparison {
+    /// Record name, the header up to the first whitespace
+    pub name: String,
+    /// How the record compares
+    pub outcome: ContigMatch,
+}
+
+/// Outcome of `AgcFile::compare_to_fasta`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompareReport {
+    /// Every FASTA record, in file order
+    pub contigs: Vec<ContigComparison>,
+    /// Contigs of the sample without a FASTA record, in listing order
+    pub not_in_fasta: Vec<String>,
+}
+
+impl CompareReport {
+    /// Whether every record matched and every contig had a record
+    pub fn is_ok(&self) -> bool {
+        self.not_in_fasta.is_empty() && self.contigs.iter().all(|c| c.outcome == ContigMatch::Match)
+    }
+}
+
+/// How one `(sample, contig)` compares between two archives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentStatus {
+    /// Both archives store the same bytes
+    Identical,
+    /// Both archives have the contig, with different bytes
+    Differs {
+        /// Length in the first archive
+        len_a: i32,
+        /// Length in the second archive
+        len_b: i32,
+    },
+    /// Only the first archive has the contig
+    OnlyInA,
+    /// Only the second archive has the contig
+    OnlyInB,
+}
+
+/// One entry of `content_diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentDiff {
+    /// Sample name
+    pub sample: String,
+    /// Contig name
+    pub contig: String,
+    /// How the contig compares
+    pub status: ContentStatus,
+}
+
+/// Compare the sequence stored by two archives, contig by contig
+///
+/// Every `(sample, contig)` of either archive gets an entry: first those
+/// of `a` in its listing order, then those only `b` has. Contigs of equal
+/// length are streamed and compared by MD5 of their exact bytes, so case
+/// counts; contigs of different length are reported without reading
+/// them. Meant for checking that e.g. a recompressed archive stores the
+/// same sequence.
+///
+/// # Arguments
+/// * `a` - first archive
+/// * `b` - second archive
+///
+/// # Returns
+/// Result containing one entry per contig
+pub fn content_diff(a: &AgcFile, b: &AgcFile) -> Result<Vec<ContentDiff>, AgcError> {
+    let entry = |sample: &str, contig: &str, status| ContentDiff {
+        sample: sample.to_string(),
+        contig: contig.to_string(),
+        status,
+    };
+    let samples_b = b.list_sample()?;
+    let mut diffs = Vec::new();
+    let mut shared: HashSet<(String, String)> = HashSet::new();
+
+    for sample in a.list_sample()? {
+        let contigs_b: HashSet<String> = if samples_b.contains(&sample) {
+            b.list_ctg(Some(&sample))?.into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+        for info in a.contigs(Some(&sample))? {
+            let info = info?;
+            if !contigs_b.contains(&info.name) {
+                diffs.push(entry(&sample, &info.name, ContentStatus::OnlyInA));
+                continue;
+            }
+            let len_b = b.ctg_len(Some(&sample), &info.name)?;
+            let identical = info.length == len_b
+                && a.content_md5(&sample, &info.name)? == b.content_md5(&sample, &info.name)?;
+            let status = if identical {
+                ContentStatus::Identical
+            } else {
+                ContentStatus::Differs {
+                    len_a: info.length,
+                    len_b,
+                }
+            };
+            diffs.push(entry(&sample, &info.name, status));
+            shared.insert((sample.clone(), info.name));
+        }
+    }
+
+    for sample in samples_b {
+        for contig in b.list_ctg(Some(&sample))? {
+            if !shared.contains(&(sample.clone(), contig.clone())) {
+                diffs.push(entry(&sample, &contig, ContentStatus::OnlyInB));
+            }
+        }
+    }
+    Ok(diffs)
+}
+
+/// One `@SQ` line of a sequence dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceDictEntry {
+    /// Contig name (`SN`)
+    pub name: String,
+    /// Contig length in bases (`LN`)
+    pub length: i32,
+    /// MD5 of the normalized bases in lowercase hex (`M5`)
+    pub m5: String,
+}
+
+/// Names, lengths and checksums of a sample's contigs, in listing order
+///
+/// Returned by `AgcFile::reference_sequence_dict`; holds what an aligner
+/// or `.dict` file needs to describe a reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequenceDict {
+    entries: Vec<SequenceDictEntry>,
+}
+
+impl SequenceDict {
+    /// All entries, in listing order
+    pub fn entries(&self) -> &[SequenceDictEntry] {
+        &self.entries
+    }
+
+    /// Number of contigs
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the dictionary has no contigs
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up a contig's entry by name
+    pub fn get(&self, name: &str) -> Option<&SequenceDictEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Write the dictionary in Picard/GATK `.dict` format
+    ///
+    /// # Arguments
+    /// * `out` - output to write to
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn write_dict<W: Write>(&self, out: &mut W) -> Result<(), AgcError> {
+        writeln!(out, "{}", SEQUENCE_DICT_HEADER)?;
+        for entry in &self.entries {
+            writeln!(
+                out,
+                "@SQ\tSN:{}\tLN:{}\tM5:{}",
+                entry.name, entry.length, entry.m5
+            )?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl AgcFile {
+    /// Extract a sample to FASTA, checking each contig against an MD5 manifest
+    ///
+    /// The checksum is taken over each contig's sequence bytes as returned
+    /// by AGC, before line wrapping. A mismatch is recorded in the report
+    /// and the export carries on, so one bad contig does not cost a long
+    /// run; only I/O and AGC errors abort it. Lines hold 60 bases, and
+    /// headers use the display names of `with_chr_naming`, as
+    /// `write_sample_fasta` does.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `path` - output file path
+    /// * `manifest` - archive contig name to expected MD5 in hex, in either
+    ///   case
+    ///
+    /// # Returns
+    /// Result containing the per-contig verification report
+    pub fn extract_sample_verified(
+        &self,
+        sample: &str,
+        path: impl AsRef<Path>,
+        manifest: &HashMap<String, String>,
+    ) -> Result<VerifyReport, AgcError> {
+        let (report, _) =
+            self.extract_sample_verified_with(sample, path, manifest, ErrorPolicy::FailFast)?;
+        Ok(report)
+    }
+
+    /// Extract a sample to FASTA against an MD5 manifest, handling errors
+    /// per `policy`
+    ///
+    /// Under `ErrorPolicy::FailFast` this is `extract_sample_verified`.
+    /// Under `ErrorPolicy::Collect` a contig that cannot be fetched is left
+    /// out of the file and of the report, and its error kept; check the
+    /// errors as well as `VerifyReport::is_ok`. Errors creating or writing
+    /// the file abort under either policy.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `path` - output file path
+    /// * `manifest` - archive contig name to expected MD5 in hex, in either
+    ///   case
+    /// * `policy` - whether to stop at or collect per-contig errors
+    ///
+    /// # Returns
+    /// Result containing the report and the errors collected, in contig
+    /// order
+    pub fn extract_sample_verified_with(
+        &self,
+        sample: &str,
+        path: impl AsRef<Path>,
+        manifest: &HashMap<String, String>,
+        policy: ErrorPolicy,
+    ) -> Result<(VerifyReport, Vec<AgcError>), AgcError> {
+        let contigs = self.list_ctg(Some(sample))?;
+        let mut out = BufWriter::new(File::create(path)?);
+        let mut writer = FastaWriter::new(&mut out, VERIFIED_LINE_WIDTH);
+        let mut report = VerifyReport::default();
+        let mut fetched = Collector::<()>::new(policy, 0);
+
+        for contig in &contigs {
+            let seq = match self.ctg_full_bytes(Some(sample), contig) {
+                Ok(seq) => seq,
+                Err(e) => {
+                    fetched.push(Err(e))?;
+                    continue;
+                }
+            };
+            writer.write_record(self.display_contig(contig), &seq)?;
+
+            match manifest.get(contig) {
+                Some(expected) => {
+                    let actual = md5_hex(&seq);
+                    if actual.eq_ignore_ascii_case(expected.trim()) {
+                        report.matched.push(contig.clone());
+                    } else {
+                        report.mismatches.push(ChecksumMismatch {
+                            contig: contig.clone(),
+                            expected: expected.clone(),
+                            actual,
+                        });
+                    }
+                }
+                None => report.unlisted.push(contig.clone()),
+            }
+        }
+        writer.into_inner()?;
+        out.flush()?;
+
+        report.missing = manifest
+            .keys()
+            .filter(|name| !contigs.contains(name))
+            .cloned()
+            .collect();
+        report.missing.sort();
+        Ok((report, fetched.finish().errors))
+    }
+
+    /// Check a sample against the FASTA it was built from
+    ///
+    /// Each record is matched to the sample's contig of the same name, the
+    /// header up to its first whitespace, and its bases compared exactly,
+    /// so a change of case counts as a difference. The archive side is
+    /// streamed; one record at a time is held in memory. Line breaks and
+    /// trailing whitespace in the FASTA are ignored.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `fasta` - FASTA to compare with
+    ///
+    /// # Returns
+    /// Result containing the per-contig outcomes; `AgcError::InvalidArgument`
+    /// if sequence comes before the first header
+    pub fn compare_to_fasta<R: BufRead>(
+        &self,
+        sample: &str,
+        fasta: R,
+    ) -> Result<CompareReport, AgcError> {
+        let contigs = self.list_ctg(Some(sample))?;
+        let known: HashSet<&str> = contigs.iter().map(String::as_str).collect();
+        let mut seen = HashSet::new();
+        let mut report = CompareReport::default();
+        let mut record: Option<(String, Vec<u8>)> = None;
+
+        let mut finish = |record: Option<(String, Vec<u8>)>| -> Result<(), AgcError> {
+            if let Some((name, seq)) = record {
+                let outcome = if known.contains(name.as_str()) {
+                    seen.insert(name.clone());
+                    self.compare_contig(sample, &name, &seq)?
+                } else {
+                    ContigMatch::NotInArchive
+                };
+                report.contigs.push(ContigComparison { name, outcome });
+            }
+            Ok(())
+        };
+
+        for line in fasta.lines() {
+            let line = line?;
+            let line = line.trim_end();
+            if let Some(header) = line.strip_prefix('>') {
+                let name = header.split_whitespace().next().unwrap_or_default();
+                finish(record.replace((name.to_string(), Vec::new())))?;
+            } else if let Some((_, seq)) = &mut record {
+                seq.extend_from_slice(line.as_bytes());
+            } else if !line.is_empty() {
+                return Err(AgcError::InvalidArgument(
+                    "FASTA has sequence before the first header".to_string(),
+                ));
+            }
+        }
+        finish(record)?;
+
+        report.not_in_fasta = contigs.into_iter().filter(|c| !seen.contains(c)).collect();
+        Ok(report)
+    }
+
+    /// Compare bases with a contig, streaming the contig
+    fn compare_contig(
+        &self,
+        sample: &str,
+        name: &str,
+        seq: &[u8],
+    ) -> Result<ContigMatch, AgcError> {
+        let len = self.ctg_len(Some(sample), name)?;
+        if len as usize != seq.len() {
+            return Ok(ContigMatch::LengthDiffers {
+                fasta: seq.len(),
+                archive: len,
+            });
+        }
+
+        let mut offset = 0;
+        for chunk in self.seq_chunks(Some(sample), name, self.stream_chunk_len())? {
+            let chunk = chunk?;
+            let expected = &seq[offset..offset + chunk.len()];
+            if let Some(i) = chunk.iter().zip(expected).position(|(a, b)| a != b) {
+                return Ok(ContigMatch::SequenceDiffers {
+                    first_mismatch: offset + i,
+                });
+            }
+            offset += chunk.len();
+        }
+        Ok(ContigMatch::Match)
+    }
+
+    /// MD5 of a contig's exact bytes, streamed in chunks
+    fn content_md5(&self, sample: &str, name: &str) -> Result<[u8; 16], AgcError> {
+        let mut md5 = Md5::new();
+        for chunk in self.seq_chunks(Some(sample), name, self.stream_chunk_len())? {
+            md5.update(&chunk?);
+        }
+        Ok(md5.finalize())
+    }
+
+    /// Get the `M5` checksum of a contig, as used by SAM, CRAM and `.dict`
+    ///
+    /// The MD5 is taken over the bases uppercased with anything but
+    /// printable ASCII dropped. The contig is streamed in chunks.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name (None for unspecified)
+    /// * `name` - contig name
+    ///
+    /// # Returns
+    /// Result containing the checksum in lowercase hex
+    pub fn sequence_m5(&self, sample: Option<&str>, name: &str) -> Result<String, AgcError> {
+        let mut md5 = Md5::new();
+        for chunk in self.seq_chunks(sample, name, self.stream_chunk_len())? {
+            update_normalized(&mut md5, &chunk?);
+        }
+        Ok(md5.hex_digest())
+    }
+
+    /// Build the sequence dictionary of a sample
+    ///
+    /// Every contig is read once to compute its `M5`.
+    fn sequence_dict(&self, sample: &str) -> Result<SequenceDict, AgcError> {
+        let mut entries = Vec::new();
+        for info in self.contigs(Some(sample))? {
+            let info = info?;
+            let m5 = self.sequence_m5(Some(sample), &info.name)?;
+            entries.push(SequenceDictEntry {
+                name: info.name,
+                length: info.length,
+                m5,
+            });
+        }
+        Ok(SequenceDict { entries })
+    }
+
+    /// Get the sequence dictionary of the reference sample
+    ///
+    /// Names, lengths and `M5` checksums of the reference's contigs in one
+    /// call, for configuring aligners against the archive's reference.
+    /// Every reference contig is read once to compute its checksum.
+    ///
+    /// # Returns
+    /// Result containing the dictionary
+    pub fn reference_sequence_dict(&self) -> Result<SequenceDict, AgcError> {
+        let reference = self.reference_sample()?;
+        self.sequence_dict(&reference)
+    }
+
+    /// Write a Picard/GATK-style sequence dictionary for a sample
+    ///
+    /// The `.dict` holds an `@HD` header and one `@SQ` line per contig in
+    /// listing order, with `SN`, `LN` and `M5` tags. Every contig is read
+    /// once to compute its `M5`.
+    ///
+    /// # Arguments
+    /// * `sample` - sample name
+    /// * `out` - output to write to
+    ///
+    /// # Returns
+    /// Result indicating success or an error
+    pub fn write_sequence_dict<W: Write>(&self, sample: &str, out: &mut W) -> Result<(), AgcError> {
+        self.sequence_dict(sample)?.write_dict(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILE: &str = "test/data/input/test.agc";
+
+    #[test]
+    fn test_update_normalized() {
+        let mut md5 = Md5::new();
+        update_normalized(&mut md5, b"acgT N\n");
+        update_normalized(&mut md5, b"nn\tacgt");
+        assert_eq!(md5.hex_digest(), md5_hex(b"ACGTNNNACGT"));
+    }
+
+    #[test]
+    fn test_write_sequence_dict() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let mut out = Vec::new();
+        agc.write_sequence_dict(&sample, &mut out)
+            .expect("Failed to write dictionary");
+        let dict = String::from_utf8(out).unwrap();
+
+        let mut lines = dict.lines();
+        assert_eq!(lines.next(), Some(SEQUENCE_DICT_HEADER));
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+        for (line, contig) in lines.by_ref().zip(&contigs) {
+            let len = agc.get_ctg_len(Some(&sample), contig).unwrap();
+            let seq = agc.ctg_full_bytes(Some(&sample), contig).unwrap();
+            let m5 = md5_hex(&seq.to_ascii_uppercase());
+            assert_eq!(line, format!("@SQ\tSN:{}\tLN:{}\tM5:{}", contig, len, m5));
+        }
+        assert_eq!(dict.lines().count(), contigs.len() + 1);
+    }
+
+    #[test]
+    fn test_reference_sequence_dict() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let dict = agc
+            .reference_sequence_dict()
+            .expect("Failed to build dictionary");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+
+        assert_eq!(dict.len(), contigs.len());
+        for (entry, contig) in dict.entries().iter().zip(&contigs) {
+            assert_eq!(&entry.name, contig);
+            assert_eq!(
+                entry.length,
+                agc.get_ctg_len(Some(&sample), contig).unwrap()
+            );
+            assert_eq!(entry.m5, agc.sequence_m5(Some(&sample), contig).unwrap());
+        }
+        assert_eq!(dict.get(&contigs[0]), dict.entries().first());
+        assert!(dict.get("no_such_contig").is_none());
+
+        let (mut written, mut expected) = (Vec::new(), Vec::new());
+        dict.write_dict(&mut written).unwrap();
+        agc.write_sequence_dict(&sample, &mut expected).unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn test_compare_to_fasta() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+        let mut fasta = Vec::new();
+        agc.write_sample_fasta(&sample, &mut fasta, 7, crate::LineEnding::CrLf)
+            .unwrap();
+
+        let report = agc
+            .compare_to_fasta(&sample, fasta.as_slice())
+            .expect("Failed to compare");
+        assert!(report.is_ok());
+        let names: Vec<&str> = report.contigs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, contigs);
+
+        // Change one base of the first record, add a record the archive
+        // lacks and cut the second record short
+        let first = agc.ctg_full_bytes(Some(&sample), &contigs[0]).unwrap();
+        let mut changed = first.clone();
+        changed[3] = if changed[3] == b'A' { b'C' } else { b'A' };
+        let mut fasta = format!(">{} description\n", contigs[0]).into_bytes();
+        fasta.extend_from_slice(&changed);
+        fasta.extend_from_slice(b"\n>no_such_contig\nACGT\n");
+        if let Some(second) = contigs.get(1) {
+            fasta.extend_from_slice(format!(">{}\nACGT\n", second).as_bytes());
+        }
+
+        let report = agc.compare_to_fasta(&sample, fasta.as_slice()).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.contigs[0].outcome,
+            ContigMatch::SequenceDiffers { first_mismatch: 3 }
+        );
+        assert_eq!(report.contigs[1].name, "no_such_contig");
+        assert_eq!(report.contigs[1].outcome, ContigMatch::NotInArchive);
+        if let Some(second) = contigs.get(1) {
+            let len = agc.get_ctg_len(Some(&sample), second).unwrap();
+            assert_eq!(
+                report.contigs[2].outcome,
+                ContigMatch::LengthDiffers {
+                    fasta: 4,
+                    archive: len
+                }
+            );
+        }
+        assert_eq!(report.not_in_fasta, contigs[contigs.len().min(2)..]);
+
+        assert!(agc.compare_to_fasta(&sample, &b"ACGT\n>x\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_content_diff() {
+        let a = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let b = AgcFile::open(TEST_FILE, false).expect("Failed to open file");
+        let diffs = content_diff(&a, &b).expect("Failed to compare archives");
+
+        let inventory = a.inventory().unwrap();
+        assert_eq!(diffs.len(), inventory.len());
+        for (diff, (sample, contig, _)) in diffs.iter().zip(&inventory) {
+            assert_eq!((&diff.sample, &diff.contig), (sample, contig));
+            assert_eq!(diff.status, ContentStatus::Identical);
+        }
+    }
+
+    #[test]
+    fn test_extract_sample_verified() {
+        let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = agc.reference_sample().unwrap();
+        let contigs = agc.list_ctg(Some(&sample)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("verified.fa");
+
+        let mut manifest: HashMap<String, String> = contigs
+            .iter()
+            .map(|c| {
+                let seq = agc.ctg_full_bytes(Some(&sample), c).unwrap();
+                (c.clone(), md5_hex(&seq).to_uppercase())
+            })
+            .collect();
+        let report = agc
+            .extract_sample_verified(&sample, &path, &manifest)
+            .expect("Failed to extract");
+        assert!(report.is_ok());
+        assert_eq!(report.matched, contigs);
+
+        manifest.insert(contigs[0].clone(), "0".repeat(32));
+        manifest.insert("no_such_contig".to_string(), "0".repeat(32));
+        let report = agc
+            .extract_sample_verified(&sample, &path, &manifest)
+            .expect("Mismatches should not abort the export");
+        assert!(!report.is_ok());
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].contig, contigs[0]);
+        assert_eq!(report.matched.len(), contigs.len() - 1);
+        assert_eq!(report.missing, ["no_such_contig"]);
+
+        let mut expected = Vec::new();
+        agc.write_sample_fasta(&sample, &mut expected, 60, crate::LineEnding::Lf)
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_extract_sample_verified_display_names() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = plain.reference_sample().unwrap();
+        let contigs = plain.list_ctg(Some(&sample)).unwrap();
+        let table = crate::ChrNameTable::new(
+            contigs
+                .iter()
+                .map(|c| [c.clone(), format!("ens_{}", c), format!("gb_{}", c)])
+                .collect(),
+        );
+        let agc = AgcFile::open(TEST_FILE, true)
+            .unwrap()
+            .with_chr_naming_table(crate::ChrNaming::Ensembl, &table)
+            .unwrap();
+        // The manifest keeps the archive's own names
+        let manifest: HashMap<String, String> = contigs
+            .iter()
+            .map(|c| {
+                (
+                    c.clone(),
+                    md5_hex(&plain.ctg_full_bytes(Some(&sample), c).unwrap()),
+                )
+            })
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("verified.fa");
+
+        let report = agc
+            .extract_sample_verified(&sample, &path, &manifest)
+            .unwrap();
+        assert_eq!(report.matched, contigs);
+        let mut expected = Vec::new();
+        agc.write_sample_fasta(&sample, &mut expected, 60, crate::LineEnding::Lf)
+            .unwrap();
+        let written = std::fs::read(&path).unwrap();
+        assert_eq!(written, expected);
+        assert!(String::from_utf8(written).unwrap().starts_with(">ens_"));
+    }
+
+    #[test]
+    fn test_extract_sample_verified_with() {
+        let plain = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+        let sample = plain.reference_sample().unwrap();
+        let lengths: Vec<(String, i32)> = plain
+            .contigs(Some(&sample))
+            .unwrap()
+            .map(|info| info.map(|info| (info.name, info.length)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let manifest: HashMap<String, String> = lengths
+            .iter()
+            .map(|(c, _)| {
+                let seq = plain.ctg_full_bytes(Some(&sample), c).unwrap();
+                (c.clone(), md5_hex(&seq))
+            })
+            .collect();
+
+        // A fetch limit at the shortest contig makes every longer one fail
+        let shortest = lengths.iter().map(|(_, len)| *len).min().unwrap();
+        let agc = AgcFile::builder(TEST_FILE)
+            .prefetching(true)
+            .max_fetch_len(shortest as usize)
+            .open()
+            .unwrap();
+        let fits: Vec<String> = lengths
+            .iter()
+            .filter(|(_, len)| *len == shortest)
+            .map(|(c, _)| c.clone())
+            .collect();
+        assert!(fits.len() < lengths.len());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("verified.fa");
+
+        assert!(matches!(
+            agc.extract_sample_verified_with(&sample, &path, &manifest, ErrorPolicy::FailFast),
+            Err(AgcError::FetchTooLarge { .. })
+        ));
+
+        let (report, errors) = agc
+            .extract_sample_verified_with(&sample, &path, &manifest, ErrorPolicy::Collect)
+            .expect("Collect should not abort");
+        assert_eq!(report.matched, fits);
+        assert!(report.mismatches.is_empty() && report.missing.is_empty());
+        assert_eq!(errors.len(), lengths.len() - fits.len());
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.matches('>').count(), fits.len());
+    }
+}
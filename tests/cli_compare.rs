@@ -0,0 +1,28 @@
+//! Cross-checks against the `agc` command-line tool: every contig must
+//! decode the same through this crate as with `agc getctg`. Skipped when
+//! `agc` is not on `PATH`.
+
+#![cfg(feature = "cli-compare")]
+
+use std::io;
+
+use libagc_sys::{AgcError, AgcFile};
+
+const TEST_FILE: &str = "test/data/input/test.agc";
+
+#[test]
+fn decode_matches_agc_cli() {
+    let agc = AgcFile::open(TEST_FILE, true).expect("Failed to open file");
+    for sample in agc.list_sample().unwrap() {
+        for contig in agc.list_ctg(Some(&sample)).unwrap() {
+            match agc.decode_matches_cli(Some(&sample), &contig) {
+                Ok(matches) => assert!(matches, "{}@{} differs from agc getctg", contig, sample),
+                Err(AgcError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                    eprintln!("agc not found on PATH, skipping CLI comparison");
+                    return;
+                }
+                Err(e) => panic!("agc getctg {}@{}: {}", contig, sample, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! Compile-fail tests: borrowed iterators and handles must not outlive
+//! the `AgcFile` they read from.
+
+#[test]
+fn borrowed_views_cannot_outlive_archive() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let contigs;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        contigs = agc.contigs_longer_than(None, 10);
+    }
+    println!("{}", contigs.count());
+}
@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let contigs;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        contigs = agc.contigs(None).unwrap();
+    }
+    println!("{}", contigs.count());
+}
@@ -0,0 +1,10 @@
+use libagc_sys::AgcPool;
+
+fn main() {
+    let agc;
+    {
+        let pool = AgcPool::new("test/data/input/test.agc", false, 1).unwrap();
+        agc = pool.checkout().unwrap();
+    }
+    println!("{}", agc.n_sample());
+}
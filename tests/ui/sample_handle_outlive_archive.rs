@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let reference;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        reference = agc.reference_handle().unwrap();
+    }
+    println!("{}", reference.name());
+}
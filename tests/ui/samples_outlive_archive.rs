@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let samples;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        samples = agc.samples().unwrap();
+    }
+    println!("{}", samples.len());
+}
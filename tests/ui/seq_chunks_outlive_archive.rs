@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let chunks;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        chunks = agc.seq_chunks(None, "chr1", 100).unwrap();
+    }
+    println!("{}", chunks.count());
+}
@@ -0,0 +1,10 @@
+use libagc_sys::AgcFile;
+
+fn main() {
+    let windows;
+    {
+        let agc = AgcFile::open("test/data/input/test.agc", false).unwrap();
+        windows = agc.tiled_windows("ref", 100, 100);
+    }
+    println!("{}", windows.count());
+}